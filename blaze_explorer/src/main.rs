@@ -1,19 +1,14 @@
 use blaze_explorer_lib::app::{App, ExitResult};
 use blaze_explorer_lib::logging::initialize_logging;
 mod plugin_manifest;
-use libloading::Library;
-use plugin_manifest::fetch_plugins;
+use plugin_manifest::{collect_libs, fetch_plugins};
 use ratatui::crossterm::{
     ExecutableCommand,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
-use std::collections::HashMap;
-use std::fs;
 use std::process::Command;
-use std::rc::Rc;
 use std::{env::set_current_dir, io::stdout};
 use std::{error::Error, path::PathBuf};
-use tracing::info;
 
 fn bring_app_back(app: &mut App) {
     app.exit_status = None;
@@ -25,43 +20,21 @@ fn open_neovim(path: &PathBuf) -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-fn collect_libs() -> HashMap<String, Rc<Library>> {
-    let mut lib_map = HashMap::new();
-    let plugins_folder_location = "../blaze_plugins";
-    let paths = fs::read_dir(plugins_folder_location).unwrap();
-    for path in paths {
-        let lib_name = path
-            .unwrap()
-            .path()
-            .file_name()
-            .unwrap()
-            .to_str()
-            .unwrap()
-            .to_string();
-        let dll_path = PathBuf::from(format!(
-            "../blaze_plugins/{}/target/debug/{}.dll",
-            lib_name, lib_name
-        ));
-        match dll_path.exists() {
-            true => {
-                let lib = unsafe { Library::new(dll_path.to_str().unwrap()).unwrap() };
-                lib_map.insert(lib_name, Rc::new(lib));
-            }
-            false => {
-                info!("Plugin {} not found/not compiled", lib_name);
-            }
-        }
-    }
-    lib_map
-}
-
 fn main() -> Result<(), Box<dyn Error>> {
     initialize_logging()?;
-    let lib_map = collect_libs();
+    // The libraries must outlive the app, as the plugins' code lives inside of them
+    let lib_map;
     {
         let mut app = App::new().unwrap();
-        let plugins = fetch_plugins(&lib_map);
+        let keymap = app.load_user_config();
+        let (libs, mut plugin_errors) = collect_libs(&app.get_plugin_dirs());
+        lib_map = libs;
+        let (plugins, fetch_errors) = fetch_plugins(&lib_map, &keymap);
+        plugin_errors.extend(fetch_errors);
         app.attach_plugins(&plugins);
+        if !plugin_errors.is_empty() {
+            app.append_command_line_message(plugin_errors.join("\n"));
+        }
         let mut cold_start = true;
         loop {
             stdout().execute(EnterAlternateScreen)?;
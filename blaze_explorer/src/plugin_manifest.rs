@@ -1,32 +1,225 @@
-use libloading::{Error, Library, Symbol};
-use std::{collections::HashMap, rc::Rc};
+use libloading::{Library, Symbol};
+use std::env::consts::{DLL_PREFIX, DLL_SUFFIX};
+use std::ffi::{CStr, c_char};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::path::{Path, PathBuf};
+use std::{collections::HashMap, fs, rc::Rc};
 
-use ratatui::crossterm::event::KeyEvent;
+use tracing::{info, warn};
 
-use blaze_explorer_lib::{mode::Mode, plugin::Plugin};
+use blaze_explorer_lib::keymap_config::KeymapConfig;
+use blaze_explorer_lib::plugin::plugin_abi::{
+    API_VERSION_SYMBOL, BUILD_FINGERPRINT_SYMBOL, PLUGIN_API_VERSION, build_fingerprint,
+    check_compatibility,
+};
+use blaze_explorer_lib::plugin::{BindingsMap, Plugin};
 
-type BindingsMap = HashMap<(Mode, Vec<KeyEvent>), String>;
-fn collect_plugin(lib: &Library, custom_bindings: Option<BindingsMap>) -> Option<Box<dyn Plugin>> {
-    let custom_bindings = custom_bindings.unwrap_or_default();
-    let get_plugin: Result<Symbol<extern "Rust" fn(BindingsMap) -> Box<dyn Plugin>>, Error> =
-        unsafe { lib.get(b"get_plugin") };
-    match get_plugin {
-        Ok(plugin) => Some(plugin(custom_bindings)),
-        Err(err) => {
-            panic!("Failed to load plugin: {}", err);
+/// Get the plugin name from a library file name, e.g. `libblaze_flash.so` -> `blaze_flash`.
+fn get_plugin_name(file_name: &str) -> Option<String> {
+    let name = file_name
+        .strip_prefix(DLL_PREFIX)?
+        .strip_suffix(DLL_SUFFIX)?;
+    match name.is_empty() {
+        true => None,
+        false => Some(name.to_string()),
+    }
+}
+
+/// Find the plugin libraries in the given directory. A plugin is either a library placed
+/// directly in the directory, or a cargo project folder whose library was built in `target/release`
+/// or `target/debug`.
+pub fn find_plugin_libraries(plugins_dir: &Path) -> Vec<(String, PathBuf)> {
+    let entries = match fs::read_dir(plugins_dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            info!("Plugin directory {} not read: {}", plugins_dir.display(), e);
+            return Vec::new();
+        }
+    };
+    let mut libraries = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if path.is_file() {
+            if let Some(name) = get_plugin_name(&file_name) {
+                libraries.push((name, path));
+            }
+            continue;
+        }
+        let lib_name = file_name.replace('-', "_");
+        let lib_file = format!("{}{}{}", DLL_PREFIX, lib_name, DLL_SUFFIX);
+        let built_lib = ["release", "debug"]
+            .iter()
+            .map(|profile| path.join("target").join(profile).join(&lib_file))
+            .find(|lib_path| lib_path.is_file());
+        match built_lib {
+            Some(lib_path) => libraries.push((lib_name, lib_path)),
+            None => info!("Plugin {} not found/not compiled", file_name),
         }
     }
+    libraries.sort();
+    libraries
+}
+
+/// Load the plugin libraries found in the given directories. Plugins found in earlier directories
+/// take precedence. Libraries which failed to load are described in the returned errors.
+pub fn collect_libs(plugin_dirs: &[PathBuf]) -> (HashMap<String, Rc<Library>>, Vec<String>) {
+    let mut lib_map = HashMap::new();
+    let mut errors = Vec::new();
+    for plugins_dir in plugin_dirs {
+        for (lib_name, lib_path) in find_plugin_libraries(plugins_dir) {
+            if lib_map.contains_key(&lib_name) {
+                continue;
+            }
+            match unsafe { Library::new(&lib_path) } {
+                Ok(lib) => {
+                    lib_map.insert(lib_name, Rc::new(lib));
+                }
+                Err(e) => {
+                    warn!("Failed to open {}: {}", lib_path.display(), e);
+                    errors.push(format!("Failed to open plugin {}: {}", lib_name, e));
+                }
+            }
+        }
+    }
+    (lib_map, errors)
+}
+
+/// Ensure the plugin was built against the same library and compiler as the app. Calling
+/// `get_plugin` of a plugin built otherwise is undefined behaviour.
+fn check_plugin_abi(lib: &Library) -> Result<(), String> {
+    let missing_symbol = |_| {
+        format!(
+            "the plugin does not report its API version (expected API version {} ({})). Rebuild it with export_plugin_abi!()",
+            PLUGIN_API_VERSION,
+            build_fingerprint()
+        )
+    };
+    let api_version: Symbol<extern "C" fn() -> u32> =
+        unsafe { lib.get(API_VERSION_SYMBOL) }.map_err(missing_symbol)?;
+    let fingerprint: Symbol<extern "C" fn() -> *const c_char> =
+        unsafe { lib.get(BUILD_FINGERPRINT_SYMBOL) }.map_err(missing_symbol)?;
+    let fingerprint = unsafe { CStr::from_ptr(fingerprint()) }.to_string_lossy();
+    check_compatibility(api_version(), &fingerprint)
+}
+
+fn collect_plugin(
+    lib: &Library,
+    custom_bindings: Option<BindingsMap>,
+) -> Result<Box<dyn Plugin>, String> {
+    check_plugin_abi(lib)?;
+    let custom_bindings = custom_bindings.unwrap_or_default();
+    let get_plugin: Symbol<extern "Rust" fn(BindingsMap) -> Box<dyn Plugin>> =
+        unsafe { lib.get(b"get_plugin") }.map_err(|e| e.to_string())?;
+    catch_unwind(AssertUnwindSafe(|| get_plugin(custom_bindings)))
+        .map_err(|_| "the plugin panicked while being created".to_string())
 }
 
-pub fn fetch_plugins(lib_map: &HashMap<String, Rc<Library>>) -> HashMap<String, Box<dyn Plugin>> {
+/// Create the plugins from the loaded libraries, applying the custom bindings configured in the
+/// keymap. Plugins which could not be created, as well as invalid custom bindings, are described
+/// in the returned errors rather than stopping the app.
+pub fn fetch_plugins(
+    lib_map: &HashMap<String, Rc<Library>>,
+    keymap: &KeymapConfig,
+) -> (HashMap<String, Box<dyn Plugin>>, Vec<String>) {
     let mut plugins = HashMap::new();
-    for lib in lib_map.values() {
-        let plugin = collect_plugin(lib, None);
-        if let Some(plugin) = plugin {
+    let mut errors = Vec::new();
+    for (lib_name, lib) in lib_map.iter() {
+        // The plugin needs to be created before its name and functionality are known, so it is
+        // created again if there are any bindings configured for it
+        let plugin = collect_plugin(lib, None).and_then(|plugin| {
             let display = plugin.display_details();
-            plugins.insert(display, plugin);
-        };
+            if !keymap.plugins.contains_key(&display) {
+                return Ok(plugin);
+            }
+            let (custom_bindings, warnings) =
+                keymap.get_plugin_bindings(&display, &plugin.get_functionality_map());
+            errors.extend(warnings);
+            collect_plugin(lib, Some(custom_bindings))
+        });
+        match plugin {
+            Ok(plugin) => {
+                let display = plugin.display_details();
+                plugins.insert(display, plugin);
+            }
+            Err(e) => {
+                warn!("Failed to load plugin {}: {}", lib_name, e);
+                errors.push(format!("Failed to load plugin {}: {}", lib_name, e));
+            }
+        }
+    }
+    for plugin_name in keymap.plugins.keys() {
+        if !plugins.contains_key(plugin_name) {
+            errors.push(format!(
+                "Bindings configured for {}, but no such plugin was loaded",
+                plugin_name
+            ));
+        }
     }
+    errors.sort();
+
+    (plugins, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
 
-    plugins
+    use super::*;
+
+    fn lib_file(name: &str) -> String {
+        format!("{}{}{}", DLL_PREFIX, name, DLL_SUFFIX)
+    }
+
+    #[test]
+    fn test_get_plugin_name() {
+        assert_eq!(
+            get_plugin_name(&lib_file("blaze_flash")),
+            Some("blaze_flash".to_string())
+        );
+        assert_eq!(get_plugin_name("notes.txt"), None);
+        assert_eq!(get_plugin_name(&lib_file("")), None);
+    }
+
+    #[test]
+    fn test_find_plugin_libraries() {
+        let plugins_dir = TempDir::new("plugins").unwrap();
+        let root = plugins_dir.path();
+        fs::write(root.join(lib_file("blaze_flash")), b"").unwrap();
+        fs::write(root.join("readme.md"), b"").unwrap();
+        let release_dir = root.join("blaze-telescope").join("target").join("release");
+        fs::create_dir_all(&release_dir).unwrap();
+        fs::write(release_dir.join(lib_file("blaze_telescope")), b"").unwrap();
+        fs::create_dir_all(root.join("not_compiled")).unwrap();
+
+        let libraries = find_plugin_libraries(root);
+        assert_eq!(
+            libraries,
+            vec![
+                (
+                    "blaze_flash".to_string(),
+                    root.join(lib_file("blaze_flash"))
+                ),
+                (
+                    "blaze_telescope".to_string(),
+                    release_dir.join(lib_file("blaze_telescope"))
+                ),
+            ]
+        );
+        assert!(find_plugin_libraries(&root.join("missing")).is_empty());
+    }
+
+    #[test]
+    fn test_collect_invalid_libs() {
+        let plugins_dir = TempDir::new("plugins").unwrap();
+        fs::write(
+            plugins_dir.path().join(lib_file("broken")),
+            b"not a library",
+        )
+        .unwrap();
+        let (lib_map, errors) = collect_libs(&[plugins_dir.path().to_path_buf()]);
+        assert!(lib_map.is_empty());
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("Failed to open plugin broken"));
+    }
 }
@@ -0,0 +1,18 @@
+use std::env;
+use std::process::Command;
+
+/// Record the version of the compiler building the library, which makes a part of the fingerprint
+/// plugins are checked against when being loaded.
+fn main() {
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_else(|| "unknown rustc".to_string());
+    println!("cargo:rustc-env=BLAZE_RUSTC_VERSION={}", rustc_version);
+    println!("cargo:rerun-if-env-changed=RUSTC");
+    println!("cargo:rerun-if-changed=build.rs");
+}
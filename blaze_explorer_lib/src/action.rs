@@ -1,25 +1,39 @@
 use crate::{
     command::{
         DeleteSplit, DisplayMessage, ExecuteFunction, FocusDown, FocusLeft, FocusRight, FocusUp,
-        OpenNeovimHere, ParseCommand, ParseKeyStrokes, RedoDirectory, SplitHorizontally,
-        SplitVertically, TerminalCommand, ToggleMark, UndoDirectory, UpdatePlugin, UpdatePopup,
-        explorer_commands::ToggleToFavourites,
+        OpenFile, OpenNeovimHere, ParseCommand, ParseKeyStrokes, RedoDirectory, SetOption,
+        SplitHorizontally, SplitVertically, TerminalCommand, ToggleMark, UndoDirectory,
+        UpdatePlugin, UpdatePopup,
+        explorer_commands::{
+            Refresh, ReverseSort, SortBy, ToggleHidden, ToggleIgnored, ToggleToFavourites,
+        },
         file_commands::{CopyToClipboard, PasteFromClipboard},
+        git_commands::{DiscardChanges, StageSelection, UnstageSelection},
+        macro_commands::{RecordMacro, ReplayMacro, ReplayRegister, StartRecording},
         navigation_commands::{
             ChangeDirectory, JumpToEnd, JumpToId, JumpToStart, ParentDirectory, SelectDirectory,
             SelectDown, SelectUp,
         },
+        operator_commands::get_operator_command,
+    },
+    components::explorer_table::explorer_sorting::{SortKey, SortOrder},
+    core_features::{
+        add::open_add_popup,
+        rename::{open_copy_rename_popup, open_rename_popup},
     },
+    function_helpers::{pull_current_branch, push_current_branch},
     plugin::{plugin_action::PluginAction, plugin_popup::PluginPopUp},
 };
 use std::path::PathBuf;
 
 use crate::app::App;
+use crate::custom_action;
 use crate::{
     command::{
-        ClearSearchQuery, Command, ConfirmCommand, ConfirmSearchQuery, DropKey, EraseText,
-        InsertKey, NextSearchResult, Noop, Quit, ShowInFolder, SwitchMode, UpdateSearchQuery,
-        file_commands::DeleteSelection,
+        BrowseHistory, ClearFilter, ClearSearchQuery, Command, Complete, ConfirmCommand,
+        ConfirmFilter, ConfirmSearchQuery, DropKey, DropWord, EraseText, InsertKey, MoveCursor,
+        NextSearchResult, Noop, PreviousSearchResult, Quit, ShowInFolder, SwitchMode, UpdateFilter,
+        UpdateSearchQuery, file_commands::DeleteSelection,
     },
     mode::Mode,
 };
@@ -36,6 +50,10 @@ pub enum ExplorerAction {
     UpdateSearchQuery(String),
     ClearSearchQuery,
     NextSearchResult,
+    PreviousSearchResult,
+    /// Hide the rows not matching the query
+    UpdateFilter(String),
+    ClearFilter,
     SplitHorizontally,
     SplitVertically,
     FocusUp,
@@ -46,6 +64,14 @@ pub enum ExplorerAction {
     JumpToId(usize),
     ToggleMark,
     ToggleToFavourites,
+    SortBy(SortOrder),
+    ReverseSort,
+    /// Show or hide the dotfiles
+    ToggleHidden,
+    /// Show or hide the entries ignored by git
+    ToggleIgnored,
+    /// Read the directories again
+    Refresh,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -53,6 +79,7 @@ pub enum AppAction {
     Quit,
     SwitchMode(Mode),
     ConfirmSearchQuery,
+    ConfirmFilter,
     ConfirmCommand,
     OpenPopup(Box<dyn PluginPopUp>),
     ShowInFolder(PathBuf),
@@ -67,13 +94,84 @@ pub enum AppAction {
     ExecuteFunction(Box<fn(&mut App) -> Option<Action>>),
     Copy,
     Paste,
+    /// Wait for a motion to apply the operator to, e.g. `d` in `dG`
+    StartOperator(Operator),
+    ApplyOperator(Operator, Motion),
+    /// Repeat the last reversible command at the current selection/directory
+    RepeatLastCommand,
+    /// Wait for a register to record a macro to, or stop the ongoing recording
+    RecordMacro,
+    StartRecording(char),
+    /// Wait for a register to replay a macro from
+    ReplayMacro,
+    ReplayRegister(char),
+    ReplayLastMacro,
+    /// Open the file with the default program
+    OpenFile(PathBuf),
+    /// Set an option changed with `:set`, e.g. `timeout`
+    SetOption(String, String),
+    /// Add the selected items to the git index
+    Stage,
+    Unstage,
+    /// Replace the changes not added to the git index with the content of the index
+    DiscardChanges,
+}
+
+/// Action waiting for a register key, e.g. `a` in `qa`
+#[derive(Clone, Debug, PartialEq)]
+pub enum RegisterAction {
+    RecordMacro,
+    ReplayMacro,
+}
+
+/// Operation applied to the rows covered by a motion
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operator {
+    Delete,
+    Yank,
+    Cut,
+    /// Move the files to the directory of the other split
+    Move,
+}
+
+/// Rows covered by an operator, counted from the selected row
+#[derive(Clone, Debug, PartialEq)]
+pub enum Motion {
+    /// Repeated operator key, e.g. `dd`
+    Line,
+    Down,
+    Up,
+    FirstRow,
+    LastRow,
+    NextSearchResult,
+    PreviousSearchResult,
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum TextAction {
     InsertKey(char),
+    /// Remove the text before the cursor
     EraseText,
     DropKey,
+    /// Remove the word before the cursor
+    DropWord,
+    MoveCursor(CursorMovement),
+    /// Show the older line of the history of the mode
+    PreviousEntry,
+    /// Show the newer line of the history of the mode
+    NextEntry,
+    /// Complete the command line, or show the next candidate
+    NextCompletion,
+    PreviousCompletion,
+}
+
+/// Movement of the cursor in the command line
+#[derive(Clone, Debug, PartialEq)]
+pub enum CursorMovement {
+    Left,
+    Right,
+    Start,
+    End,
 }
 #[derive(Clone, Debug, PartialEq)]
 pub enum Action {
@@ -84,6 +182,8 @@ pub enum Action {
     PopupAct(PopupAction),
     CommandAct(CommandAction),
     PluginAct(PluginAction),
+    /// Action preceded by a count, e.g. `5j`
+    CountAct(usize, Box<Action>),
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -127,6 +227,13 @@ pub fn get_command(app: &mut App, action: Action) -> Box<dyn Command> {
         Action::ExplorerAct(ExplorerAction::NextSearchResult) => {
             Box::new(NextSearchResult::new(ctx))
         }
+        Action::ExplorerAct(ExplorerAction::PreviousSearchResult) => {
+            Box::new(PreviousSearchResult::new(ctx))
+        }
+        Action::ExplorerAct(ExplorerAction::UpdateFilter(query)) => {
+            Box::new(UpdateFilter::new(ctx, query))
+        }
+        Action::ExplorerAct(ExplorerAction::ClearFilter) => Box::new(ClearFilter::new(ctx)),
         Action::ExplorerAct(ExplorerAction::SplitHorizontally) => {
             Box::new(SplitHorizontally::new(ctx))
         }
@@ -141,9 +248,17 @@ pub fn get_command(app: &mut App, action: Action) -> Box<dyn Command> {
         Action::ExplorerAct(ExplorerAction::ToggleToFavourites) => {
             Box::new(ToggleToFavourites::new(ctx))
         }
+        Action::ExplorerAct(ExplorerAction::SortBy(sort_order)) => {
+            Box::new(SortBy::new(ctx, sort_order))
+        }
+        Action::ExplorerAct(ExplorerAction::ReverseSort) => Box::new(ReverseSort::new(ctx)),
+        Action::ExplorerAct(ExplorerAction::ToggleHidden) => Box::new(ToggleHidden::new(ctx)),
+        Action::ExplorerAct(ExplorerAction::ToggleIgnored) => Box::new(ToggleIgnored::new(ctx)),
+        Action::ExplorerAct(ExplorerAction::Refresh) => Box::new(Refresh::new(ctx)),
         Action::AppAct(AppAction::Quit) => Box::new(Quit::new()),
         Action::AppAct(AppAction::SwitchMode(mode)) => Box::new(SwitchMode::new(ctx, mode)),
         Action::AppAct(AppAction::ConfirmSearchQuery) => Box::new(ConfirmSearchQuery::new()),
+        Action::AppAct(AppAction::ConfirmFilter) => Box::new(ConfirmFilter::new(ctx)),
         Action::AppAct(AppAction::ConfirmCommand) => Box::new(ConfirmCommand::new(ctx)),
         Action::AppAct(AppAction::OpenPopup(popup_type)) => {
             panic!(
@@ -154,6 +269,9 @@ pub fn get_command(app: &mut App, action: Action) -> Box<dyn Command> {
         Action::AppAct(AppAction::ShowInFolder(path)) => Box::new(ShowInFolder::new(ctx, path)),
         Action::AppAct(AppAction::Delete) => Box::new(DeleteSelection::new(ctx)),
         Action::AppAct(AppAction::Copy) => Box::new(CopyToClipboard::new(ctx)),
+        Action::AppAct(AppAction::Stage) => Box::new(StageSelection::new(ctx)),
+        Action::AppAct(AppAction::Unstage) => Box::new(UnstageSelection::new(ctx)),
+        Action::AppAct(AppAction::DiscardChanges) => Box::new(DiscardChanges::new(ctx)),
         Action::AppAct(AppAction::Paste) => Box::new(PasteFromClipboard::new(ctx)),
         Action::AppAct(AppAction::OpenNeovimHere) => Box::new(OpenNeovimHere::new(ctx)),
         Action::AppAct(AppAction::DisplayMessage(msg)) => Box::new(DisplayMessage::new(msg)),
@@ -169,14 +287,367 @@ pub fn get_command(app: &mut App, action: Action) -> Box<dyn Command> {
         Action::AppAct(AppAction::ParseKeyStrokes(command)) => {
             Box::new(ParseKeyStrokes::new(ctx, command))
         }
+        // Operators are resolved by the input machine
+        Action::AppAct(AppAction::StartOperator(_)) => Box::new(Noop::new()),
+        Action::AppAct(AppAction::ApplyOperator(operator, motion)) => {
+            get_operator_command(ctx, operator, motion, None)
+        }
+        Action::AppAct(AppAction::RecordMacro) => Box::new(RecordMacro::new(ctx)),
+        Action::AppAct(AppAction::StartRecording(register)) => {
+            Box::new(StartRecording::new(ctx, register))
+        }
+        Action::AppAct(AppAction::ReplayMacro) => Box::new(ReplayMacro::new(ctx)),
+        Action::AppAct(AppAction::ReplayRegister(register)) => {
+            Box::new(ReplayRegister::new(ctx, Some(register)))
+        }
+        Action::AppAct(AppAction::ReplayLastMacro) => Box::new(ReplayRegister::new(ctx, None)),
+        Action::AppAct(AppAction::OpenFile(path)) => Box::new(OpenFile::new(ctx, path)),
+        Action::AppAct(AppAction::SetOption(name, value)) => {
+            Box::new(SetOption::new(ctx, name, value))
+        }
+        Action::AppAct(AppAction::RepeatLastCommand) => {
+            match ctx
+                .last_command
+                .clone()
+                .and_then(|command| command.retarget(ctx))
+            {
+                Some(command) => command,
+                None => Box::new(Noop::new()),
+            }
+        }
         Action::TextAct(TextAction::InsertKey(ch)) => Box::new(InsertKey::new(ctx, ch)),
         Action::TextAct(TextAction::EraseText) => Box::new(EraseText::new()),
         Action::TextAct(TextAction::DropKey) => Box::new(DropKey::new()),
+        Action::TextAct(TextAction::DropWord) => Box::new(DropWord::new()),
+        Action::TextAct(TextAction::MoveCursor(movement)) => Box::new(MoveCursor::new(movement)),
+        Action::TextAct(TextAction::PreviousEntry) => Box::new(BrowseHistory::new(true)),
+        Action::TextAct(TextAction::NextEntry) => Box::new(BrowseHistory::new(false)),
+        Action::TextAct(TextAction::NextCompletion) => Box::new(Complete::new(true)),
+        Action::TextAct(TextAction::PreviousCompletion) => Box::new(Complete::new(false)),
         Action::PopupAct(PopupAction::UpdatePlugin) => Box::new(UpdatePlugin::new()),
         Action::PopupAct(PopupAction::UpdatePopup) => Box::new(UpdatePopup::new()),
         Action::Noop => Box::new(Noop::new()),
         Action::CommandAct(_) => Box::new(Noop::new()),
         Action::PluginAct(plugin_action) => plugin_action.get_command(),
+        Action::CountAct(count, action) => match *action {
+            Action::ExplorerAct(ExplorerAction::SelectUp) => {
+                Box::new(SelectUp::new_with_count(ctx, count))
+            }
+            Action::ExplorerAct(ExplorerAction::SelectDown) => {
+                Box::new(SelectDown::new_with_count(ctx, count))
+            }
+            Action::ExplorerAct(ExplorerAction::JumpToStart)
+            | Action::ExplorerAct(ExplorerAction::JumpToEnd) => {
+                Box::new(JumpToId::new_with_line_number(ctx, count))
+            }
+            Action::AppAct(AppAction::Delete) => {
+                Box::new(DeleteSelection::new_with_count(ctx, count))
+            }
+            Action::AppAct(AppAction::Copy) => {
+                Box::new(CopyToClipboard::new_with_count(ctx, count))
+            }
+            Action::AppAct(AppAction::ApplyOperator(operator, motion)) => {
+                get_operator_command(ctx, operator, motion, Some(count))
+            }
+            // Actions not accepting a count are executed once
+            action => get_command(app, action),
+        },
         action => panic!("Action {:?} not implemented", action),
     }
 }
+
+/// Actions which can be referred to by a stable name, e.g. in the keymap file
+pub fn get_named_actions() -> Vec<(&'static str, Action)> {
+    vec![
+        (
+            "ParentDirectory",
+            Action::ExplorerAct(ExplorerAction::ParentDirectory),
+        ),
+        (
+            "JumpToStart",
+            Action::ExplorerAct(ExplorerAction::JumpToStart),
+        ),
+        ("JumpToEnd", Action::ExplorerAct(ExplorerAction::JumpToEnd)),
+        ("SelectUp", Action::ExplorerAct(ExplorerAction::SelectUp)),
+        (
+            "SelectDown",
+            Action::ExplorerAct(ExplorerAction::SelectDown),
+        ),
+        (
+            "SelectDirectory",
+            Action::ExplorerAct(ExplorerAction::SelectDirectory),
+        ),
+        (
+            "ClearSearchQuery",
+            Action::ExplorerAct(ExplorerAction::ClearSearchQuery),
+        ),
+        (
+            "NextSearchResult",
+            Action::ExplorerAct(ExplorerAction::NextSearchResult),
+        ),
+        (
+            "PreviousSearchResult",
+            Action::ExplorerAct(ExplorerAction::PreviousSearchResult),
+        ),
+        (
+            "ClearFilter",
+            Action::ExplorerAct(ExplorerAction::ClearFilter),
+        ),
+        (
+            "SplitHorizontally",
+            Action::ExplorerAct(ExplorerAction::SplitHorizontally),
+        ),
+        (
+            "SplitVertically",
+            Action::ExplorerAct(ExplorerAction::SplitVertically),
+        ),
+        ("FocusUp", Action::ExplorerAct(ExplorerAction::FocusUp)),
+        ("FocusDown", Action::ExplorerAct(ExplorerAction::FocusDown)),
+        ("FocusLeft", Action::ExplorerAct(ExplorerAction::FocusLeft)),
+        (
+            "FocusRight",
+            Action::ExplorerAct(ExplorerAction::FocusRight),
+        ),
+        (
+            "DeleteSplit",
+            Action::ExplorerAct(ExplorerAction::DeleteSplit),
+        ),
+        (
+            "ToggleMark",
+            Action::ExplorerAct(ExplorerAction::ToggleMark),
+        ),
+        (
+            "ToggleToFavourites",
+            Action::ExplorerAct(ExplorerAction::ToggleToFavourites),
+        ),
+        (
+            "SortByName",
+            Action::ExplorerAct(ExplorerAction::SortBy(SortOrder::new(SortKey::Name, false))),
+        ),
+        (
+            "SortBySize",
+            Action::ExplorerAct(ExplorerAction::SortBy(SortOrder::new(SortKey::Size, false))),
+        ),
+        (
+            "SortByModified",
+            Action::ExplorerAct(ExplorerAction::SortBy(SortOrder::new(
+                SortKey::Modified,
+                false,
+            ))),
+        ),
+        (
+            "SortByExtension",
+            Action::ExplorerAct(ExplorerAction::SortBy(SortOrder::new(
+                SortKey::Extension,
+                false,
+            ))),
+        ),
+        (
+            "SortByType",
+            Action::ExplorerAct(ExplorerAction::SortBy(SortOrder::new(SortKey::Type, false))),
+        ),
+        (
+            "ReverseSort",
+            Action::ExplorerAct(ExplorerAction::ReverseSort),
+        ),
+        (
+            "ToggleHidden",
+            Action::ExplorerAct(ExplorerAction::ToggleHidden),
+        ),
+        (
+            "ToggleIgnored",
+            Action::ExplorerAct(ExplorerAction::ToggleIgnored),
+        ),
+        ("Refresh", Action::ExplorerAct(ExplorerAction::Refresh)),
+        ("Quit", Action::AppAct(AppAction::Quit)),
+        (
+            "NormalMode",
+            Action::AppAct(AppAction::SwitchMode(Mode::Normal)),
+        ),
+        (
+            "SearchMode",
+            Action::AppAct(AppAction::SwitchMode(Mode::Search)),
+        ),
+        (
+            "FilterMode",
+            Action::AppAct(AppAction::SwitchMode(Mode::Filter)),
+        ),
+        (
+            "CommandMode",
+            Action::AppAct(AppAction::SwitchMode(Mode::Command)),
+        ),
+        (
+            "VisualMode",
+            Action::AppAct(AppAction::SwitchMode(Mode::Visual)),
+        ),
+        (
+            "ConfirmSearchQuery",
+            Action::AppAct(AppAction::ConfirmSearchQuery),
+        ),
+        ("ConfirmFilter", Action::AppAct(AppAction::ConfirmFilter)),
+        ("ConfirmCommand", Action::AppAct(AppAction::ConfirmCommand)),
+        ("Delete", Action::AppAct(AppAction::Delete)),
+        ("Copy", Action::AppAct(AppAction::Copy)),
+        ("Paste", Action::AppAct(AppAction::Paste)),
+        ("Stage", Action::AppAct(AppAction::Stage)),
+        ("Unstage", Action::AppAct(AppAction::Unstage)),
+        ("DiscardChanges", Action::AppAct(AppAction::DiscardChanges)),
+        (
+            "DeleteOperator",
+            Action::AppAct(AppAction::StartOperator(Operator::Delete)),
+        ),
+        (
+            "YankOperator",
+            Action::AppAct(AppAction::StartOperator(Operator::Yank)),
+        ),
+        (
+            "CutOperator",
+            Action::AppAct(AppAction::StartOperator(Operator::Cut)),
+        ),
+        (
+            "MoveOperator",
+            Action::AppAct(AppAction::StartOperator(Operator::Move)),
+        ),
+        (
+            "RepeatLastCommand",
+            Action::AppAct(AppAction::RepeatLastCommand),
+        ),
+        ("RecordMacro", Action::AppAct(AppAction::RecordMacro)),
+        ("ReplayMacro", Action::AppAct(AppAction::ReplayMacro)),
+        (
+            "ReplayLastMacro",
+            Action::AppAct(AppAction::ReplayLastMacro),
+        ),
+        ("OpenNeovimHere", Action::AppAct(AppAction::OpenNeovimHere)),
+        ("UndoDirectory", Action::AppAct(AppAction::UndoDirectory)),
+        ("RedoDirectory", Action::AppAct(AppAction::RedoDirectory)),
+        ("Rename", custom_action!(open_rename_popup)),
+        ("CopyRename", custom_action!(open_copy_rename_popup)),
+        ("Add", custom_action!(open_add_popup)),
+        ("PushCurrentBranch", custom_action!(push_current_branch)),
+        ("PullCurrentBranch", custom_action!(pull_current_branch)),
+        ("EraseText", Action::TextAct(TextAction::EraseText)),
+        ("DropKey", Action::TextAct(TextAction::DropKey)),
+        ("DropWord", Action::TextAct(TextAction::DropWord)),
+        (
+            "CursorLeft",
+            Action::TextAct(TextAction::MoveCursor(CursorMovement::Left)),
+        ),
+        (
+            "CursorRight",
+            Action::TextAct(TextAction::MoveCursor(CursorMovement::Right)),
+        ),
+        (
+            "CursorToStart",
+            Action::TextAct(TextAction::MoveCursor(CursorMovement::Start)),
+        ),
+        (
+            "CursorToEnd",
+            Action::TextAct(TextAction::MoveCursor(CursorMovement::End)),
+        ),
+        ("PreviousEntry", Action::TextAct(TextAction::PreviousEntry)),
+        ("NextEntry", Action::TextAct(TextAction::NextEntry)),
+        (
+            "NextCompletion",
+            Action::TextAct(TextAction::NextCompletion),
+        ),
+        (
+            "PreviousCompletion",
+            Action::TextAct(TextAction::PreviousCompletion),
+        ),
+        ("Undo", Action::CommandAct(CommandAction::Undo)),
+        ("Redo", Action::CommandAct(CommandAction::Redo)),
+        ("Noop", Action::Noop),
+    ]
+}
+
+/// Obtain an action from its stable name. Actions taking an argument are written as the name
+/// followed by a space and the argument, e.g. `ParseCommand !git status`.
+pub fn get_action_by_name(name: &str) -> Option<Action> {
+    let (name, argument) = match name.trim().split_once(' ') {
+        Some((name, argument)) => (name, Some(argument.trim().to_string())),
+        None => (name.trim(), None),
+    };
+    match (name, argument) {
+        ("ChangeDirectory", Some(path)) => Some(Action::ExplorerAct(
+            ExplorerAction::ChangeDirectory(PathBuf::from(path)),
+        )),
+        ("ParseCommand", Some(command)) => Some(Action::AppAct(AppAction::ParseCommand(command))),
+        ("ParseKeyStrokes", Some(keys)) => Some(Action::AppAct(AppAction::ParseKeyStrokes(keys))),
+        ("TerminalCommand", Some(command)) => {
+            Some(Action::AppAct(AppAction::TerminalCommand(command)))
+        }
+        ("DisplayMessage", Some(message)) => {
+            Some(Action::AppAct(AppAction::DisplayMessage(message)))
+        }
+        ("OpenFile", Some(path)) => Some(Action::AppAct(AppAction::OpenFile(PathBuf::from(path)))),
+        (name, None) => get_named_actions()
+            .into_iter()
+            .find(|(action_name, _)| *action_name == name)
+            .map(|(_, action)| action),
+        _ => None,
+    }
+}
+
+/// Obtain the stable name of an action, if it has one. Inverse of [get_action_by_name].
+pub fn get_action_name(action: &Action) -> Option<String> {
+    match action {
+        Action::ExplorerAct(ExplorerAction::ChangeDirectory(path)) => {
+            Some(format!("ChangeDirectory {}", path.display()))
+        }
+        Action::AppAct(AppAction::ParseCommand(command)) => {
+            Some(format!("ParseCommand {}", command))
+        }
+        Action::AppAct(AppAction::ParseKeyStrokes(keys)) => {
+            Some(format!("ParseKeyStrokes {}", keys))
+        }
+        Action::AppAct(AppAction::TerminalCommand(command)) => {
+            Some(format!("TerminalCommand {}", command))
+        }
+        Action::AppAct(AppAction::DisplayMessage(message)) => {
+            Some(format!("DisplayMessage {}", message))
+        }
+        Action::AppAct(AppAction::OpenFile(path)) => Some(format!("OpenFile {}", path.display())),
+        action => get_named_actions()
+            .into_iter()
+            .find(|(_, named_action)| named_action == action)
+            .map(|(name, _)| name.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_action_by_name() {
+        assert_eq!(
+            get_action_by_name("SelectDown"),
+            Some(Action::ExplorerAct(ExplorerAction::SelectDown))
+        );
+        assert_eq!(
+            get_action_by_name("ParseCommand !git status"),
+            Some(Action::AppAct(AppAction::ParseCommand(
+                "!git status".to_string()
+            )))
+        );
+        assert_eq!(
+            get_action_by_name("Rename"),
+            Some(custom_action!(open_rename_popup))
+        );
+        assert_eq!(get_action_by_name("NotAnAction"), None);
+        assert_eq!(get_action_by_name("SelectDown extra"), None);
+    }
+
+    #[test]
+    fn test_get_action_name() {
+        for (name, action) in get_named_actions() {
+            assert_eq!(get_action_name(&action), Some(name.to_string()));
+        }
+        let action = Action::AppAct(AppAction::ParseKeyStrokes(":q<cr>".to_string()));
+        assert_eq!(
+            get_action_by_name(&get_action_name(&action).unwrap()),
+            Some(action)
+        );
+    }
+}
@@ -1,9 +1,10 @@
 use std::collections::{HashMap, VecDeque};
-use std::env::set_current_dir;
+use std::env::{self, set_current_dir};
 use std::error::Error;
 use std::fs;
 use std::io::{Stdout, stdout};
-use std::path::{self, PathBuf};
+use std::path::{self, Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use color_eyre::Result;
 use directories::ProjectDirs;
@@ -19,20 +20,38 @@ use ratatui::{
 
 use crate::action::{AppAction, CommandAction, ExplorerAction, get_command};
 use crate::app_input_machine::AppInputMachine;
+use crate::clipboard::{Clipboard, system_clipboard};
 use crate::command::Command;
+use crate::command_line_history::CommandLineHistory;
 use crate::components::command_line::CommandLine;
 use crate::components::explorer_manager::ExplorerManager;
 use crate::components::explorer_table::explorer_utils::FileConfig;
 use crate::core_features::favourites::Config;
+use crate::core_features::which_key::{WhichKeyPopUp, get_sequence_hints};
+use crate::directory_watcher::DirectoryWatcher;
+use crate::ex_commands::parse_ex_command;
 use crate::explorer_helpers::convert_sequence_to_string;
 use crate::history_stack::directory_history::DirectoryDetails;
 use crate::history_stack::{HistoryStack, command_history::CommandHistory};
 use crate::input_machine::{InputMachine, KeyProcessingResult};
+use crate::key_notation::normalize_key;
+use crate::keymap_config::KeymapConfig;
 use crate::line_entry::LineEntry;
+use crate::macro_registers::MacroRegisters;
 use crate::plugin::Plugin;
 use crate::plugin::plugin_popup::PluginPopUp;
+use crate::search_pattern::{SearchPattern, SearchSettings};
 use crate::{action::Action, components::Component, mode::Mode};
 
+/// Environment variable overriding the directories plugins are loaded from
+pub const PLUGIN_DIRS_ENV: &str = "BLAZE_EXPLORER_PLUGINS";
+
+/// Time without key presses after which the continuations of a pending key sequence are shown
+pub const WHICH_KEY_DELAY: Duration = Duration::from_millis(500);
+
+/// Time between checks for changes in the watched directories while waiting for a key
+pub const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
 #[derive(Clone, PartialEq, Debug)]
 pub enum ExitResult {
     Quit,
@@ -67,6 +86,8 @@ pub struct App {
     pub current_sequence: Vec<KeyEvent>,
     pub input_machine: AppInputMachine<Action>,
     pub popup: Option<Box<dyn PluginPopUp>>,
+    /// Continuations of the pending key sequence, shown on top of everything without taking keys
+    pub hint_popup: Option<Box<dyn PluginPopUp>>,
     pub command_history: HashMap<PathBuf, CommandHistory>,
     pub command_input: Option<String>,
     pub exit_status: Option<ExitResult>,
@@ -75,6 +96,15 @@ pub struct App {
     pub plugins: HashMap<String, Box<dyn Plugin>>,
     pub config: Config,
     pub project_dir: ProjectDirs,
+    pub clipboard: Box<dyn Clipboard>,
+    /// Files cut to the clipboard, moved instead of copied when pasted
+    pub cut_selection: Option<Vec<PathBuf>>,
+    /// Last reversible command, repeated with `.`
+    pub last_command: Option<Box<dyn Command>>,
+    pub macro_registers: MacroRegisters,
+    pub command_line_history: CommandLineHistory,
+    pub search_settings: SearchSettings,
+    pub directory_watcher: DirectoryWatcher,
 }
 impl App {
     pub fn new_with_name(name: String) -> Result<Self, Box<dyn Error>> {
@@ -88,6 +118,7 @@ impl App {
             current_sequence: Vec::new(),
             input_machine: AppInputMachine::new(),
             popup: None,
+            hint_popup: None,
             command_history: HashMap::new(),
             command_input: None,
             exit_status: None,
@@ -96,6 +127,13 @@ impl App {
             plugins: HashMap::new(),
             config: Config::new(vec![]),
             project_dir: ProjectDirs::from("", "", &name).unwrap(),
+            clipboard: system_clipboard(),
+            cut_selection: None,
+            last_command: None,
+            macro_registers: MacroRegisters::default(),
+            command_line_history: CommandLineHistory::default(),
+            search_settings: SearchSettings::default(),
+            directory_watcher: DirectoryWatcher::new(),
         };
         let mut app = match app.create_project_dirs() {
             Ok(_) => app,
@@ -104,6 +142,26 @@ impl App {
         app.config = Config::try_load_from_file(app.get_config_path())?;
         Ok(app)
     }
+
+    /// Load the user's macros, command line history and keymap. Kept out of the constructor, so
+    /// that apps created in tests only use the defaults. The keymap is returned, as it also holds
+    /// the bindings of the plugins.
+    pub fn load_user_config(&mut self) -> KeymapConfig {
+        self.load_macros_from(self.get_macros_path());
+        self.command_line_history =
+            match CommandLineHistory::try_load_from_file(self.get_history_path()) {
+                Ok(history) => history,
+                Err(e) => {
+                    self.append_command_line_message(format!(
+                        "Failed to read the history file: {}",
+                        e
+                    ));
+                    CommandLineHistory::default()
+                }
+            };
+        self.load_keymap()
+    }
+
     pub fn new() -> Result<App, Box<dyn Error>> {
         Self::new_with_name("blaze_explorer".to_string())
     }
@@ -117,6 +175,71 @@ impl App {
         config_path
     }
 
+    pub fn get_macros_path(&self) -> PathBuf {
+        self.project_dir.data_dir().join("macros.json")
+    }
+
+    pub fn get_history_path(&self) -> PathBuf {
+        self.project_dir.data_dir().join("history.json")
+    }
+
+    pub fn get_keymap_path(&self) -> PathBuf {
+        self.project_dir.config_dir().join("keymap.json")
+    }
+
+    /// Directories searched for plugin libraries. The `BLAZE_EXPLORER_PLUGINS` environment variable
+    /// (a list of paths, separated like PATH) takes precedence over the `plugins` folder in the
+    /// data directory.
+    pub fn get_plugin_dirs(&self) -> Vec<PathBuf> {
+        match env::var_os(PLUGIN_DIRS_ENV) {
+            Some(dirs) if !dirs.is_empty() => env::split_paths(&dirs).collect(),
+            _ => vec![self.project_dir.data_dir().join("plugins")],
+        }
+    }
+
+    /// Apply the user keymap on top of the default bindings. Problems with the keymap file are
+    /// reported in the command line rather than preventing the app from starting, with the
+    /// default keymap returned if the file cannot be read.
+    pub fn load_keymap(&mut self) -> KeymapConfig {
+        self.load_keymap_from(self.get_keymap_path())
+    }
+
+    pub fn load_keymap_from(&mut self, keymap_path: PathBuf) -> KeymapConfig {
+        let (keymap, errors) = match KeymapConfig::try_load_from_file(keymap_path) {
+            Ok(keymap) => {
+                let errors = keymap.apply(&mut self.input_machine);
+                (keymap, errors)
+            }
+            Err(e) => (
+                KeymapConfig::default(),
+                vec![format!("Failed to read the keymap file: {}", e)],
+            ),
+        };
+        if !errors.is_empty() {
+            self.append_command_line_message(format!("Keymap errors:\n{}", errors.join("\n")));
+        }
+        keymap
+    }
+
+    pub fn load_macros_from(&mut self, macros_path: PathBuf) {
+        self.macro_registers = match MacroRegisters::try_load_from_file(macros_path) {
+            Ok(macro_registers) => macro_registers,
+            Err(e) => {
+                self.append_command_line_message(format!("Failed to read the macros file: {}", e));
+                MacroRegisters::default()
+            }
+        };
+    }
+
+    /// Save the macros to the data directory, so that they survive restarts
+    pub fn save_macros(&self) -> std::io::Result<()> {
+        self.save_macros_to(self.get_macros_path())
+    }
+
+    pub fn save_macros_to(&self, macros_path: PathBuf) -> std::io::Result<()> {
+        self.macro_registers.save_to_file(macros_path)
+    }
+
     /// Create the project directories if they do not exist
     pub fn create_project_dirs(&self) -> Result<PathBuf, Box<dyn Error>> {
         let cache_dir = self.project_dir.cache_dir();
@@ -149,10 +272,30 @@ impl App {
         self.action_list.push_back(action);
     }
 
+    /// Next event typed, refreshing the directories changed in the meantime
     pub fn draw_key_event(&mut self) -> std::io::Result<Event> {
-        match self.key_queue.pop_front() {
-            Some(key) => Ok(event::Event::Key(key)),
-            None => event::read(),
+        loop {
+            if event::poll(WATCH_POLL_INTERVAL)? {
+                return event::read();
+            }
+            if self.refresh_changed_directories() {
+                let _ = self.render();
+            }
+        }
+    }
+
+    /// Read the shown directories which changed on disk again, and the git status of the ones in
+    /// a repository whose index changed, returning whether any did
+    pub fn refresh_changed_directories(&mut self) -> bool {
+        let mut watched_paths = self.explorer_manager.get_visible_paths();
+        watched_paths.extend(self.explorer_manager.get_index_paths());
+        self.directory_watcher.watch_paths(watched_paths);
+        match self.directory_watcher.take_changes() {
+            Some(changes) => {
+                self.explorer_manager.refresh_paths(&changes);
+                true
+            }
+            None => false,
         }
     }
     pub fn attach_popup(&mut self, popup: Box<dyn PluginPopUp>) {
@@ -168,7 +311,23 @@ impl App {
         self.enter_normal_mode();
     }
     pub fn process_key_event(&mut self, key: KeyEvent) {
+        self.process_key(key, true);
+    }
+
+    /// Process a key queued by the app itself, e.g. when replaying a macro. Such keys are not
+    /// recorded, as the key which made the app queue them already was.
+    pub fn process_queued_key_event(&mut self, key: KeyEvent) {
+        self.process_key(key, false);
+    }
+
+    fn process_key(&mut self, key: KeyEvent, typed: bool) {
         if key.kind == KeyEventKind::Press {
+            let key = normalize_key(key);
+            self.hint_popup = None;
+            if typed {
+                self.macro_registers
+                    .record_key(key, self.current_sequence.is_empty());
+            }
             self.handle_key_event(key);
         };
     }
@@ -187,6 +346,68 @@ impl App {
         }
     }
 
+    /// Show the bindings which can complete the pending key sequence, if there are any
+    pub fn show_sequence_hints(&mut self) {
+        let hints = get_sequence_hints(self);
+        if !hints.is_empty() {
+            let sequence = convert_sequence_to_string(self.current_sequence.clone());
+            self.hint_popup = Some(Box::new(WhichKeyPopUp::new(sequence, hints)));
+        }
+    }
+
+    /// Wait for the next key of the pending key sequence. The continuations of the sequence are
+    /// shown after `WHICH_KEY_DELAY`, and the sequence is resolved once its timeout passes.
+    /// Returns false if the sequence was resolved without any key coming in.
+    fn await_sequence_key(&mut self) -> std::io::Result<bool> {
+        let started = Instant::now();
+        let timeout = self.input_machine.get_sequence_timeout();
+        loop {
+            let elapsed = started.elapsed();
+            if timeout.is_some_and(|timeout| elapsed >= timeout) {
+                self.resolve_pending_sequence();
+                return Ok(false);
+            }
+            let hints_pending = self.hint_popup.is_none() && elapsed < WHICH_KEY_DELAY;
+            if self.hint_popup.is_none() && elapsed >= WHICH_KEY_DELAY {
+                self.show_sequence_hints();
+                let _ = self.render();
+            }
+            let wait = [
+                hints_pending.then(|| WHICH_KEY_DELAY - elapsed),
+                timeout.map(|timeout| timeout - elapsed),
+            ]
+            .into_iter()
+            .flatten()
+            .min();
+            match wait {
+                None => return Ok(true),
+                Some(wait) if event::poll(wait)? => return Ok(true),
+                Some(_) => {}
+            }
+        }
+    }
+
+    /// Resolve the pending key sequence after no key was typed for its timeout
+    pub fn resolve_pending_sequence(&mut self) {
+        self.hint_popup = None;
+        let result = self
+            .input_machine
+            .resolve_timeout(&self.mode, &mut self.current_sequence);
+        if let KeyProcessingResult::Complete(action) = result {
+            self.own_push_action(action);
+        }
+        let replay_keys = self.input_machine.take_replay_keys();
+        self.replay_keys(replay_keys);
+    }
+
+    /// Process the keys again before any other queued keys, e.g. the keys typed after a sequence
+    /// which turned out to be complete
+    fn replay_keys(&mut self, keys: Vec<KeyEvent>) {
+        for key in keys.into_iter().rev() {
+            self.key_queue.push_front(key);
+        }
+    }
+
     pub fn try_drop_popup(&mut self) {
         if let Some(popup) = &mut self.popup {
             popup.quit();
@@ -196,6 +417,8 @@ impl App {
         if !test_mode {
             self.terminal.clear()?;
         }
+        // Files may have changed while the app was in the background
+        self.explorer_manager.invalidate_listings();
         if cold_start {
             let path = "./";
             let starting_path = path::absolute(path).unwrap();
@@ -209,14 +432,29 @@ impl App {
         if !test_mode {
             loop {
                 let _ = self.render();
-                if let event::Event::Key(key) = self.draw_key_event()? {
-                    self.process_key_event(key);
+                if !self.current_sequence.is_empty()
+                    && self.key_queue.is_empty()
+                    && !self.await_sequence_key()?
+                {
+                    let _ = self.handle_new_actions();
                     self.check_popup();
                     if self.should_quit {
                         break;
                     }
-                    let _ = self.handle_new_actions();
+                    continue;
                 }
+                if let Some(key) = self.key_queue.pop_front() {
+                    self.process_queued_key_event(key);
+                } else if let event::Event::Key(key) = self.draw_key_event()? {
+                    self.process_key_event(key);
+                } else {
+                    continue;
+                }
+                self.check_popup();
+                if self.should_quit {
+                    break;
+                }
+                let _ = self.handle_new_actions();
             }
         }
 
@@ -236,6 +474,7 @@ impl App {
         let keymap_result =
             self.input_machine
                 .process_keys(&self.mode, &mut self.current_sequence, key_event);
+        let replay_keys = self.input_machine.take_replay_keys();
         if self.command_line.current_message.is_some() {
             self.command_line.get_message_batch();
             if self.command_line.current_message.is_none() {
@@ -255,16 +494,21 @@ impl App {
                 }
                 _ => {}
             }
+            self.replay_keys(replay_keys);
         }
     }
 
+    /// Execute a command typed in command mode, e.g. `vsplit ../docs`, and return to normal mode
     pub fn execute_command(&mut self, command: String) -> Option<Action> {
-        match command.as_str() {
-            "q" => Some(Action::ExplorerAct(ExplorerAction::DeleteSplit)),
-            other_command => Some(Action::AppAct(AppAction::DisplayMessage(format!(
-                "Not a supported command: {}",
-                other_command
-            )))),
+        self.own_push_action(Action::AppAct(AppAction::SwitchMode(Mode::Normal)));
+        match parse_ex_command(self, &command) {
+            Ok(actions) => {
+                for action in actions {
+                    self.own_push_action(action);
+                }
+                None
+            }
+            Err(e) => Some(Action::AppAct(AppAction::DisplayMessage(e))),
         }
     }
 
@@ -288,6 +532,19 @@ impl App {
         self.mode = Mode::Search;
         self.explorer_manager.switch_mode(Mode::Search);
         self.command_line.focus();
+        self.command_line_history.stop_browsing();
+        self.explorer_manager.unfocus();
+    }
+
+    /// Type the filter of the focused table, starting from the current one
+    pub fn enter_filter_mode(&mut self) {
+        self.mode = Mode::Filter;
+        self.explorer_manager.switch_mode(Mode::Filter);
+        self.command_line.focus();
+        if let Some(filter) = self.explorer_manager.get_filter() {
+            self.command_line.set_contents(filter);
+        }
+        self.command_line_history.stop_browsing();
         self.explorer_manager.unfocus();
     }
 
@@ -303,6 +560,7 @@ impl App {
         self.mode = Mode::Command;
         self.explorer_manager.switch_mode(Mode::Command);
         self.command_line.focus();
+        self.command_line_history.stop_browsing();
         self.explorer_manager.unfocus();
     }
     pub fn enter_popup_mode(&mut self) {
@@ -318,12 +576,31 @@ impl App {
         self.explorer_manager.focus();
     }
 
+    /// Go to the first search result, or report the query if it is not a valid pattern, in
+    /// which case nothing matches
     pub fn confirm_search_query(&mut self) -> Option<Action> {
+        let query = self.command_line.get_contents();
+        self.command_line_history.add(&Mode::Search, query.clone());
+        self.enter_normal_mode();
+        match SearchPattern::new(&query, &self.search_settings).is_valid() {
+            true => Some(Action::ExplorerAct(ExplorerAction::NextSearchResult)),
+            false => Some(Action::AppAct(AppAction::DisplayMessage(format!(
+                "Invalid pattern: {}",
+                query
+            )))),
+        }
+    }
+
+    pub fn confirm_filter(&mut self) {
+        self.command_line_history
+            .add(&Mode::Filter, self.command_line.get_contents());
         self.enter_normal_mode();
-        Some(Action::ExplorerAct(ExplorerAction::NextSearchResult))
     }
 
     pub fn record_command(&mut self, command: Box<dyn Command>) {
+        if command.is_reversible() {
+            self.last_command = Some(command.clone());
+        }
         let current_path = self.explorer_manager.get_current_path();
         let c_history = self.command_history.get_mut(&current_path);
         if let Some(history) = c_history {
@@ -344,6 +621,7 @@ impl App {
         let command = path_history.unwrap().undo();
         if let Some(mut c) = command {
             c.undo(self);
+            self.explorer_manager.invalidate_listings();
         }
         //FIXME: this should capture the action returned from the command
     }
@@ -353,6 +631,7 @@ impl App {
         let command = path_history.unwrap().redo();
         if let Some(mut c) = command {
             c.execute(self);
+            self.explorer_manager.invalidate_listings();
         }
         //FIXME: this should capture the action returned from the command
     }
@@ -361,6 +640,9 @@ impl App {
         if let Some(action) = command.execute(self) {
             self.action_list.push_back(action);
         }
+        if command.modifies_files() {
+            self.explorer_manager.invalidate_listings();
+        }
         //Record the command after execution (execution can mutate the command)
         self.record_command(command.clone());
     }
@@ -369,6 +651,20 @@ impl App {
             match action {
                 Action::CommandAct(CommandAction::Undo) => self.undo(),
                 Action::CommandAct(CommandAction::Redo) => self.redo(),
+                Action::CountAct(count, action)
+                    if *action == Action::CommandAct(CommandAction::Undo) =>
+                {
+                    for _ in 0..count {
+                        self.undo();
+                    }
+                }
+                Action::CountAct(count, action)
+                    if *action == Action::CommandAct(CommandAction::Redo) =>
+                {
+                    for _ in 0..count {
+                        self.redo();
+                    }
+                }
                 _ => {
                     let command = get_command(self, action.clone());
                     self.run_command(command);
@@ -405,6 +701,9 @@ impl App {
     }
 
     pub fn move_directory(&mut self, path: PathBuf, selected: Option<String>) {
+        if !self.can_enter(&path) {
+            return;
+        }
         let directory_history = self.explorer_manager.get_directory_history();
         // Save the directory to be entered
         directory_history.perform(DirectoryDetails {
@@ -424,9 +723,19 @@ impl App {
 
     pub fn render(&mut self) -> Result<()> {
         self.terminal.draw(|frame| {
+            let count = self
+                .input_machine
+                .get_count()
+                .map(|count| count.to_string())
+                .unwrap_or_default();
+            let recording = self
+                .macro_registers
+                .get_recording_register()
+                .map(|register| format!("recording @{} ", register))
+                .unwrap_or_default();
             let file_config = FileConfig::new(
                 self.config.favourites.clone(),
-                convert_sequence_to_string(self.current_sequence.clone()),
+                recording + &count + &convert_sequence_to_string(self.current_sequence.clone()),
             );
             let areas = get_component_areas(frame);
             self.explorer_manager
@@ -437,6 +746,9 @@ impl App {
             if let &mut Some(ref mut popup) = &mut self.popup {
                 popup.draw(frame, frame.size());
             }
+            if let Some(hint_popup) = &mut self.hint_popup {
+                let _ = hint_popup.draw(frame, frame.size());
+            }
         })?;
         Ok(())
     }
@@ -445,6 +757,9 @@ impl App {
         let abs_path = path::absolute(path.clone());
         match abs_path {
             Ok(abs_path) => {
+                if !self.can_enter(&abs_path) {
+                    return;
+                }
                 self.current_path = abs_path.clone();
                 self.explorer_manager
                     .update_path(abs_path.clone(), selected);
@@ -456,10 +771,31 @@ impl App {
         }
     }
 
+    /// Whether the directory can be listed, reporting why not otherwise
+    fn can_enter(&mut self, path: &Path) -> bool {
+        match fs::read_dir(path) {
+            Ok(_) => true,
+            Err(err) => {
+                self.action_list
+                    .push_back(Action::AppAct(AppAction::DisplayMessage(format!(
+                        "Cannot open {}: {}",
+                        path.display(),
+                        err
+                    ))));
+                false
+            }
+        }
+    }
+
     pub fn command_line_message(&mut self, msg: String) {
         self.command_line.command_line_message(msg);
     }
 
+    /// Show the message after the ones not read yet, e.g. the problems found on start-up
+    pub fn append_command_line_message(&mut self, msg: String) {
+        self.command_line.append_message(msg);
+    }
+
     pub fn command_line_contents(&self) -> String {
         self.command_line.get_contents()
     }
@@ -467,9 +803,9 @@ impl App {
     pub fn go_up(&mut self) {
         let prev_folder = self.current_path.file_name().map(|name| name.to_owned());
         if let Some(prev_folder_name) = prev_folder {
-            let prev_folder_string = prev_folder_name.to_str().unwrap();
+            let prev_folder_string = prev_folder_name.to_string_lossy().to_string();
             let new_absolute_path = self.current_path.parent().unwrap().to_owned();
-            self.move_directory(new_absolute_path, Some(prev_folder_string.to_string()));
+            self.move_directory(new_absolute_path, Some(prev_folder_string));
         }
     }
 
@@ -485,6 +821,16 @@ impl App {
         }
     }
 
+    pub fn save_command_line_history(&self) -> Option<String> {
+        match self
+            .command_line_history
+            .save_to_file(self.get_history_path())
+        {
+            Ok(_) => None,
+            Err(e) => Some(format!("Failed to save the history: {}", e)),
+        }
+    }
+
     pub fn save_config(&self) -> Option<String> {
         let config_path = self.get_config_path();
         match self.config.save_to_file(config_path) {
@@ -498,6 +844,7 @@ impl App {
         let funcs = vec![
             Box::new(App::remove_cache),
             Box::new(App::save_config) as Box<dyn Fn(&App) -> Option<String>>,
+            Box::new(App::save_command_line_history) as Box<dyn Fn(&App) -> Option<String>>,
         ];
         funcs
             .into_iter()
@@ -519,6 +866,7 @@ impl Clone for App {
             current_sequence: self.current_sequence.clone(),
             input_machine: self.input_machine.clone(),
             popup: self.popup.clone(),
+            hint_popup: self.hint_popup.clone(),
             command_history: self.command_history.clone(),
             command_input: self.command_input.clone(),
             exit_status: self.exit_status.clone(),
@@ -527,6 +875,14 @@ impl Clone for App {
             plugins: self.plugins.clone(),
             config: self.config.clone(),
             project_dir: self.project_dir.clone(),
+            clipboard: self.clipboard.clone(),
+            cut_selection: self.cut_selection.clone(),
+            last_command: self.last_command.clone(),
+            macro_registers: self.macro_registers.clone(),
+            command_line_history: self.command_line_history.clone(),
+            search_settings: self.search_settings.clone(),
+            // Clones do not watch the directories
+            directory_watcher: DirectoryWatcher::new(),
         }
     }
 }
@@ -554,8 +910,10 @@ mod tests {
     use std::{env, fs::remove_dir_all};
 
     use ratatui::crossterm::event::{KeyCode, KeyModifiers};
+    use tempdir::TempDir;
 
     use crate::plugin::plugin_helpers::DummyPluginPopUp;
+    use crate::testing_utils::create_custom_testing_folder;
 
     use super::*;
 
@@ -569,6 +927,70 @@ mod tests {
         app.move_directory(starting_path, None);
     }
 
+    #[test]
+    fn test_move_to_unreadable_directory() {
+        let mut app = App::new().unwrap();
+        let starting_path = env::current_dir().unwrap();
+        app.move_directory(starting_path.clone(), None);
+        let abs_path = path::absolute("../tests/").unwrap();
+        app.move_directory(abs_path.clone(), None);
+        app.action_list.clear();
+        app.move_directory(abs_path.join("missing"), None);
+        assert_eq!(app.explorer_manager.get_current_path(), abs_path);
+        assert!(matches!(
+            app.action_list.pop_front(),
+            Some(Action::AppAct(AppAction::DisplayMessage(_)))
+        ));
+        // The directory is not recorded in the history
+        app.undo_directory();
+        assert_eq!(app.explorer_manager.get_current_path(), starting_path);
+        app.move_directory(starting_path, None);
+    }
+
+    #[test]
+    fn test_refresh_changed_directories() {
+        let mut app = App::new().unwrap();
+        let starting_path = env::current_dir().unwrap();
+        let testing_folder = create_custom_testing_folder(vec!["a.txt"]).unwrap();
+        let root_dir = testing_folder.root_dir.path().to_path_buf();
+        app.move_directory(root_dir.clone(), None);
+        app.refresh_changed_directories();
+        assert_eq!(app.explorer_manager.find_elements("").len(), 1);
+
+        fs::write(root_dir.join("b.txt"), "").unwrap();
+        let started = Instant::now();
+        while !app.refresh_changed_directories() && started.elapsed() < Duration::from_secs(2) {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(app.explorer_manager.find_elements("").len(), 2);
+
+        // Changes to the git index, e.g. from `git add` in another terminal, update the statuses
+        let repo = git2::Repository::init(&root_dir).unwrap();
+        app.move_directory(root_dir.clone(), None);
+        app.refresh_changed_directories();
+        assert_eq!(
+            app.explorer_manager.get_git_status("a.txt"),
+            Some(git2::Status::WT_NEW)
+        );
+        assert!(
+            app.directory_watcher
+                .get_watched_paths()
+                .contains(&repo.path().join("index"))
+        );
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("a.txt")).unwrap();
+        index.write().unwrap();
+        let started = Instant::now();
+        while !app.refresh_changed_directories() && started.elapsed() < Duration::from_secs(2) {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(
+            app.explorer_manager.get_git_status("a.txt"),
+            Some(git2::Status::INDEX_NEW)
+        );
+        app.move_directory(starting_path, None);
+    }
+
     #[test]
     fn test_undo_directory() {
         let mut app = App::new().unwrap();
@@ -665,4 +1087,30 @@ mod tests {
         assert_eq!(app.mode, Mode::Normal);
     }
 
+    #[test]
+    fn test_load_keymap() {
+        let mut app = App::new_test().unwrap();
+        let config_dir = TempDir::new("keymap").unwrap();
+        let keymap_path = config_dir.path().join("keymap.json");
+        fs::write(
+            &keymap_path,
+            r#"{"bindings": {"normal": {"Q": "Quit", "<Bad>": "Quit"}}}"#,
+        )
+        .unwrap();
+        // Earlier problems stay shown with the keymap errors
+        app.append_command_line_message("Failed to read the macros file".to_string());
+        let keymap = app.load_keymap_from(keymap_path);
+        assert_eq!(keymap.bindings["normal"].len(), 2);
+
+        let message = app.command_line.current_message.clone().unwrap();
+        assert_eq!(message.len(), 3);
+        assert_eq!(message[0], "Failed to read the macros file");
+        assert!(message[2].contains("<Bad>"));
+        app.command_line.current_message = None;
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('Q'), KeyModifiers::NONE));
+        assert_eq!(
+            app.action_list.pop_front(),
+            Some(Action::AppAct(AppAction::Quit))
+        );
+    }
 }
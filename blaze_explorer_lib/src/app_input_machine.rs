@@ -1,22 +1,30 @@
 // Describes the Input machine for the main app - the implementation of the trait and the keymaps
 // used in the main app
 use std::collections::HashMap;
+use std::time::Duration;
 
 use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
 use crate::{
-    action::{Action, AppAction, CommandAction, ExplorerAction, TextAction},
+    action::{
+        Action, AppAction, CommandAction, CursorMovement, ExplorerAction, Motion, Operator,
+        RegisterAction, TextAction,
+    },
+    components::explorer_table::explorer_sorting::{SortKey, SortOrder},
     core_features::{
         add::open_add_popup,
         rename::{open_copy_rename_popup, open_rename_popup},
     },
     custom_action,
     function_helpers::{pull_current_branch, push_current_branch},
-    input_machine::{InputMachine, KeyMapNode, KeyProcessingResult},
+    input_machine::{InputMachine, KeyMapNode, KeyProcessingResult, process_keys},
     mode::Mode,
     plugin::plugin_popup::PluginPopUp,
 };
 
+/// Time after which a pending key sequence is resolved, as with Vim's `timeoutlen`
+pub const DEFAULT_SEQUENCE_TIMEOUT: Duration = Duration::from_millis(1000);
+
 type DefaultActionMap = HashMap<Mode, Box<fn(KeyEvent) -> Option<Action>>>;
 fn get_default_search_command_action(last_key: KeyEvent) -> Option<Action> {
     match last_key.code {
@@ -29,10 +37,42 @@ pub fn get_none_action(last_key: KeyEvent) -> Option<Action> {
     None
 }
 
+/// Operator waiting for its motion, e.g. after typing `2d` in `2d3j`
+#[derive(Debug, Clone, PartialEq)]
+struct PendingOperator {
+    operator: Operator,
+    operator_key: KeyEvent,
+    count: Option<usize>,
+    motion_count: Option<usize>,
+    motion_keys: Vec<KeyEvent>,
+}
+
+impl PendingOperator {
+    /// Counts typed before the operator and before the motion multiply, e.g. `2d3j` deletes
+    /// 6 rows down
+    fn into_action(self, motion: Motion) -> Action {
+        let action = Action::AppAct(AppAction::ApplyOperator(self.operator, motion));
+        match (self.count, self.motion_count) {
+            (None, None) => action,
+            (count, motion_count) => Action::CountAct(
+                count.unwrap_or(1).saturating_mul(motion_count.unwrap_or(1)),
+                Box::new(action),
+            ),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct AppInputMachine<T> {
     keymap_nodes: HashMap<Mode, KeyMapNode<T>>,
     default_actions: DefaultActionMap,
+    count: Option<usize>,
+    motion_keymap: KeyMapNode<Motion>,
+    pending_operator: Option<PendingOperator>,
+    pending_register: Option<RegisterAction>,
+    timeout: Option<Duration>,
+    /// Keys typed after a resolved sequence, which need to be processed again
+    replay_keys: Vec<KeyEvent>,
 }
 
 impl InputMachine for AppInputMachine<Action> {
@@ -42,8 +82,41 @@ impl InputMachine for AppInputMachine<Action> {
         current_sequence: &mut Vec<KeyEvent>,
         input_key: KeyEvent,
     ) -> KeyProcessingResult<Action> {
+        if let Some(register_action) = self.pending_register.take() {
+            return match get_register_action(register_action, input_key) {
+                Some(action) => KeyProcessingResult::Complete(action),
+                None => KeyProcessingResult::Invalid,
+            };
+        }
+        if let Some(pending_operator) = self.pending_operator.take() {
+            return self.process_motion_keys(pending_operator, current_sequence, input_key);
+        }
+        if let Some(digit) = self.get_count_digit(mode, current_sequence, input_key) {
+            let count = self.count.unwrap_or(0).saturating_mul(10);
+            self.count = Some(count.saturating_add(digit));
+            return KeyProcessingResult::Incomplete;
+        }
         let keymap = self.keymap_nodes.get(mode).unwrap();
-        process_app_keys(keymap, current_sequence, input_key)
+        let mut typed_sequence = current_sequence.clone();
+        typed_sequence.push(input_key);
+        match process_app_keys(keymap, current_sequence, input_key) {
+            // The sequence may continue a shorter binding, e.g. `gx` with `g` and `gg` bound
+            KeyProcessingResult::Invalid => {
+                match self.split_at_longest_action(mode, &typed_sequence) {
+                    Some((action, last_key)) => self.finish_sequence(
+                        KeyProcessingResult::Complete(action),
+                        current_sequence,
+                        last_key,
+                    ),
+                    None => self.finish_sequence(
+                        KeyProcessingResult::Invalid,
+                        current_sequence,
+                        input_key,
+                    ),
+                }
+            }
+            result => self.finish_sequence(result, current_sequence, input_key),
+        }
     }
 
     fn get_default_action(&self, mode: &Mode, last_key: KeyEvent) -> Option<Action> {
@@ -52,10 +125,107 @@ impl InputMachine for AppInputMachine<Action> {
 }
 
 impl AppInputMachine<Action> {
+    /// Apply the count and the operator to the result of a key sequence. `last_key` is the key
+    /// completing the sequence.
+    fn finish_sequence(
+        &mut self,
+        result: KeyProcessingResult<Action>,
+        current_sequence: &mut Vec<KeyEvent>,
+        last_key: KeyEvent,
+    ) -> KeyProcessingResult<Action> {
+        match result {
+            KeyProcessingResult::Complete(Action::AppAct(AppAction::StartOperator(operator))) => {
+                // Keep the operator key displayed until the motion is complete
+                current_sequence.push(last_key);
+                self.pending_operator = Some(PendingOperator {
+                    operator,
+                    operator_key: last_key,
+                    count: self.count.take(),
+                    motion_count: None,
+                    motion_keys: Vec::new(),
+                });
+                KeyProcessingResult::Incomplete
+            }
+            KeyProcessingResult::Complete(action) => match self.count.take() {
+                Some(count) => {
+                    KeyProcessingResult::Complete(Action::CountAct(count, Box::new(action)))
+                }
+                None => KeyProcessingResult::Complete(action),
+            },
+            KeyProcessingResult::Invalid => {
+                self.count = None;
+                KeyProcessingResult::Invalid
+            }
+            KeyProcessingResult::Incomplete => KeyProcessingResult::Incomplete,
+        }
+    }
+
+    /// Action bound to the longest prefix of the sequence, along with the last key of the prefix.
+    /// The keys typed after the prefix are kept to be processed again, as they may start another
+    /// sequence.
+    fn split_at_longest_action(
+        &mut self,
+        mode: &Mode,
+        sequence: &[KeyEvent],
+    ) -> Option<(Action, KeyEvent)> {
+        let (length, action) = self.keymap_nodes.get(mode)?.get_longest_action(sequence)?;
+        let action = action.clone();
+        self.replay_keys.extend_from_slice(&sequence[length..]);
+        Some((action, sequence[length - 1]))
+    }
+
+    /// Resolve the pending sequence once no key was typed for the timeout, as if a key continuing
+    /// no binding was typed. Sequences without any bound prefix are discarded. Operators keep
+    /// waiting for their motion.
+    pub fn resolve_timeout(
+        &mut self,
+        mode: &Mode,
+        current_sequence: &mut Vec<KeyEvent>,
+    ) -> KeyProcessingResult<Action> {
+        if self.pending_operator.is_some() || current_sequence.is_empty() {
+            return KeyProcessingResult::Incomplete;
+        }
+        let sequence = std::mem::take(current_sequence);
+        match self.split_at_longest_action(mode, &sequence) {
+            Some((action, last_key)) => self.finish_sequence(
+                KeyProcessingResult::Complete(action),
+                current_sequence,
+                last_key,
+            ),
+            None => {
+                self.count = None;
+                KeyProcessingResult::Invalid
+            }
+        }
+    }
+
+    /// Keys typed after the last resolved sequence, to be processed again
+    pub fn take_replay_keys(&mut self) -> Vec<KeyEvent> {
+        std::mem::take(&mut self.replay_keys)
+    }
+
+    /// Time after which the pending sequence is resolved, if it can time out
+    pub fn get_sequence_timeout(&self) -> Option<Duration> {
+        match self.pending_operator {
+            Some(_) => None,
+            None => self.timeout,
+        }
+    }
+
+    /// Set the time after which pending sequences are resolved. `None` makes them wait forever.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    pub fn get_timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
     pub fn new() -> Self {
         let mut keymap_nodes = HashMap::new();
         keymap_nodes.insert(Mode::Normal, default_key_map());
         keymap_nodes.insert(Mode::Search, search_key_map());
+        keymap_nodes.insert(Mode::Filter, filter_key_map());
         keymap_nodes.insert(Mode::Command, command_key_map());
         keymap_nodes.insert(Mode::PopUp, KeyMapNode::new());
         keymap_nodes.insert(Mode::Visual, visual_key_map());
@@ -68,11 +238,134 @@ impl AppInputMachine<Action> {
         default_actions.insert(Mode::PopUp, Box::new(get_none_action));
         default_actions.insert(Mode::Visual, Box::new(get_none_action));
         default_actions.insert(Mode::Search, Box::new(get_default_search_command_action));
+        default_actions.insert(Mode::Filter, Box::new(get_default_search_command_action));
         default_actions.insert(Mode::Command, Box::new(get_default_search_command_action));
 
         AppInputMachine {
             keymap_nodes,
             default_actions,
+            count: None,
+            motion_keymap: motion_key_map(),
+            pending_operator: None,
+            pending_register: None,
+            timeout: Some(DEFAULT_SEQUENCE_TIMEOUT),
+            replay_keys: Vec::new(),
+        }
+    }
+
+    /// Treat the next key as a register name, e.g. `a` after `q`
+    pub fn await_register(&mut self, register_action: RegisterAction) {
+        self.pending_register = Some(register_action);
+    }
+
+    /// Count typed before the current key sequence, e.g. 5 in `5j` or `5dd`
+    pub fn get_count(&self) -> Option<usize> {
+        match &self.pending_operator {
+            Some(pending_operator) => pending_operator.count,
+            None => self.count,
+        }
+    }
+
+    /// Bindings completing the current sequence in the given mode, e.g. `ff` and `fg` after
+    /// `<space>`. The sequences are given without the keys typed so far.
+    pub fn get_continuations(
+        &self,
+        mode: &Mode,
+        current_sequence: &[KeyEvent],
+    ) -> Vec<(Vec<KeyEvent>, Action)> {
+        if self.pending_operator.is_some() || self.pending_register.is_some() {
+            return Vec::new();
+        }
+        match self
+            .keymap_nodes
+            .get(mode)
+            .and_then(|keymap| keymap.get_node(current_sequence))
+        {
+            Some(node) => node
+                .get_continuations()
+                .into_iter()
+                .map(|(sequence, action)| (sequence, action.clone()))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Process a key typed after an operator: a count, the motion, or the operator key again to
+    /// apply it to the selected row. Any other key cancels the operator.
+    fn process_motion_keys(
+        &mut self,
+        mut pending_operator: PendingOperator,
+        current_sequence: &mut Vec<KeyEvent>,
+        input_key: KeyEvent,
+    ) -> KeyProcessingResult<Action> {
+        current_sequence.push(input_key);
+        if pending_operator.motion_keys.is_empty() && input_key.modifiers == KeyModifiers::NONE {
+            if input_key == pending_operator.operator_key {
+                current_sequence.clear();
+                return KeyProcessingResult::Complete(pending_operator.into_action(Motion::Line));
+            }
+            let digit = match input_key.code {
+                KeyCode::Char(ch) => ch.to_digit(10),
+                _ => None,
+            };
+            if let Some(digit) = digit
+                && (digit != 0 || pending_operator.motion_count.is_some())
+            {
+                let motion_count = pending_operator.motion_count.unwrap_or(0);
+                pending_operator.motion_count = Some(
+                    motion_count
+                        .saturating_mul(10)
+                        .saturating_add(digit as usize),
+                );
+                self.pending_operator = Some(pending_operator);
+                return KeyProcessingResult::Incomplete;
+            }
+        }
+        match process_keys(
+            &self.motion_keymap,
+            &mut pending_operator.motion_keys,
+            input_key,
+        ) {
+            KeyProcessingResult::Complete(motion) => {
+                current_sequence.clear();
+                KeyProcessingResult::Complete(pending_operator.into_action(motion))
+            }
+            KeyProcessingResult::Incomplete => {
+                self.pending_operator = Some(pending_operator);
+                KeyProcessingResult::Incomplete
+            }
+            KeyProcessingResult::Invalid => {
+                current_sequence.clear();
+                KeyProcessingResult::Invalid
+            }
+        }
+    }
+
+    /// Check whether the key continues a count prefix. Counts are accepted in the normal and
+    /// visual modes before any other key of the sequence, as long as the digit is not bound to
+    /// anything itself. Zero can only follow other digits.
+    fn get_count_digit(
+        &self,
+        mode: &Mode,
+        current_sequence: &[KeyEvent],
+        input_key: KeyEvent,
+    ) -> Option<usize> {
+        if !matches!(mode, Mode::Normal | Mode::Visual)
+            || !current_sequence.is_empty()
+            || input_key.modifiers != KeyModifiers::NONE
+        {
+            return None;
+        }
+        let digit = match input_key.code {
+            KeyCode::Char(ch) => ch.to_digit(10)? as usize,
+            _ => return None,
+        };
+        if digit == 0 && self.count.is_none() {
+            return None;
+        }
+        match self.keymap_nodes.get(mode)?.get_node(&[input_key]) {
+            Some(_) => None,
+            None => Some(digit),
         }
     }
 
@@ -100,12 +393,44 @@ impl AppInputMachine<Action> {
             .add_sequence(sequence, action);
     }
 
+    /// Remove the binding of a sequence in the given mode. Returns true if anything was unbound.
+    pub fn detach_binding(&mut self, mode: Mode, sequence: &[KeyEvent]) -> bool {
+        self.keymap_nodes
+            .get_mut(&mode)
+            .unwrap()
+            .remove_sequence(sequence)
+    }
+
     pub fn attach_from_hashmap(&mut self, keymap: HashMap<(Mode, Vec<KeyEvent>), Action>) {
         for ((mode, seq), action) in keymap {
             self.attach_binding(mode, seq, action);
         }
     }
 }
+/// Action for the register typed after `q` or `@`. Registers are letters and digits, while `@@`
+/// replays the last macro.
+fn get_register_action(register_action: RegisterAction, input_key: KeyEvent) -> Option<Action> {
+    if !matches!(
+        input_key.modifiers,
+        KeyModifiers::NONE | KeyModifiers::SHIFT
+    ) {
+        return None;
+    }
+    let KeyCode::Char(register) = input_key.code else {
+        return None;
+    };
+    match (register_action, register) {
+        (RegisterAction::ReplayMacro, '@') => Some(Action::AppAct(AppAction::ReplayLastMacro)),
+        (_, register) if !register.is_ascii_alphanumeric() => None,
+        (RegisterAction::RecordMacro, register) => {
+            Some(Action::AppAct(AppAction::StartRecording(register)))
+        }
+        (RegisterAction::ReplayMacro, register) => {
+            Some(Action::AppAct(AppAction::ReplayRegister(register)))
+        }
+    }
+}
+
 pub fn process_app_keys(
     keymap: &KeyMapNode<Action>,
     current_sequence: &mut Vec<KeyEvent>,
@@ -114,11 +439,12 @@ pub fn process_app_keys(
     current_sequence.push(input_key);
     match keymap.get_node(current_sequence) {
         Some(node) => match &node.action {
-            None => KeyProcessingResult::Incomplete, // More keys can follow
-            Some(action) => {
+            // More keys can follow, including after a binding which is the prefix of another one
+            Some(action) if !node.has_children() => {
                 current_sequence.clear();
                 KeyProcessingResult::Complete(action.clone()) // Final action reached
             }
+            _ => KeyProcessingResult::Incomplete,
         },
         None => {
             current_sequence.clear(); // Remove invalid key
@@ -132,6 +458,10 @@ pub fn default_key_map() -> KeyMapNode<Action> {
         vec![KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE)],
         Action::ExplorerAct(ExplorerAction::NextSearchResult),
     );
+    root.add_sequence(
+        vec![KeyEvent::new(KeyCode::Char('N'), KeyModifiers::NONE)],
+        Action::ExplorerAct(ExplorerAction::PreviousSearchResult),
+    );
     root.add_sequence(
         vec![KeyEvent::new(KeyCode::Char('k'), KeyModifiers::NONE)],
         Action::ExplorerAct(ExplorerAction::SelectUp),
@@ -164,7 +494,11 @@ pub fn default_key_map() -> KeyMapNode<Action> {
         Action::AppAct(AppAction::SwitchMode(Mode::Search)),
     );
     root.add_sequence(
-        vec![KeyEvent::new(KeyCode::Char(':'), KeyModifiers::SHIFT)],
+        vec![KeyEvent::new(KeyCode::Char('F'), KeyModifiers::NONE)],
+        Action::AppAct(AppAction::SwitchMode(Mode::Filter)),
+    );
+    root.add_sequence(
+        vec![KeyEvent::new(KeyCode::Char(':'), KeyModifiers::NONE)],
         Action::AppAct(AppAction::SwitchMode(Mode::Command)),
     );
     root.add_sequence(
@@ -188,23 +522,37 @@ pub fn default_key_map() -> KeyMapNode<Action> {
         custom_action!(open_add_popup),
     );
     root.add_sequence(
-        vec![
-            KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE),
-            KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE),
-        ],
-        Action::AppAct(AppAction::Delete),
+        vec![KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE)],
+        Action::AppAct(AppAction::StartOperator(Operator::Delete)),
     );
     root.add_sequence(
-        vec![
-            KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE),
-            KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE),
-        ],
-        Action::AppAct(AppAction::Copy),
+        vec![KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE)],
+        Action::AppAct(AppAction::StartOperator(Operator::Yank)),
+    );
+    root.add_sequence(
+        vec![KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE)],
+        Action::AppAct(AppAction::StartOperator(Operator::Cut)),
+    );
+    root.add_sequence(
+        vec![KeyEvent::new(KeyCode::Char('m'), KeyModifiers::NONE)],
+        Action::AppAct(AppAction::StartOperator(Operator::Move)),
     );
     root.add_sequence(
         vec![KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE)],
         Action::AppAct(AppAction::Paste),
     );
+    root.add_sequence(
+        vec![KeyEvent::new(KeyCode::Char('.'), KeyModifiers::NONE)],
+        Action::AppAct(AppAction::RepeatLastCommand),
+    );
+    root.add_sequence(
+        vec![KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE)],
+        Action::AppAct(AppAction::RecordMacro),
+    );
+    root.add_sequence(
+        vec![KeyEvent::new(KeyCode::Char('@'), KeyModifiers::NONE)],
+        Action::AppAct(AppAction::ReplayMacro),
+    );
     root.add_sequence(
         vec![KeyEvent::new(KeyCode::Char('u'), KeyModifiers::NONE)],
         Action::CommandAct(CommandAction::Undo),
@@ -293,6 +641,20 @@ pub fn default_key_map() -> KeyMapNode<Action> {
         ],
         Action::AppAct(AppAction::ExecuteFunction(Box::new(pull_current_branch))),
     );
+    for (ch, action) in [
+        ('s', AppAction::Stage),
+        ('u', AppAction::Unstage),
+        ('d', AppAction::DiscardChanges),
+    ] {
+        root.add_sequence(
+            vec![
+                KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Char('h'), KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE),
+            ],
+            Action::AppAct(action),
+        );
+    }
 
     root.add_sequence(
         vec![
@@ -303,6 +665,83 @@ pub fn default_key_map() -> KeyMapNode<Action> {
         Action::ExplorerAct(ExplorerAction::ToggleToFavourites),
     );
 
+    for (ch, sort_key) in [
+        ('n', SortKey::Name),
+        ('s', SortKey::Size),
+        ('m', SortKey::Modified),
+        ('e', SortKey::Extension),
+        ('t', SortKey::Type),
+    ] {
+        root.add_sequence(
+            vec![
+                KeyEvent::new(KeyCode::Char('o'), KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE),
+            ],
+            Action::ExplorerAct(ExplorerAction::SortBy(SortOrder::new(sort_key, false))),
+        );
+    }
+    root.add_sequence(
+        vec![
+            KeyEvent::new(KeyCode::Char('o'), KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::Char('r'), KeyModifiers::NONE),
+        ],
+        Action::ExplorerAct(ExplorerAction::ReverseSort),
+    );
+    root.add_sequence(
+        vec![
+            KeyEvent::new(KeyCode::Char('z'), KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::Char('h'), KeyModifiers::NONE),
+        ],
+        Action::ExplorerAct(ExplorerAction::ToggleHidden),
+    );
+    root.add_sequence(
+        vec![
+            KeyEvent::new(KeyCode::Char('z'), KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::Char('i'), KeyModifiers::NONE),
+        ],
+        Action::ExplorerAct(ExplorerAction::ToggleIgnored),
+    );
+    root.add_sequence(
+        vec![
+            KeyEvent::new(KeyCode::Char('z'), KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::Char('r'), KeyModifiers::NONE),
+        ],
+        Action::ExplorerAct(ExplorerAction::Refresh),
+    );
+
+    root
+}
+
+/// Motions accepted after an operator, e.g. `G` in `dG`
+pub fn motion_key_map() -> KeyMapNode<Motion> {
+    let mut root = KeyMapNode::new();
+    root.add_sequence(
+        vec![KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE)],
+        Motion::Down,
+    );
+    root.add_sequence(
+        vec![KeyEvent::new(KeyCode::Char('k'), KeyModifiers::NONE)],
+        Motion::Up,
+    );
+    root.add_sequence(
+        vec![
+            KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE),
+        ],
+        Motion::FirstRow,
+    );
+    root.add_sequence(
+        vec![KeyEvent::new(KeyCode::Char('G'), KeyModifiers::NONE)],
+        Motion::LastRow,
+    );
+    root.add_sequence(
+        vec![KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE)],
+        Motion::NextSearchResult,
+    );
+    root.add_sequence(
+        vec![KeyEvent::new(KeyCode::Char('N'), KeyModifiers::NONE)],
+        Motion::PreviousSearchResult,
+    );
     root
 }
 
@@ -324,6 +763,29 @@ pub fn search_key_map() -> KeyMapNode<Action> {
         vec![KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)],
         Action::AppAct(AppAction::ConfirmSearchQuery),
     );
+    add_line_editing_keys(&mut root);
+    root
+}
+
+pub fn filter_key_map() -> KeyMapNode<Action> {
+    let mut root = KeyMapNode::new();
+    root.add_sequence(
+        vec![KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)],
+        Action::ExplorerAct(ExplorerAction::ClearFilter),
+    );
+    root.add_sequence(
+        vec![KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL)],
+        Action::TextAct(TextAction::EraseText),
+    );
+    root.add_sequence(
+        vec![KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE)],
+        Action::TextAct(TextAction::DropKey),
+    );
+    root.add_sequence(
+        vec![KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)],
+        Action::AppAct(AppAction::ConfirmFilter),
+    );
+    add_line_editing_keys(&mut root);
     root
 }
 pub fn command_key_map() -> KeyMapNode<Action> {
@@ -344,8 +806,44 @@ pub fn command_key_map() -> KeyMapNode<Action> {
         vec![KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)],
         Action::AppAct(AppAction::ConfirmCommand),
     );
+    root.add_sequence(
+        vec![KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE)],
+        Action::TextAct(TextAction::NextCompletion),
+    );
+    root.add_sequence(
+        vec![KeyEvent::new(KeyCode::BackTab, KeyModifiers::NONE)],
+        Action::TextAct(TextAction::PreviousCompletion),
+    );
+    add_line_editing_keys(&mut root);
     root
 }
+
+/// Cursor movement, word deletion and history browsing shared by the search and command modes
+fn add_line_editing_keys(root: &mut KeyMapNode<Action>) {
+    root.add_sequence(
+        vec![KeyEvent::new(KeyCode::Char('w'), KeyModifiers::CONTROL)],
+        Action::TextAct(TextAction::DropWord),
+    );
+    for (code, movement) in [
+        (KeyCode::Left, CursorMovement::Left),
+        (KeyCode::Right, CursorMovement::Right),
+        (KeyCode::Home, CursorMovement::Start),
+        (KeyCode::End, CursorMovement::End),
+    ] {
+        root.add_sequence(
+            vec![KeyEvent::new(code, KeyModifiers::NONE)],
+            Action::TextAct(TextAction::MoveCursor(movement)),
+        );
+    }
+    root.add_sequence(
+        vec![KeyEvent::new(KeyCode::Up, KeyModifiers::NONE)],
+        Action::TextAct(TextAction::PreviousEntry),
+    );
+    root.add_sequence(
+        vec![KeyEvent::new(KeyCode::Down, KeyModifiers::NONE)],
+        Action::TextAct(TextAction::NextEntry),
+    );
+}
 pub fn visual_key_map() -> KeyMapNode<Action> {
     let mut root = KeyMapNode::new();
     root.add_sequence(
@@ -376,5 +874,249 @@ pub fn visual_key_map() -> KeyMapNode<Action> {
         vec![KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE)],
         Action::AppAct(AppAction::Paste),
     );
+    for (ch, action) in [
+        ('s', AppAction::Stage),
+        ('u', AppAction::Unstage),
+        ('d', AppAction::DiscardChanges),
+    ] {
+        root.add_sequence(
+            vec![
+                KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Char('h'), KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE),
+            ],
+            Action::AppAct(action),
+        );
+    }
     root
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::input_machine::input_machine_helpers::convert_str_to_events;
+
+    use super::*;
+
+    fn press_keys(
+        input_machine: &mut AppInputMachine<Action>,
+        mode: &Mode,
+        keys: &str,
+    ) -> KeyProcessingResult<Action> {
+        let mut current_sequence = Vec::new();
+        let mut result = KeyProcessingResult::Invalid;
+        for key in convert_str_to_events(keys) {
+            result = input_machine.process_keys(mode, &mut current_sequence, key);
+        }
+        result
+    }
+
+    #[test]
+    fn test_count_prefix() {
+        let mut input_machine = AppInputMachine::new();
+        assert_eq!(
+            press_keys(&mut input_machine, &Mode::Normal, "10"),
+            KeyProcessingResult::Incomplete
+        );
+        assert_eq!(input_machine.get_count(), Some(10));
+        assert_eq!(
+            press_keys(&mut input_machine, &Mode::Normal, "G"),
+            KeyProcessingResult::Complete(Action::CountAct(
+                10,
+                Box::new(Action::ExplorerAct(ExplorerAction::JumpToEnd))
+            ))
+        );
+        assert_eq!(input_machine.get_count(), None);
+
+        assert_eq!(
+            press_keys(&mut input_machine, &Mode::Normal, "3dd"),
+            KeyProcessingResult::Complete(Action::CountAct(
+                3,
+                Box::new(Action::AppAct(AppAction::ApplyOperator(
+                    Operator::Delete,
+                    Motion::Line
+                )))
+            ))
+        );
+        assert_eq!(
+            press_keys(&mut input_machine, &Mode::Normal, "j"),
+            KeyProcessingResult::Complete(Action::ExplorerAct(ExplorerAction::SelectDown))
+        );
+    }
+
+    #[test]
+    fn test_count_prefix_rejected() {
+        let mut input_machine = AppInputMachine::new();
+        // A count cannot start with zero
+        assert_eq!(
+            press_keys(&mut input_machine, &Mode::Normal, "0"),
+            KeyProcessingResult::Invalid
+        );
+        // An invalid key drops the count
+        press_keys(&mut input_machine, &Mode::Normal, "5Z");
+        assert_eq!(input_machine.get_count(), None);
+        // Digits are inserted as text in the command mode
+        assert_eq!(
+            press_keys(&mut input_machine, &Mode::Command, "5"),
+            KeyProcessingResult::Invalid
+        );
+        // Digits bound to an action are not treated as a count
+        input_machine.attach_binding(
+            Mode::Normal,
+            convert_str_to_events("1"),
+            Action::AppAct(AppAction::Quit),
+        );
+        assert_eq!(
+            press_keys(&mut input_machine, &Mode::Normal, "1"),
+            KeyProcessingResult::Complete(Action::AppAct(AppAction::Quit))
+        );
+    }
+
+    #[test]
+    fn test_operator_motions() {
+        let mut input_machine = AppInputMachine::new();
+        assert_eq!(
+            press_keys(&mut input_machine, &Mode::Normal, "dG"),
+            KeyProcessingResult::Complete(Action::AppAct(AppAction::ApplyOperator(
+                Operator::Delete,
+                Motion::LastRow
+            )))
+        );
+        assert_eq!(
+            press_keys(&mut input_machine, &Mode::Normal, "y3j"),
+            KeyProcessingResult::Complete(Action::CountAct(
+                3,
+                Box::new(Action::AppAct(AppAction::ApplyOperator(
+                    Operator::Yank,
+                    Motion::Down
+                )))
+            ))
+        );
+        assert_eq!(
+            press_keys(&mut input_machine, &Mode::Normal, "2x3k"),
+            KeyProcessingResult::Complete(Action::CountAct(
+                6,
+                Box::new(Action::AppAct(AppAction::ApplyOperator(
+                    Operator::Cut,
+                    Motion::Up
+                )))
+            ))
+        );
+        assert_eq!(
+            press_keys(&mut input_machine, &Mode::Normal, "mgg"),
+            KeyProcessingResult::Complete(Action::AppAct(AppAction::ApplyOperator(
+                Operator::Move,
+                Motion::FirstRow
+            )))
+        );
+
+        // The operator is displayed while waiting for the motion
+        let mut current_sequence = Vec::new();
+        for key in convert_str_to_events("4dg") {
+            input_machine.process_keys(&Mode::Normal, &mut current_sequence, key);
+        }
+        assert_eq!(current_sequence, convert_str_to_events("dg"));
+        assert_eq!(input_machine.get_count(), Some(4));
+
+        // Any key other than a motion cancels the operator
+        assert_eq!(
+            press_keys(&mut input_machine, &Mode::Normal, "<Esc>"),
+            KeyProcessingResult::Invalid
+        );
+        assert_eq!(input_machine.get_count(), None);
+        assert_eq!(
+            press_keys(&mut input_machine, &Mode::Normal, "j"),
+            KeyProcessingResult::Complete(Action::ExplorerAct(ExplorerAction::SelectDown))
+        );
+    }
+
+    #[test]
+    fn test_register_keys() {
+        let mut input_machine = AppInputMachine::new();
+        assert_eq!(
+            press_keys(&mut input_machine, &Mode::Normal, "q"),
+            KeyProcessingResult::Complete(Action::AppAct(AppAction::RecordMacro))
+        );
+        input_machine.await_register(RegisterAction::RecordMacro);
+        assert_eq!(
+            press_keys(&mut input_machine, &Mode::Normal, "a"),
+            KeyProcessingResult::Complete(Action::AppAct(AppAction::StartRecording('a')))
+        );
+        input_machine.await_register(RegisterAction::ReplayMacro);
+        assert_eq!(
+            press_keys(&mut input_machine, &Mode::Normal, "@"),
+            KeyProcessingResult::Complete(Action::AppAct(AppAction::ReplayLastMacro))
+        );
+        input_machine.await_register(RegisterAction::RecordMacro);
+        assert_eq!(
+            press_keys(&mut input_machine, &Mode::Normal, "<Esc>"),
+            KeyProcessingResult::Invalid
+        );
+        // The register is only awaited for a single key
+        assert_eq!(
+            press_keys(&mut input_machine, &Mode::Normal, "j"),
+            KeyProcessingResult::Complete(Action::ExplorerAct(ExplorerAction::SelectDown))
+        );
+    }
+
+    #[test]
+    fn test_ambiguous_sequences() {
+        let mut input_machine = AppInputMachine::new();
+        input_machine.attach_binding(
+            Mode::Normal,
+            convert_str_to_events("g"),
+            Action::AppAct(AppAction::Quit),
+        );
+        // `g` waits for `gg`
+        assert_eq!(
+            press_keys(&mut input_machine, &Mode::Normal, "g"),
+            KeyProcessingResult::Incomplete
+        );
+        assert_eq!(
+            press_keys(&mut input_machine, &Mode::Normal, "gg"),
+            KeyProcessingResult::Complete(Action::ExplorerAct(ExplorerAction::JumpToStart))
+        );
+        assert!(input_machine.take_replay_keys().is_empty());
+
+        // A key continuing no binding completes `g` and is processed again
+        assert_eq!(
+            press_keys(&mut input_machine, &Mode::Normal, "gj"),
+            KeyProcessingResult::Complete(Action::AppAct(AppAction::Quit))
+        );
+        assert_eq!(input_machine.take_replay_keys(), convert_str_to_events("j"));
+
+        // The timeout completes `g`, including its count
+        let mut current_sequence = Vec::new();
+        for key in convert_str_to_events("3g") {
+            input_machine.process_keys(&Mode::Normal, &mut current_sequence, key);
+        }
+        assert_eq!(
+            input_machine.resolve_timeout(&Mode::Normal, &mut current_sequence),
+            KeyProcessingResult::Complete(Action::CountAct(
+                3,
+                Box::new(Action::AppAct(AppAction::Quit))
+            ))
+        );
+        assert!(current_sequence.is_empty());
+
+        // Sequences without any complete prefix are discarded
+        for key in convert_str_to_events("<space>h") {
+            input_machine.process_keys(&Mode::Normal, &mut current_sequence, key);
+        }
+        assert_eq!(
+            input_machine.resolve_timeout(&Mode::Normal, &mut current_sequence),
+            KeyProcessingResult::Invalid
+        );
+        assert!(current_sequence.is_empty());
+        assert!(input_machine.take_replay_keys().is_empty());
+
+        // Operators keep waiting for their motion
+        for key in convert_str_to_events("d") {
+            input_machine.process_keys(&Mode::Normal, &mut current_sequence, key);
+        }
+        assert_eq!(input_machine.get_sequence_timeout(), None);
+        assert_eq!(
+            input_machine.resolve_timeout(&Mode::Normal, &mut current_sequence),
+            KeyProcessingResult::Incomplete
+        );
+    }
+}
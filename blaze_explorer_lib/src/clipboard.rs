@@ -0,0 +1,389 @@
+use std::env;
+use std::fmt::Debug;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command as ProcessCommand, Stdio};
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use percent_encoding::{AsciiSet, CONTROLS, percent_decode_str, percent_encode};
+
+/// Characters escaped when writing paths as `file://` URIs. Path separators are kept as they are.
+const URI_ESCAPE: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'[')
+    .add(b']')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// A place the copied file paths are stored in, so that they can be pasted later on. Every
+/// implementation stores a list of paths, which makes the paste (and its undo) logic independent of
+/// the platform.
+pub trait Clipboard: ClipboardClone + Debug {
+    fn name(&self) -> &'static str;
+    fn write_paths(&mut self, paths: &[PathBuf]) -> io::Result<()>;
+    fn read_paths(&mut self) -> io::Result<Vec<PathBuf>>;
+}
+
+pub trait ClipboardClone {
+    fn clone_box(&self) -> Box<dyn Clipboard>;
+}
+
+impl<T> ClipboardClone for T
+where
+    T: 'static + Clipboard + Clone,
+{
+    fn clone_box(&self) -> Box<dyn Clipboard> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn Clipboard> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// Clipboard kept within the app itself. Used whenever no system clipboard is available.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RegisterClipboard {
+    paths: Vec<PathBuf>,
+}
+
+impl RegisterClipboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Clipboard for RegisterClipboard {
+    fn name(&self) -> &'static str {
+        "register"
+    }
+
+    fn write_paths(&mut self, paths: &[PathBuf]) -> io::Result<()> {
+        self.paths = paths.to_vec();
+        Ok(())
+    }
+
+    fn read_paths(&mut self) -> io::Result<Vec<PathBuf>> {
+        match self.paths.is_empty() {
+            true => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "Clipboard is empty",
+            )),
+            false => Ok(self.paths.clone()),
+        }
+    }
+}
+
+/// Native Windows clipboard, storing the paths in the file list format used by the Explorer.
+#[cfg(windows)]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WindowsClipboard;
+
+#[cfg(windows)]
+impl Clipboard for WindowsClipboard {
+    fn name(&self) -> &'static str {
+        "windows"
+    }
+
+    fn write_paths(&mut self, paths: &[PathBuf]) -> io::Result<()> {
+        use clipboard_win::{Clipboard as WinClipboard, Setter, empty, formats::FileList};
+
+        let to_io_error = |e: clipboard_win::ErrorCode| io::Error::other(e.to_string());
+        let _clip = WinClipboard::new_attempts(10).map_err(to_io_error)?;
+        empty().map_err(to_io_error)?;
+        let str_paths = paths
+            .iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect::<Vec<String>>();
+        FileList.write_clipboard(&str_paths).map_err(to_io_error)
+    }
+
+    fn read_paths(&mut self) -> io::Result<Vec<PathBuf>> {
+        use clipboard_win::{Clipboard as WinClipboard, formats::FileList, get_clipboard};
+
+        let to_io_error = |e: clipboard_win::ErrorCode| io::Error::other(e.to_string());
+        let _clip = WinClipboard::new_attempts(10).map_err(to_io_error)?;
+        let str_files: Vec<String> = get_clipboard(FileList).map_err(to_io_error)?;
+        Ok(str_files.iter().map(PathBuf::from).collect())
+    }
+}
+
+/// System clipboard driven by an external tool, exchanging the paths as a `text/uri-list`, which
+/// is the format file managers use for copied files.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommandClipboard {
+    name: &'static str,
+    copy_command: Vec<&'static str>,
+    paste_command: Vec<&'static str>,
+}
+
+impl CommandClipboard {
+    pub fn wayland() -> Self {
+        Self {
+            name: "wl-clipboard",
+            copy_command: vec!["wl-copy", "--type", "text/uri-list"],
+            paste_command: vec!["wl-paste", "--no-newline", "--type", "text/uri-list"],
+        }
+    }
+
+    pub fn xclip() -> Self {
+        Self {
+            name: "xclip",
+            copy_command: vec![
+                "xclip",
+                "-selection",
+                "clipboard",
+                "-t",
+                "text/uri-list",
+                "-i",
+            ],
+            paste_command: vec![
+                "xclip",
+                "-selection",
+                "clipboard",
+                "-t",
+                "text/uri-list",
+                "-o",
+            ],
+        }
+    }
+}
+
+impl Clipboard for CommandClipboard {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn write_paths(&mut self, paths: &[PathBuf]) -> io::Result<()> {
+        // The tools keep running in the background to serve the selection, hence their output is
+        // not captured
+        let mut child = ProcessCommand::new(self.copy_command[0])
+            .args(&self.copy_command[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(paths_to_uri_list(paths).as_bytes())?;
+        }
+        let status = child.wait()?;
+        match status.success() {
+            true => Ok(()),
+            false => Err(io::Error::other(format!(
+                "{} exited with {}",
+                self.copy_command[0], status
+            ))),
+        }
+    }
+
+    fn read_paths(&mut self) -> io::Result<Vec<PathBuf>> {
+        let output = ProcessCommand::new(self.paste_command[0])
+            .args(&self.paste_command[1..])
+            .stdin(Stdio::null())
+            .output()?;
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "{} exited with {}",
+                self.paste_command[0], output.status
+            )));
+        }
+        let paths = uri_list_to_paths(&String::from_utf8_lossy(&output.stdout));
+        match paths.is_empty() {
+            true => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "Clipboard does not contain any files",
+            )),
+            false => Ok(paths),
+        }
+    }
+}
+
+/// Sends the copied paths to the terminal's clipboard through the OSC 52 escape sequence. Most
+/// terminals refuse to report the clipboard back, so pasting uses the paths remembered by the app.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Osc52Clipboard {
+    register: RegisterClipboard,
+}
+
+impl Osc52Clipboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Clipboard for Osc52Clipboard {
+    fn name(&self) -> &'static str {
+        "osc52"
+    }
+
+    fn write_paths(&mut self, paths: &[PathBuf]) -> io::Result<()> {
+        let mut stdout = io::stdout();
+        stdout.write_all(osc52_sequence(&paths_to_uri_list(paths)).as_bytes())?;
+        stdout.flush()?;
+        self.register.write_paths(paths)
+    }
+
+    fn read_paths(&mut self) -> io::Result<Vec<PathBuf>> {
+        self.register.read_paths()
+    }
+}
+
+/// Pick the clipboard best suited for the current platform and session.
+pub fn system_clipboard() -> Box<dyn Clipboard> {
+    #[cfg(windows)]
+    {
+        Box::new(WindowsClipboard)
+    }
+    #[cfg(not(windows))]
+    {
+        if env::var_os("WAYLAND_DISPLAY").is_some()
+            && is_executable_in_path("wl-copy")
+            && is_executable_in_path("wl-paste")
+        {
+            return Box::new(CommandClipboard::wayland());
+        }
+        if env::var_os("DISPLAY").is_some() && is_executable_in_path("xclip") {
+            return Box::new(CommandClipboard::xclip());
+        }
+        if io::stdout().is_terminal() {
+            return Box::new(Osc52Clipboard::new());
+        }
+        Box::new(RegisterClipboard::new())
+    }
+}
+
+/// Check whether an executable with the given name can be found in any of the PATH directories.
+pub fn is_executable_in_path(name: &str) -> bool {
+    match env::var_os("PATH") {
+        Some(paths) => env::split_paths(&paths).any(|dir| dir.join(name).is_file()),
+        None => false,
+    }
+}
+
+fn path_to_uri(path: &Path) -> String {
+    #[cfg(unix)]
+    let bytes = {
+        use std::os::unix::ffi::OsStrExt;
+        path.as_os_str().as_bytes().to_vec()
+    };
+    #[cfg(not(unix))]
+    let bytes = path.to_string_lossy().replace('\\', "/").into_bytes();
+    format!("file://{}", percent_encode(&bytes, URI_ESCAPE))
+}
+
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?;
+    // Skip the host part of the uri, e.g. file://localhost/home
+    let encoded = &encoded[encoded.find('/')?..];
+    let bytes = percent_decode_str(encoded).collect::<Vec<u8>>();
+    #[cfg(unix)]
+    {
+        use std::ffi::OsString;
+        use std::os::unix::ffi::OsStringExt;
+        Some(PathBuf::from(OsString::from_vec(bytes)))
+    }
+    #[cfg(not(unix))]
+    {
+        Some(PathBuf::from(String::from_utf8_lossy(&bytes).to_string()))
+    }
+}
+
+/// Convert the paths to the `text/uri-list` format (one uri per line, CRLF separated).
+pub fn paths_to_uri_list(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|path| path_to_uri(path) + "\r\n")
+        .collect::<String>()
+}
+
+/// Read the paths from a `text/uri-list`, ignoring comments and non-file uris.
+pub fn uri_list_to_paths(uri_list: &str) -> Vec<PathBuf> {
+    uri_list
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(uri_to_path)
+        .collect()
+}
+
+fn osc52_sequence(contents: &str) -> String {
+    format!("\x1b]52;c;{}\x07", STANDARD.encode(contents))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_clipboard() {
+        let mut clipboard = RegisterClipboard::new();
+        assert!(clipboard.read_paths().is_err());
+        let paths = vec![PathBuf::from("/tmp/a.txt"), PathBuf::from("/tmp/b")];
+        clipboard.write_paths(&paths).unwrap();
+        assert_eq!(clipboard.read_paths().unwrap(), paths);
+
+        let mut boxed: Box<dyn Clipboard> = Box::new(clipboard);
+        let mut cloned = boxed.clone();
+        assert_eq!(cloned.read_paths().unwrap(), paths);
+        boxed.write_paths(&[PathBuf::from("/tmp/c")]).unwrap();
+        assert_eq!(cloned.read_paths().unwrap(), paths);
+    }
+
+    #[test]
+    fn test_uri_list_round_trip() {
+        let paths = vec![
+            PathBuf::from("/home/user/my file.txt"),
+            PathBuf::from("/home/user/100%/#notes"),
+            PathBuf::from("/home/user/zażółć"),
+        ];
+        let uri_list = paths_to_uri_list(&paths);
+        assert_eq!(
+            uri_list.lines().next(),
+            Some("file:///home/user/my%20file.txt")
+        );
+        assert_eq!(uri_list_to_paths(&uri_list), paths);
+    }
+
+    #[test]
+    fn test_uri_list_to_paths() {
+        let uri_list = "# copied files\r\nfile://localhost/tmp/a\r\nhttps://example.com\r\n\r\nfile:///tmp/b%20c\n";
+        assert_eq!(
+            uri_list_to_paths(uri_list),
+            vec![PathBuf::from("/tmp/a"), PathBuf::from("/tmp/b c")]
+        );
+    }
+
+    #[test]
+    fn test_osc52_sequence() {
+        assert_eq!(
+            osc52_sequence("file:///a\r\n"),
+            "\x1b]52;c;ZmlsZTovLy9hDQo=\x07"
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_copy_and_read_windows_clipboard() {
+        let original_dir = tempdir::TempDir::new("original_directory").unwrap();
+        let file_list = vec![
+            original_dir.path().join("file1.txt"),
+            original_dir.path().join("file2.txt"),
+        ];
+        for file in &file_list {
+            std::fs::write(file, b"Hello, world!").unwrap();
+        }
+        let mut clipboard = WindowsClipboard;
+        clipboard.write_paths(&file_list).unwrap();
+        assert_eq!(clipboard.read_paths().unwrap(), file_list);
+    }
+}
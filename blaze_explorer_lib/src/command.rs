@@ -2,15 +2,20 @@ pub mod command_helpers;
 pub mod command_utils;
 pub mod explorer_commands;
 pub mod file_commands;
-pub mod key_press;
+pub mod git_commands;
+pub mod macro_commands;
 pub mod navigation_commands;
-use key_press::decode_expression;
+pub mod operator_commands;
 
-use crate::action::ExplorerAction;
+use crate::action::{AppAction, CursorMovement, ExplorerAction};
 use crate::app::ExitResult;
+use crate::completion::get_completions;
 use crate::components::explorer_manager::SplitDirection;
 use crate::components::explorer_table::GlobalStyling;
+use crate::ex_commands::set_option;
+use crate::key_notation::parse_keys;
 use crate::plugin::plugin_popup::PluginPopUp;
+use crate::search_pattern::{FilterPattern, SearchPattern};
 use crate::{action::Action, line_entry::LineEntry};
 use std::any::Any;
 use std::fmt::Debug;
@@ -45,6 +50,15 @@ pub trait Command: CommandClone + Any + CommandEq {
     fn is_reversible(&self) -> bool {
         false
     }
+    /// Whether the command may change the files on disk, in which case the listings are read again
+    fn modifies_files(&self) -> bool {
+        self.is_reversible()
+    }
+    /// Fresh command doing the same at the current selection/directory, used to repeat the
+    /// command with `.`. Commands which cannot be repeated return None.
+    fn retarget(&self, _ctx: App) -> Option<Box<dyn Command>> {
+        None
+    }
 }
 
 pub trait CommandClone: Debug {
@@ -107,8 +121,9 @@ impl UpdateSearchQuery {
 
 impl Command for UpdateSearchQuery {
     fn execute(&mut self, app: &mut App) -> Option<Action> {
+        let pattern = SearchPattern::new(&self.query, &app.search_settings);
         app.explorer_manager
-            .set_highlighting_rule(GlobalStyling::HighlightSearch(self.query.clone()));
+            .set_highlighting_rule(GlobalStyling::HighlightSearch(pattern));
         None
     }
 }
@@ -144,6 +159,75 @@ impl Command for NextSearchResult {
     }
 }
 
+#[derive(Clone, PartialEq, Debug)]
+pub struct PreviousSearchResult {}
+
+impl PreviousSearchResult {
+    pub fn new(_app: App) -> Self {
+        Self {}
+    }
+}
+impl Command for PreviousSearchResult {
+    fn execute(&mut self, app: &mut App) -> Option<Action> {
+        app.explorer_manager.previous_search_result();
+        None
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct UpdateFilter {
+    query: String,
+}
+
+impl UpdateFilter {
+    pub fn new(_app: App, query: String) -> Self {
+        Self { query }
+    }
+}
+
+impl Command for UpdateFilter {
+    fn execute(&mut self, app: &mut App) -> Option<Action> {
+        let filter = FilterPattern::new(&self.query, &app.search_settings);
+        app.explorer_manager.set_filter(Some(filter));
+        None
+    }
+}
+
+/// Show all the rows again, leaving filter mode if it was cancelled
+#[derive(Clone, PartialEq, Debug)]
+pub struct ClearFilter {}
+
+impl ClearFilter {
+    pub fn new(_app: App) -> Self {
+        Self {}
+    }
+}
+
+impl Command for ClearFilter {
+    fn execute(&mut self, app: &mut App) -> Option<Action> {
+        app.explorer_manager.set_filter(None);
+        match app.mode {
+            Mode::Filter => Some(Action::AppAct(AppAction::SwitchMode(Mode::Normal))),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ConfirmFilter {}
+
+impl ConfirmFilter {
+    pub fn new(_app: App) -> Self {
+        Self {}
+    }
+}
+impl Command for ConfirmFilter {
+    fn execute(&mut self, app: &mut App) -> Option<Action> {
+        app.confirm_filter();
+        None
+    }
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub struct ShowInFolder {
     current_file_path: PathBuf,
@@ -168,7 +252,7 @@ impl Command for ShowInFolder {
         let filename = self.target_path.file_name().unwrap();
         app.move_directory(
             folder.to_path_buf(),
-            Some(filename.to_str().unwrap().to_string()),
+            Some(filename.to_string_lossy().to_string()),
         );
         None
     }
@@ -205,6 +289,7 @@ impl Command for SwitchMode {
             Mode::Normal => app.enter_normal_mode(),
             Mode::Command => app.enter_command_mode(),
             Mode::Search => app.enter_search_mode(),
+            Mode::Filter => app.enter_filter_mode(),
             Mode::PopUp => app.enter_popup_mode(),
             Mode::Visual => app.enter_visual_mode(),
         }
@@ -239,6 +324,8 @@ impl ConfirmCommand {
 }
 impl Command for ConfirmCommand {
     fn execute(&mut self, app: &mut App) -> Option<Action> {
+        app.command_line_history
+            .add(&Mode::Command, self.command.clone());
         if !self.command.is_empty() && self.command.chars().nth(0).unwrap() == '!' {
             Some(Action::AppAct(crate::action::AppAction::TerminalCommand(
                 self.command[1..].to_string(),
@@ -293,9 +380,13 @@ impl ParseKeyStrokes {
 }
 impl Command for ParseKeyStrokes {
     fn execute(&mut self, app: &mut App) -> Option<Action> {
-        let key_chain = decode_expression(self.command.clone());
-        app.execute_keys(key_chain);
-        None
+        match parse_keys(&self.command) {
+            Ok(key_chain) => {
+                app.execute_keys(key_chain);
+                None
+            }
+            Err(e) => Some(Action::AppAct(AppAction::DisplayMessage(e))),
+        }
     }
 }
 
@@ -377,24 +468,17 @@ impl Command for UpdatePlugin {
 #[derive(Clone, PartialEq, Debug)]
 pub struct InsertKey {
     ch: char,
-    search: bool,
 }
 
 impl InsertKey {
-    pub fn new(app: App, ch: char) -> Self {
-        let search = matches!(app.mode, Mode::Search);
-        Self { ch, search }
+    pub fn new(_app: App, ch: char) -> Self {
+        Self { ch }
     }
 }
 impl Command for InsertKey {
     fn execute(&mut self, app: &mut App) -> Option<Action> {
         app.command_line.append_char(self.ch);
-        match &self.search {
-            true => Some(Action::ExplorerAct(ExplorerAction::UpdateSearchQuery(
-                app.command_line.get_contents(),
-            ))),
-            false => None,
-        }
+        update_query(app)
     }
 }
 
@@ -408,10 +492,8 @@ impl EraseText {
 }
 impl Command for EraseText {
     fn execute(&mut self, app: &mut App) -> Option<Action> {
-        app.command_line.clear_contents();
-        Some(Action::ExplorerAct(ExplorerAction::UpdateSearchQuery(
-            app.command_line.get_contents(),
-        )))
+        app.command_line.drop_to_start();
+        update_query(app)
     }
 }
 
@@ -425,7 +507,110 @@ impl DropKey {
 }
 impl Command for DropKey {
     fn execute(&mut self, app: &mut App) -> Option<Action> {
-        app.command_line.remove_char()
+        match app.command_line.remove_char() {
+            Some(Action::ExplorerAct(ExplorerAction::UpdateSearchQuery(_))) => update_query(app),
+            action => action,
+        }
+    }
+}
+
+/// Search query or filter update following an edit of the command line, made in search and
+/// filter mode only
+fn update_query(app: &App) -> Option<Action> {
+    match app.mode {
+        Mode::Search => Some(Action::ExplorerAct(ExplorerAction::UpdateSearchQuery(
+            app.command_line.get_contents(),
+        ))),
+        Mode::Filter => Some(Action::ExplorerAct(ExplorerAction::UpdateFilter(
+            app.command_line.get_contents(),
+        ))),
+        _ => None,
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct DropWord {}
+
+impl DropWord {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+impl Command for DropWord {
+    fn execute(&mut self, app: &mut App) -> Option<Action> {
+        app.command_line.drop_word();
+        update_query(app)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct MoveCursor {
+    movement: CursorMovement,
+}
+
+impl MoveCursor {
+    pub fn new(movement: CursorMovement) -> Self {
+        Self { movement }
+    }
+}
+impl Command for MoveCursor {
+    fn execute(&mut self, app: &mut App) -> Option<Action> {
+        match self.movement {
+            CursorMovement::Left => app.command_line.move_cursor_left(),
+            CursorMovement::Right => app.command_line.move_cursor_right(),
+            CursorMovement::Start => app.command_line.move_cursor_to_start(),
+            CursorMovement::End => app.command_line.move_cursor_to_end(),
+        }
+        None
+    }
+}
+
+/// Complete the text before the cursor, or show the next/previous candidate of the ongoing
+/// completion
+#[derive(Clone, PartialEq, Debug)]
+pub struct Complete {
+    forward: bool,
+}
+
+impl Complete {
+    pub fn new(forward: bool) -> Self {
+        Self { forward }
+    }
+}
+impl Command for Complete {
+    fn execute(&mut self, app: &mut App) -> Option<Action> {
+        if app.command_line.get_completion().is_none() {
+            let line = app.command_line.get_contents_before_cursor();
+            let completions = get_completions(app, &line);
+            app.command_line
+                .start_completion(completions.start, completions.candidates);
+        }
+        app.command_line.cycle_completion(self.forward);
+        None
+    }
+}
+
+/// Replace the command line with an older or newer line of the history of the current mode
+#[derive(Clone, PartialEq, Debug)]
+pub struct BrowseHistory {
+    older: bool,
+}
+
+impl BrowseHistory {
+    pub fn new(older: bool) -> Self {
+        Self { older }
+    }
+}
+impl Command for BrowseHistory {
+    fn execute(&mut self, app: &mut App) -> Option<Action> {
+        let contents = app.command_line.get_contents();
+        let entry = match self.older {
+            true => app.command_line_history.previous(&app.mode, &contents),
+            false => app.command_line_history.next(&app.mode, &contents),
+        };
+        let entry = entry?;
+        app.command_line.set_contents(entry);
+        update_query(app)
     }
 }
 
@@ -538,6 +723,10 @@ impl Command for TerminalCommand {
             output_message,
         )))
     }
+
+    fn modifies_files(&self) -> bool {
+        true
+    }
 }
 #[derive(Clone, PartialEq, Debug)]
 pub struct OpenNeovimHere {
@@ -559,6 +748,51 @@ impl Command for OpenNeovimHere {
     }
 }
 
+#[derive(Clone, PartialEq, Debug)]
+pub struct OpenFile {
+    path: PathBuf,
+}
+
+impl OpenFile {
+    pub fn new(_app: App, path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl Command for OpenFile {
+    fn execute(&mut self, _app: &mut App) -> Option<Action> {
+        match open::that(&self.path) {
+            Ok(()) => None,
+            Err(e) => Some(Action::AppAct(AppAction::DisplayMessage(format!(
+                "Failed to open {}: {}",
+                self.path.display(),
+                e
+            )))),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct SetOption {
+    name: String,
+    value: String,
+}
+
+impl SetOption {
+    pub fn new(_app: App, name: String, value: String) -> Self {
+        Self { name, value }
+    }
+}
+
+impl Command for SetOption {
+    fn execute(&mut self, app: &mut App) -> Option<Action> {
+        match set_option(app, &self.name, &self.value) {
+            Ok(()) => None,
+            Err(e) => Some(Action::AppAct(AppAction::DisplayMessage(e))),
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub struct SplitVertically {}
 
@@ -721,8 +955,10 @@ mod tests {
 
     use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
-    use crate::action::ExplorerAction;
+    use crate::action::{ExplorerAction, TextAction};
     use crate::command::navigation_commands::JumpToId;
+    use crate::command_line_history::CommandLineHistory;
+    use crate::testing_utils::create_custom_testing_folder;
 
     use super::*;
 
@@ -760,6 +996,28 @@ mod tests {
         );
         app.move_directory(starting_path, None);
     }
+
+    #[test]
+    fn test_confirm_invalid_search_query() {
+        let mut app = App::new().unwrap();
+        app.enter_search_mode();
+        app.command_line.set_contents("re:(".to_string());
+        let mut command = ConfirmSearchQuery::new();
+        assert_eq!(
+            command.execute(&mut app),
+            Some(Action::AppAct(AppAction::DisplayMessage(
+                "Invalid pattern: re:(".to_string()
+            )))
+        );
+        assert_eq!(app.mode, Mode::Normal);
+
+        app.enter_search_mode();
+        app.command_line.set_contents("re:a+".to_string());
+        assert_eq!(
+            command.execute(&mut app),
+            Some(Action::ExplorerAct(ExplorerAction::NextSearchResult))
+        );
+    }
     #[test]
     fn test_clear_search_query() {
         let mut app = App::new().unwrap();
@@ -785,6 +1043,88 @@ mod tests {
         app.move_directory(starting_path, None);
     }
 
+    #[test]
+    fn test_filter_mode() {
+        let mut app = App::new().unwrap();
+        let testing_folder =
+            create_custom_testing_folder(vec!["notes.md", "main.rs", "readme.md"]).unwrap();
+        let starting_path = env::current_dir().unwrap();
+        app.move_directory(testing_folder.root_dir.path().to_path_buf(), None);
+        app.action_list
+            .push_back(Action::AppAct(AppAction::SwitchMode(Mode::Filter)));
+        for ch in ".md".chars() {
+            app.action_list
+                .push_back(Action::TextAct(TextAction::InsertKey(ch)));
+        }
+        let _ = app.handle_new_actions();
+        assert_eq!(app.explorer_manager.find_elements("").len(), 2);
+
+        app.action_list
+            .push_back(Action::AppAct(AppAction::ConfirmFilter));
+        let _ = app.handle_new_actions();
+        assert_eq!(app.mode, Mode::Normal);
+        assert_eq!(app.explorer_manager.get_filter(), Some(".md".to_string()));
+
+        // The filter is edited again, then cancelled
+        app.action_list
+            .push_back(Action::AppAct(AppAction::SwitchMode(Mode::Filter)));
+        let _ = app.handle_new_actions();
+        assert_eq!(app.command_line.get_contents(), ".md");
+        app.action_list
+            .push_back(Action::ExplorerAct(ExplorerAction::ClearFilter));
+        let _ = app.handle_new_actions();
+        assert_eq!(app.mode, Mode::Normal);
+        assert_eq!(app.explorer_manager.get_filter(), None);
+        assert_eq!(app.explorer_manager.find_elements("").len(), 3);
+        app.move_directory(starting_path, None);
+    }
+
+    #[test]
+    fn test_cycle_search_results() {
+        let mut app = App::new().unwrap();
+        let testing_folder =
+            create_custom_testing_folder(vec!["Alpha.md", "beta.txt", "alpine.rs", "gamma"])
+                .unwrap();
+        let starting_path = env::current_dir().unwrap();
+        app.move_directory(testing_folder.root_dir.path().to_path_buf(), None);
+        app.action_list
+            .push_back(Action::ExplorerAct(ExplorerAction::UpdateSearchQuery(
+                "al".into(),
+            )));
+        app.action_list
+            .push_back(Action::ExplorerAct(ExplorerAction::NextSearchResult));
+        let _ = app.handle_new_actions();
+        // Lowercase queries ignore case
+        assert_eq!(app.explorer_manager.get_marked_ids().unwrap().len(), 2);
+        let first = app.explorer_manager.get_selected_string().unwrap();
+        assert!(first.to_lowercase().starts_with("al"));
+
+        app.action_list
+            .push_back(Action::ExplorerAct(ExplorerAction::NextSearchResult));
+        let _ = app.handle_new_actions();
+        let second = app.explorer_manager.get_selected_string().unwrap();
+        assert!(second.to_lowercase().starts_with("al"));
+        assert_ne!(first, second);
+
+        app.action_list
+            .push_back(Action::ExplorerAct(ExplorerAction::PreviousSearchResult));
+        let _ = app.handle_new_actions();
+        assert_eq!(app.explorer_manager.get_selected_string(), Some(first));
+
+        app.action_list
+            .push_back(Action::ExplorerAct(ExplorerAction::UpdateSearchQuery(
+                "re:^(beta|gamma)$".into(),
+            )));
+        app.action_list
+            .push_back(Action::ExplorerAct(ExplorerAction::PreviousSearchResult));
+        let _ = app.handle_new_actions();
+        assert_eq!(
+            app.explorer_manager.get_selected_string(),
+            Some("gamma".to_string())
+        );
+        app.move_directory(starting_path, None);
+    }
+
     #[test]
     fn test_parse_shell_command() {
         let command_string = "git".to_string();
@@ -827,6 +1167,16 @@ mod tests {
                 KeyEvent::new(KeyCode::Char('3'), KeyModifiers::NONE)
             ])
         );
+
+        let mut app = App::new().unwrap();
+        let mut invalid_command = ParseKeyStrokes::new(app.clone(), "<Nope>j".into());
+        assert_eq!(
+            invalid_command.execute(&mut app),
+            Some(Action::AppAct(AppAction::DisplayMessage(
+                "Invalid key <Nope>".to_string()
+            )))
+        );
+        assert!(app.key_queue.is_empty());
     }
 
     #[test]
@@ -837,6 +1187,30 @@ mod tests {
         assert_eq!(app.command_line.get_contents(), "git".to_string());
     }
 
+    #[test]
+    fn test_browse_history() {
+        let mut app = App::new().unwrap();
+        app.command_line_history = CommandLineHistory::default();
+        app.action_list
+            .push_back(Action::AppAct(AppAction::ParseCommand(
+                "set timeout=1000".into(),
+            )));
+        let _ = app.handle_new_actions();
+        assert_eq!(app.command_line_history.commands, vec!["set timeout=1000"]);
+
+        app.enter_command_mode();
+        app.command_line.set_contents("se".to_string());
+        BrowseHistory::new(true).execute(&mut app);
+        assert_eq!(app.command_line.get_contents(), "set timeout=1000");
+        BrowseHistory::new(false).execute(&mut app);
+        assert_eq!(app.command_line.get_contents(), "se");
+
+        // Search mode has its own history
+        app.enter_search_mode();
+        assert_eq!(BrowseHistory::new(true).execute(&mut app), None);
+        assert_eq!(app.command_line.get_contents(), "");
+    }
+
     #[test]
     fn test_execute_function() {
         fn dummy_function(app: &mut App) -> Option<Action> {
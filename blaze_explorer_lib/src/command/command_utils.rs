@@ -1,14 +1,8 @@
-use clipboard_win::Setter;
-use clipboard_win::empty;
-use clipboard_win::formats::FileList;
-use clipboard_win::get_clipboard;
 use fs_extra;
 use rand::distr::{Alphanumeric, SampleString};
 use std::path::Path;
 use std::{collections::HashMap, fs, io, path::PathBuf};
 
-use clipboard_win::Clipboard;
-
 use directories::ProjectDirs;
 
 use crate::app::App;
@@ -55,24 +49,23 @@ pub fn move_recursively(
     Ok(join_paths(files_to_move, destination_path))
 }
 
-pub fn copy_to_clipboard(file_paths: Vec<&str>) -> Result<(), clipboard_win::ErrorCode> {
-    let _clip = Clipboard::new_attempts(10).expect("Open clipboard");
-    empty()?;
-    match FileList.write_clipboard(&file_paths) {
-        Ok(_) => Ok(()),
-        Err(e) => Err(e),
+/// Move a single file or directory, falling back to copying when it cannot be renamed, e.g.
+/// across file systems
+pub fn move_path(src: &Path, dest: &Path) -> io::Result<()> {
+    if fs::rename(src, dest).is_ok() {
+        return Ok(());
     }
+    copy_recursively(src, dest)?;
+    remove_path(src)
 }
 
-pub fn read_from_clipboard() -> Result<Vec<PathBuf>, clipboard_win::ErrorCode> {
-    let _clip = Clipboard::new_attempts(10).expect("Open clipboard");
-    let str_files = get_clipboard(FileList)?;
-    let paths = str_files
-        .iter()
-        .map(PathBuf::from)
-        .collect::<Vec<PathBuf>>();
-    Ok(paths)
+pub fn remove_path(path: &Path) -> io::Result<()> {
+    match path.is_dir() {
+        true => fs::remove_dir_all(path),
+        false => fs::remove_file(path),
+    }
 }
+
 pub fn copy_recursively(src: &Path, dest: &Path) -> io::Result<()> {
     if src.is_file() {
         fs::copy(src, dest)?;
@@ -181,33 +174,6 @@ mod tests {
         assert_eq!(resulting_file_list, expected_file_list);
         Ok(())
     }
-    #[test]
-    fn test_copy_and_read_clipboard() -> io::Result<()> {
-        let original_dir = TempDir::new("original_directory").unwrap();
-        let folder_1 = original_dir.path().join("folder_1");
-        let folder_2 = folder_1.join("folder_2");
-        create_dir_all(folder_2.clone())?;
-        let file_list = vec![
-            original_dir.path().join("file1.txt"),
-            original_dir.path().join("file2.txt"),
-            folder_1.join("file2.txt"),
-            folder_2.join("file3.txt"),
-        ];
-        for file in &file_list {
-            let mut f = File::create(file)?;
-            f.write_all(b"Hello, world!")?;
-            f.sync_all()?;
-        }
-        let str_path_list = file_list
-            .iter()
-            .map(|f| f.to_str().unwrap())
-            .collect::<Vec<_>>();
-        copy_to_clipboard(str_path_list).unwrap();
-        let resulting_paths = read_from_clipboard().unwrap();
-        assert_eq!(file_list, resulting_paths);
-        Ok(())
-    }
-
     #[test]
     fn test_copy_recursively_subfolder() -> io::Result<()> {
         let test_folder = create_testing_folder().unwrap();
@@ -1,5 +1,6 @@
 use std::path::PathBuf;
 
+use crate::components::explorer_table::explorer_sorting::SortOrder;
 use crate::{action::Action, app::App};
 
 use super::Command;
@@ -23,13 +24,114 @@ impl Command for ToggleToFavourites {
     }
 }
 
+#[derive(Clone, PartialEq, Debug)]
+pub struct SortBy {
+    sort_order: SortOrder,
+}
+
+impl SortBy {
+    pub fn new(_app: App, sort_order: SortOrder) -> Self {
+        Self { sort_order }
+    }
+}
+
+impl Command for SortBy {
+    fn execute(&mut self, app: &mut App) -> Option<Action> {
+        app.explorer_manager.set_sort_order(self.sort_order);
+        None
+    }
+}
+
+/// Toggle between the ascending and descending order of the current sort key
+#[derive(Clone, PartialEq, Debug)]
+pub struct ReverseSort {}
+
+impl ReverseSort {
+    pub fn new(_app: App) -> Self {
+        Self {}
+    }
+}
+
+impl Command for ReverseSort {
+    fn execute(&mut self, app: &mut App) -> Option<Action> {
+        let sort_order = app.explorer_manager.get_sort_order().reversed();
+        app.explorer_manager.set_sort_order(sort_order);
+        None
+    }
+}
+
+/// Show or hide the dotfiles in the focused split
+#[derive(Clone, PartialEq, Debug)]
+pub struct ToggleHidden {}
+
+impl ToggleHidden {
+    pub fn new(_app: App) -> Self {
+        Self {}
+    }
+}
+
+impl Command for ToggleHidden {
+    fn execute(&mut self, app: &mut App) -> Option<Action> {
+        app.explorer_manager.toggle_hidden();
+        None
+    }
+}
+
+/// Show or hide the entries ignored by git in the focused split
+#[derive(Clone, PartialEq, Debug)]
+pub struct ToggleIgnored {}
+
+impl ToggleIgnored {
+    pub fn new(_app: App) -> Self {
+        Self {}
+    }
+}
+
+impl Command for ToggleIgnored {
+    fn execute(&mut self, app: &mut App) -> Option<Action> {
+        app.explorer_manager.toggle_ignored();
+        None
+    }
+}
+
+/// Read the directories of all splits again
+#[derive(Clone, PartialEq, Debug)]
+pub struct Refresh {}
+
+impl Refresh {
+    pub fn new(_app: App) -> Self {
+        Self {}
+    }
+}
+
+impl Command for Refresh {
+    fn execute(&mut self, app: &mut App) -> Option<Action> {
+        app.explorer_manager.invalidate_listings();
+        None
+    }
+}
+
+#[cfg(test)]
 mod tests {
+    use std::{env, fs};
+
+    use git2::Repository;
+
     use crate::command::Command;
     use crate::components::explorer_manager;
     use crate::testing_utils::create_custom_testing_folder;
 
-    use super::ToggleToFavourites;
+    use super::{ReverseSort, SortBy, ToggleHidden, ToggleIgnored, ToggleToFavourites};
     use crate::app::App;
+    use crate::components::explorer_table::explorer_sorting::{SortKey, SortOrder};
+
+    fn get_row_names(app: &mut App) -> Vec<String> {
+        app.explorer_manager
+            .find_elements("")
+            .into_iter()
+            .map(|element| element.filename)
+            .collect()
+    }
 
     #[test]
     fn test_toggle_to_favourites() {
@@ -50,4 +152,96 @@ mod tests {
 
         assert!(!app.config.favourites.contains(&root_dir));
     }
+
+    #[test]
+    fn test_sort_by() {
+        let mut app = App::new().unwrap();
+        let testing_folder =
+            create_custom_testing_folder(vec!["docs/", "file10.txt", "file2.txt", "a.rs"]).unwrap();
+        let starting_path = env::current_dir().unwrap();
+        let root_dir = testing_folder.root_dir.path().to_path_buf();
+        app.move_directory(root_dir.clone(), None);
+        // Directories come first by default
+        assert_eq!(
+            get_row_names(&mut app),
+            vec!["docs", "a.rs", "file2.txt", "file10.txt"]
+        );
+
+        app.explorer_manager
+            .update_path(root_dir.clone(), Some("a.rs".to_string()));
+        let mut command = SortBy::new(app.clone(), SortOrder::new(SortKey::Extension, false));
+        command.execute(&mut app);
+        assert_eq!(
+            get_row_names(&mut app),
+            vec!["docs", "a.rs", "file2.txt", "file10.txt"]
+        );
+        ReverseSort::new(app.clone()).execute(&mut app);
+        assert_eq!(
+            get_row_names(&mut app),
+            vec!["file10.txt", "file2.txt", "a.rs", "docs"]
+        );
+        // The selected file stays selected
+        assert_eq!(
+            app.explorer_manager.get_selected_string(),
+            Some("a.rs".to_string())
+        );
+
+        // The order is only used again in the directory if asked to
+        app.move_directory(root_dir.join("docs"), None);
+        app.move_directory(root_dir.clone(), None);
+        assert_eq!(
+            app.explorer_manager.get_sort_order(),
+            SortOrder::new(SortKey::Extension, true)
+        );
+        app.explorer_manager.remember_sort_orders = true;
+        SortBy::new(app.clone(), SortOrder::new(SortKey::Name, false)).execute(&mut app);
+        app.move_directory(root_dir.join("docs"), None);
+        SortBy::new(app.clone(), SortOrder::new(SortKey::Size, true)).execute(&mut app);
+        app.move_directory(root_dir.clone(), None);
+        assert_eq!(
+            app.explorer_manager.get_sort_order(),
+            SortOrder::new(SortKey::Name, false)
+        );
+        app.move_directory(starting_path, None);
+    }
+
+    #[test]
+    fn test_toggle_hidden_and_ignored() {
+        let mut app = App::new().unwrap();
+        let testing_folder = create_custom_testing_folder(vec![
+            ".env",
+            ".gitignore",
+            "build/",
+            "build/out.o",
+            "a.txt",
+        ])
+        .unwrap();
+        let starting_path = env::current_dir().unwrap();
+        let root_dir = testing_folder.root_dir.path().to_path_buf();
+        fs::write(root_dir.join(".gitignore"), "build/\n").unwrap();
+        Repository::init(&root_dir).unwrap();
+        app.move_directory(root_dir.clone(), Some("a.txt".to_string()));
+        assert_eq!(
+            get_row_names(&mut app),
+            vec![".git", "build", ".env", ".gitignore", "a.txt"]
+        );
+
+        ToggleHidden::new(app.clone()).execute(&mut app);
+        assert_eq!(get_row_names(&mut app), vec!["build", "a.txt"]);
+        // The selected file stays selected
+        assert_eq!(
+            app.explorer_manager.get_selected_string(),
+            Some("a.txt".to_string())
+        );
+        ToggleIgnored::new(app.clone()).execute(&mut app);
+        assert_eq!(get_row_names(&mut app), vec!["a.txt"]);
+        ToggleHidden::new(app.clone()).execute(&mut app);
+        assert_eq!(get_row_names(&mut app), vec![".env", ".gitignore", "a.txt"]);
+
+        // The toggles stay on in other directories
+        app.move_directory(root_dir.join("build"), None);
+        app.move_directory(root_dir.clone(), None);
+        assert_eq!(get_row_names(&mut app), vec![".env", ".gitignore", "a.txt"]);
+        app.move_directory(starting_path, None);
+    }
 }
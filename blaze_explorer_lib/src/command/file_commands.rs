@@ -1,11 +1,12 @@
 use itertools::Itertools;
 
-use super::command_utils::{copy_recursively, copy_to_clipboard, read_from_clipboard};
+use super::command_utils::{copy_recursively, move_path, remove_path};
+use super::operator_commands::get_motion_paths;
 use crate::command::Command;
 
 use super::command_utils::{create_backup_map, get_backup_dir, join_paths};
 
-use crate::action::{Action, AppAction};
+use crate::action::{Action, AppAction, Motion};
 use std::fmt::Debug;
 use std::fs::File;
 use std::io;
@@ -18,6 +19,9 @@ use crate::{app::App, mode::Mode};
 pub struct DeleteSelection {
     pub affected_files: Option<Vec<PathBuf>>,
     backup_path: Option<HashMap<PathBuf, PathBuf>>,
+    /// Motion and count the rows were chosen with, used again when repeating the command
+    motion: Option<Motion>,
+    count: Option<usize>,
 }
 
 /// Command used to delete files. Considers all selected items at the time of creating the struct.
@@ -27,9 +31,49 @@ impl DeleteSelection {
         Self {
             affected_files,
             backup_path: None,
+            motion: None,
+            count: None,
+        }
+    }
+
+    /// Delete `count` rows starting at the selected one, e.g. `4dd`. In the visual mode the
+    /// marked items are deleted regardless of the count.
+    pub fn new_with_count(mut ctx: App, count: usize) -> Self {
+        let affected_files = get_counted_paths(&mut ctx, count);
+        Self {
+            affected_files,
+            backup_path: None,
+            motion: None,
+            count: Some(count),
+        }
+    }
+
+    /// Delete the rows covered by the motion, e.g. `d3j`
+    pub fn new_with_motion(
+        affected_files: Vec<PathBuf>,
+        motion: Motion,
+        count: Option<usize>,
+    ) -> Self {
+        Self {
+            affected_files: Some(affected_files),
+            backup_path: None,
+            motion: Some(motion),
+            count,
         }
     }
 }
+
+/// Paths of `count` rows starting at the selected one in the normal mode, or of the marked rows in
+/// other modes.
+fn get_counted_paths(ctx: &mut App, count: usize) -> Option<Vec<PathBuf>> {
+    match ctx.mode {
+        Mode::Normal => ctx.explorer_manager.get_selected().map(|selected| {
+            ctx.explorer_manager
+                .get_paths_in_range(selected, selected.saturating_add(count.saturating_sub(1)))
+        }),
+        _ => ctx.explorer_manager.get_affected_paths(),
+    }
+}
 impl Command for DeleteSelection {
     /// Assign a backup path for each individual entry selected
     /// Move each of the entries to their designated backup path
@@ -69,6 +113,21 @@ impl Command for DeleteSelection {
     fn is_reversible(&self) -> bool {
         true
     }
+
+    fn retarget(&self, mut ctx: App) -> Option<Box<dyn Command>> {
+        let command = match (&self.motion, self.count) {
+            (Some(motion), count) => {
+                let affected_files = get_motion_paths(&mut ctx, motion, count);
+                if affected_files.is_empty() {
+                    return None;
+                }
+                DeleteSelection::new_with_motion(affected_files, motion.clone(), count)
+            }
+            (None, Some(count)) => DeleteSelection::new_with_count(ctx, count),
+            (None, None) => DeleteSelection::new(ctx),
+        };
+        Some(Box::new(command))
+    }
 }
 
 impl Debug for DeleteSelection {
@@ -192,27 +251,61 @@ impl CopyToClipboard {
         let affected_files = ctx.explorer_manager.get_affected_paths();
         Self { affected_files }
     }
+
+    /// Copy `count` rows starting at the selected one, e.g. `3yy`
+    pub fn new_with_count(mut ctx: App, count: usize) -> Self {
+        let affected_files = get_counted_paths(&mut ctx, count);
+        Self { affected_files }
+    }
+
+    pub fn new_with_paths(affected_files: Vec<PathBuf>) -> Self {
+        Self {
+            affected_files: Some(affected_files),
+        }
+    }
 }
 
 impl Command for CopyToClipboard {
     fn execute(&mut self, app: &mut App) -> Option<Action> {
-        match &self.affected_files {
-            Some(affected_files) => {
-                match copy_to_clipboard(
-                    affected_files.iter().map(|x| x.to_str().unwrap()).collect(),
-                ) {
-                    Ok(()) => None,
-                    Err(e) => Some(Action::AppAct(AppAction::DisplayMessage(format!(
-                        "Error while copying: {:?}",
-                        e
-                    )))),
-                }
-            }
-            None => None,
-        };
+        if let Some(affected_files) = &self.affected_files
+            && let Err(e) = app.clipboard.write_paths(affected_files)
+        {
+            app.enter_normal_mode();
+            return Some(Action::AppAct(AppAction::DisplayMessage(format!(
+                "Error while copying: {}",
+                e
+            ))));
+        }
+        Some(Action::AppAct(AppAction::SwitchMode(Mode::Normal)))
+    }
+}
+
+/// Put files in the clipboard so that the next paste moves them instead of copying
+#[derive(Clone, PartialEq, Debug)]
+pub struct CutToClipboard {
+    affected_files: Vec<PathBuf>,
+}
+
+impl CutToClipboard {
+    pub fn new(affected_files: Vec<PathBuf>) -> Self {
+        Self { affected_files }
+    }
+}
+
+impl Command for CutToClipboard {
+    fn execute(&mut self, app: &mut App) -> Option<Action> {
+        if let Err(e) = app.clipboard.write_paths(&self.affected_files) {
+            app.enter_normal_mode();
+            return Some(Action::AppAct(AppAction::DisplayMessage(format!(
+                "Error while cutting: {}",
+                e
+            ))));
+        }
+        app.cut_selection = Some(self.affected_files.clone());
         Some(Action::AppAct(AppAction::SwitchMode(Mode::Normal)))
     }
 }
+
 #[derive(Clone, PartialEq, Debug)]
 pub struct PasteFromClipboard {
     current_directory: PathBuf,
@@ -223,6 +316,7 @@ pub struct PasteFromClipboard {
 /// Paste files from clipboard
 /// Undoing this action removes the pasted files from the given directory, while redoing the action
 /// will paste the exact same files back (even if clipboard contents have changed)
+/// Files which were cut are moved rather than copied, and undoing moves them back
 impl PasteFromClipboard {
     pub fn new(mut ctx: App) -> Self {
         let current_directory = ctx.explorer_manager.get_current_path();
@@ -245,7 +339,7 @@ impl Command for PasteFromClipboard {
             Some(map) => (map.to_owned(), true),
             None => {
                 //Read from clipboard and join paths
-                let paths_to_copy = match read_from_clipboard() {
+                let paths_to_copy = match app.clipboard.read_paths() {
                     Ok(paths) => paths,
                     Err(e) => {
                         return Some(Action::AppAct(AppAction::DisplayMessage(format!(
@@ -260,14 +354,21 @@ impl Command for PasteFromClipboard {
                     .into_iter()
                     .zip(join_paths(paths_to_copy.clone(), &self.current_directory))
                     .collect::<HashMap<PathBuf, PathBuf>>();
-                (map, false)
+                let is_cut = app
+                    .cut_selection
+                    .take_if(|cut_paths| *cut_paths == paths_to_copy)
+                    .is_some();
+                (map, is_cut)
             }
         };
         for (source_path, target_path) in copy_map.iter() {
+            if should_delete && source_path == target_path {
+                continue;
+            }
             match copy_recursively(source_path, target_path) {
                 Ok(_) => {
                     if should_delete {
-                        let _ = fs::remove_dir_all(source_path);
+                        let _ = remove_path(source_path);
                     }
                 }
                 Err(e) => {
@@ -280,15 +381,21 @@ impl Command for PasteFromClipboard {
         }
 
         if self.source_files_map.is_none() {
-            let backup_path = create_backup_map(
-                &app.project_dir,
-                copy_map.values().cloned().collect::<Vec<PathBuf>>(),
-            );
-            let inverted_map = backup_path
-                .iter()
-                .map(|(k, v)| (v.to_owned(), k.to_owned()))
-                .collect::<HashMap<PathBuf, PathBuf>>();
-            self.source_files_map = Some(inverted_map);
+            let source_files_map = match should_delete {
+                // Cut files are restored to where they came from
+                true => copy_map.clone(),
+                false => {
+                    let backup_path = create_backup_map(
+                        &app.project_dir,
+                        copy_map.values().cloned().collect::<Vec<PathBuf>>(),
+                    );
+                    backup_path
+                        .iter()
+                        .map(|(k, v)| (v.to_owned(), k.to_owned()))
+                        .collect::<HashMap<PathBuf, PathBuf>>()
+                }
+            };
+            self.source_files_map = Some(source_files_map);
         }
         self.reversible = true;
 
@@ -301,17 +408,16 @@ impl Command for PasteFromClipboard {
     fn undo(&mut self, app: &mut App) -> Option<Action> {
         let mut result = Ok(());
         for (backup_path, target_path) in self.source_files_map.as_ref().unwrap().iter() {
-            match fs::rename(target_path, backup_path) {
-                Ok(_) => (),
-                Err(e) => {
-                    result = Err(e);
-                }
+            // Cut files may go back to another file system
+            if let Err(e) = move_path(target_path, backup_path) {
+                result = Err((target_path, e));
             }
         }
         match result {
             Ok(()) => None,
-            Err(e) => Some(Action::AppAct(AppAction::DisplayMessage(format!(
-                "Error while copying: {:?}",
+            Err((target_path, e)) => Some(Action::AppAct(AppAction::DisplayMessage(format!(
+                "Failed to undo the paste of {}: {}",
+                target_path.display(),
                 e
             )))),
         }
@@ -320,6 +426,75 @@ impl Command for PasteFromClipboard {
     fn is_reversible(&self) -> bool {
         self.reversible
     }
+
+    fn retarget(&self, ctx: App) -> Option<Box<dyn Command>> {
+        Some(Box::new(PasteFromClipboard::new(ctx)))
+    }
+}
+
+/// Move files to another directory, e.g. the one shown in the other split
+#[derive(Clone, PartialEq, Debug)]
+pub struct MoveToDirectory {
+    affected_files: Vec<PathBuf>,
+    target_directory: PathBuf,
+    moved_files: Vec<(PathBuf, PathBuf)>,
+}
+
+impl MoveToDirectory {
+    pub fn new(affected_files: Vec<PathBuf>, target_directory: PathBuf) -> Self {
+        Self {
+            affected_files,
+            target_directory,
+            moved_files: Vec::new(),
+        }
+    }
+}
+
+impl Command for MoveToDirectory {
+    fn execute(&mut self, _app: &mut App) -> Option<Action> {
+        self.moved_files.clear();
+        let target_paths = join_paths(self.affected_files.clone(), &self.target_directory);
+        for (source_path, target_path) in self.affected_files.iter().zip(target_paths) {
+            if *source_path == target_path {
+                continue;
+            }
+            if let Err(e) = move_path(source_path, &target_path) {
+                return Some(Action::AppAct(AppAction::DisplayMessage(format!(
+                    "Failed to move {}: {}",
+                    source_path.display(),
+                    e
+                ))));
+            }
+            self.moved_files.push((source_path.clone(), target_path));
+        }
+        Some(Action::AppAct(AppAction::SwitchMode(Mode::Normal)))
+    }
+
+    fn undo(&mut self, _app: &mut App) -> Option<Action> {
+        for (source_path, target_path) in self.moved_files.iter() {
+            if let Err(e) = move_path(target_path, source_path) {
+                return Some(Action::AppAct(AppAction::DisplayMessage(format!(
+                    "Failed to move {} back: {}",
+                    target_path.display(),
+                    e
+                ))));
+            }
+        }
+        None
+    }
+
+    fn is_reversible(&self) -> bool {
+        !self.moved_files.is_empty()
+    }
+
+    /// Move the current selection to the same directory
+    fn retarget(&self, mut ctx: App) -> Option<Box<dyn Command>> {
+        let affected_files = ctx.explorer_manager.get_affected_paths()?;
+        Some(Box::new(MoveToDirectory::new(
+            affected_files,
+            self.target_directory.clone(),
+        )))
+    }
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -394,6 +569,19 @@ impl Command for AddDir {
     fn is_reversible(&self) -> bool {
         self.reversible
     }
+
+    /// Create an item with the same name in the current directory
+    fn retarget(&self, mut ctx: App) -> Option<Box<dyn Command>> {
+        let new_dir = ctx
+            .explorer_manager
+            .get_current_path()
+            .join(self.new_dir.file_name()?);
+        Some(Box::new(AddDir {
+            new_dir,
+            is_folder: self.is_folder,
+            reversible: false,
+        }))
+    }
 }
 #[cfg(test)]
 mod tests {
@@ -401,7 +589,7 @@ mod tests {
     use std::env;
 
     use crate::{
-        action::ExplorerAction,
+        action::{CommandAction, ExplorerAction, Operator},
         testing_utils::{create_custom_testing_folder, create_testing_folder},
     };
     #[test]
@@ -524,6 +712,33 @@ mod tests {
         );
         app.move_directory(current_path, None);
     }
+    #[test]
+    fn test_cut_paste() {
+        let mut app = App::new().unwrap();
+        let current_path = env::current_dir().unwrap();
+        let testing_folder = create_custom_testing_folder(vec!["a.txt", "folder/"]).unwrap();
+        let root_dir = testing_folder.root_dir.path().to_path_buf();
+        let cut_file = root_dir.join("a.txt");
+        let target_dir = root_dir.join("folder");
+        let mut cut_selection = CutToClipboard::new(vec![cut_file.clone()]);
+        cut_selection.execute(&mut app);
+        assert_eq!(app.cut_selection, Some(vec![cut_file.clone()]));
+
+        app.update_path(target_dir.clone(), None);
+        let mut paste_selection = PasteFromClipboard::new(app.clone());
+        paste_selection.execute(&mut app);
+        assert!(!cut_file.exists());
+        assert!(target_dir.join("a.txt").exists());
+        assert_eq!(app.cut_selection, None);
+
+        // Undoing moves the file back
+        let result = paste_selection.undo(&mut app);
+        assert!(result.is_none(), "{:?}", result);
+        assert!(cut_file.exists());
+        assert!(!target_dir.join("a.txt").exists());
+        app.move_directory(current_path, None);
+    }
+
     #[test]
     fn test_write_delete_read_clipboard() {
         // Ensure a display action is issued when trying to paste a deleted file.
@@ -585,6 +800,147 @@ mod tests {
         app.move_directory(starting_path, None);
     }
 
+    #[test]
+    fn test_delete_with_count() {
+        let mut app = App::new().unwrap();
+        let testing_folder =
+            create_custom_testing_folder(vec!["a.txt", "b.txt", "c.txt", "d.txt"]).unwrap();
+        let starting_path = env::current_dir().unwrap();
+        let root_dir = testing_folder.root_dir.path().to_path_buf();
+        app.move_directory(root_dir.clone(), None);
+        let selected = app.explorer_manager.get_selected().unwrap();
+        let expected = app
+            .explorer_manager
+            .get_paths_in_range(selected, selected + 2);
+        assert_eq!(expected.len(), 3);
+
+        app.action_list.push_back(Action::CountAct(
+            3,
+            Box::new(Action::AppAct(AppAction::Delete)),
+        ));
+        let _ = app.handle_new_actions();
+        for path in expected.iter() {
+            assert!(!path.exists());
+        }
+        assert_eq!(fs::read_dir(&root_dir).unwrap().count(), 1);
+
+        // The rows are restored with a single undo
+        app.action_list
+            .push_back(Action::CommandAct(CommandAction::Undo));
+        let _ = app.handle_new_actions();
+        for path in expected.iter() {
+            assert!(path.exists());
+        }
+
+        // Counts past the last row delete up to the end
+        app.explorer_manager
+            .update_path(root_dir.clone(), Some("c.txt".to_string()));
+        app.action_list.push_back(Action::CountAct(
+            usize::MAX,
+            Box::new(Action::AppAct(AppAction::Delete)),
+        ));
+        let _ = app.handle_new_actions();
+        assert!(root_dir.join("a.txt").exists());
+        assert!(root_dir.join("b.txt").exists());
+        assert_eq!(fs::read_dir(&root_dir).unwrap().count(), 2);
+        app.move_directory(starting_path, None);
+    }
+
+    #[test]
+    fn test_repeat_counted_delete() {
+        let mut app = App::new().unwrap();
+        let testing_folder =
+            create_custom_testing_folder(vec!["a.txt", "b.txt", "c.txt", "d.txt", "e.txt"])
+                .unwrap();
+        let starting_path = env::current_dir().unwrap();
+        let root_dir = testing_folder.root_dir.path().to_path_buf();
+        app.move_directory(root_dir.clone(), Some("a.txt".to_string()));
+
+        // `2dd` followed by `.` deletes two rows each time
+        app.action_list.push_back(Action::CountAct(
+            2,
+            Box::new(Action::AppAct(AppAction::ApplyOperator(
+                Operator::Delete,
+                Motion::Line,
+            ))),
+        ));
+        let _ = app.handle_new_actions();
+        assert_eq!(fs::read_dir(&root_dir).unwrap().count(), 3);
+        app.explorer_manager
+            .update_path(root_dir.clone(), Some("c.txt".to_string()));
+        app.action_list
+            .push_back(Action::AppAct(AppAction::RepeatLastCommand));
+        let _ = app.handle_new_actions();
+        assert!(!root_dir.join("c.txt").exists());
+        assert!(!root_dir.join("d.txt").exists());
+        assert!(root_dir.join("e.txt").exists());
+
+        // The same goes for `3dd`
+        app.explorer_manager
+            .update_path(root_dir.clone(), Some("e.txt".to_string()));
+        app.action_list.push_back(Action::CountAct(
+            3,
+            Box::new(Action::AppAct(AppAction::Delete)),
+        ));
+        let _ = app.handle_new_actions();
+        assert!(!root_dir.join("e.txt").exists());
+        match app.last_command.clone().unwrap().retarget(app.clone()) {
+            Some(command) => assert_eq!(
+                command
+                    .as_any()
+                    .downcast_ref::<DeleteSelection>()
+                    .unwrap()
+                    .count,
+                Some(3)
+            ),
+            None => panic!("Expected the delete to be repeatable"),
+        }
+        app.move_directory(starting_path, None);
+    }
+
+    #[test]
+    fn test_repeat_last_command() {
+        let testing_folder =
+            create_custom_testing_folder(vec!["first/a.txt", "second/b.txt"]).unwrap();
+        let starting_path = env::current_dir().unwrap();
+        let first_dir = testing_folder.root_dir.path().join("first");
+        let second_dir = testing_folder.root_dir.path().join("second");
+        let mut app = App::new().unwrap();
+        app.move_directory(first_dir.clone(), None);
+        app.run_command(Box::new(AddDir::new(
+            first_dir.clone(),
+            "new_dir/".to_string(),
+        )));
+        assert!(first_dir.join("new_dir").is_dir());
+
+        // The same folder is created in the second directory
+        app.move_directory(second_dir.clone(), None);
+        app.action_list
+            .push_back(Action::AppAct(AppAction::RepeatLastCommand));
+        let _ = app.handle_new_actions();
+        assert!(second_dir.join("new_dir").is_dir());
+
+        // Deleting is repeated for the selection at the time of repeating
+        app.move_directory(second_dir.clone(), Some("b.txt".to_string()));
+        app.action_list.push_back(Action::AppAct(AppAction::Delete));
+        let _ = app.handle_new_actions();
+        assert!(!second_dir.join("b.txt").exists());
+        app.move_directory(first_dir.clone(), Some("a.txt".to_string()));
+        app.action_list
+            .push_back(Action::AppAct(AppAction::RepeatLastCommand));
+        let _ = app.handle_new_actions();
+        assert!(!first_dir.join("a.txt").exists());
+        assert!(first_dir.join("new_dir").exists());
+
+        // The repeated command is undone on its own
+        app.action_list
+            .push_back(Action::CommandAct(CommandAction::Undo));
+        let _ = app.handle_new_actions();
+        assert!(first_dir.join("a.txt").exists());
+        assert!(first_dir.join("new_dir").exists());
+        app.move_directory(starting_path, None);
+    }
+
     #[test]
     fn test_add_dir() {
         let temp_dir = create_custom_testing_folder(Vec::new()).unwrap();
@@ -0,0 +1,569 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use git2::{
+    Index, IndexAddOption, IndexEntry, IndexTime, Oid, Repository, Status, StatusOptions,
+    build::CheckoutBuilder,
+};
+
+use super::command_utils::get_backup_dir;
+use crate::action::{Action, AppAction};
+use crate::app::App;
+use crate::command::Command;
+use crate::git_helpers::{get_relative_path, get_repo};
+use crate::mode::Mode;
+
+fn display_message(message: String) -> Option<Action> {
+    Some(Action::AppAct(AppAction::DisplayMessage(message)))
+}
+
+/// Repository containing the current directory, with the paths relative to its worktree
+fn open_repository(app: &mut App, paths: &[PathBuf]) -> Result<(Repository, Vec<String>), String> {
+    let repo = get_repo(app.explorer_manager.get_current_path())
+        .ok_or("Not inside a git repository".to_string())?;
+    let relative_paths = paths
+        .iter()
+        .map(|path| {
+            get_relative_path(&repo, path)
+                .filter(|relative_path| !relative_path.is_empty())
+                .ok_or(format!("{} is not inside the repository", path.display()))
+        })
+        .collect::<Result<Vec<String>, String>>()?;
+    Ok((repo, relative_paths))
+}
+
+/// Whether the path is one of the relative paths or inside one of them
+fn is_affected(path: &str, relative_paths: &[String]) -> bool {
+    relative_paths.iter().any(|relative_path| {
+        path == relative_path
+            || path
+                .strip_prefix(relative_path.as_str())
+                .is_some_and(|rest| rest.starts_with('/'))
+    })
+}
+
+/// Pattern matching only the path itself, for the options which always match paths as patterns
+fn escape_pathspec(path: &str) -> String {
+    let mut escaped = String::with_capacity(path.len());
+    for c in path.chars() {
+        if matches!(c, '*' | '?' | '[' | '\\' | '!') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Patterns matching only the paths and the files inside them
+fn escape_pathspecs(relative_paths: &[String]) -> Vec<String> {
+    let mut pathspecs = Vec::new();
+    for relative_path in relative_paths {
+        let escaped = escape_pathspec(relative_path);
+        // Only unescaped names match the files inside a directory by themselves
+        if escaped != *relative_path {
+            pathspecs.push(format!("{}/*", escaped));
+        }
+        pathspecs.push(escaped);
+    }
+    pathspecs
+}
+
+/// Index entry of a file, saved to be put back on undo
+#[derive(Clone, Debug, PartialEq)]
+struct SavedEntry {
+    path: Vec<u8>,
+    id: Oid,
+    mode: u32,
+    flags: u16,
+}
+
+impl SavedEntry {
+    fn new(entry: &IndexEntry) -> Self {
+        Self {
+            path: entry.path.clone(),
+            id: entry.id,
+            mode: entry.mode,
+            flags: entry.flags,
+        }
+    }
+
+    /// Entry without the cached file stats, which git fills in again when it looks at the file
+    fn to_index_entry(&self) -> IndexEntry {
+        IndexEntry {
+            ctime: IndexTime::new(0, 0),
+            mtime: IndexTime::new(0, 0),
+            dev: 0,
+            ino: 0,
+            mode: self.mode,
+            uid: 0,
+            gid: 0,
+            file_size: 0,
+            id: self.id,
+            flags: self.flags,
+            flags_extended: 0,
+            path: self.path.clone(),
+        }
+    }
+}
+
+/// Entries of the index under the affected paths, taken before the index is changed
+#[derive(Clone, Debug, PartialEq)]
+struct IndexSnapshot {
+    repo_path: PathBuf,
+    relative_paths: Vec<String>,
+    entries: Vec<SavedEntry>,
+}
+
+impl IndexSnapshot {
+    fn take(repo: &Repository, relative_paths: Vec<String>) -> Result<Self, git2::Error> {
+        let entries = repo
+            .index()?
+            .iter()
+            .filter(|entry| is_affected(&String::from_utf8_lossy(&entry.path), &relative_paths))
+            .map(|entry| SavedEntry::new(&entry))
+            .collect();
+        Ok(Self {
+            repo_path: repo.path().to_path_buf(),
+            relative_paths,
+            entries,
+        })
+    }
+
+    /// Put the affected entries of the index back as they were
+    fn restore(&self) -> Result<(), git2::Error> {
+        let repo = Repository::open(&self.repo_path)?;
+        let mut index = repo.index()?;
+        remove_entries(&mut index, &self.relative_paths)?;
+        for entry in &self.entries {
+            index.add(&entry.to_index_entry())?;
+        }
+        index.write()
+    }
+}
+
+fn remove_entries(index: &mut Index, relative_paths: &[String]) -> Result<(), git2::Error> {
+    let mut paths = index
+        .iter()
+        .map(|entry| String::from_utf8_lossy(&entry.path).to_string())
+        .filter(|path| is_affected(path, relative_paths))
+        .collect::<Vec<String>>();
+    // Conflicted files have several entries, all removed at once
+    paths.dedup();
+    for path in paths {
+        index.remove_path(Path::new(&path))?;
+    }
+    Ok(())
+}
+
+fn stage_paths(repo: &Repository, relative_paths: &[String]) -> Result<(), git2::Error> {
+    let mut index = repo.index()?;
+    let pathspecs = escape_pathspecs(relative_paths);
+    index.add_all(&pathspecs, IndexAddOption::DEFAULT, None)?;
+    // Also stage the removal of the deleted files
+    index.update_all(&pathspecs, None)?;
+    index.write()
+}
+
+fn unstage_paths(repo: &Repository, relative_paths: &[String]) -> Result<(), git2::Error> {
+    match repo.head().and_then(|head| head.peel_to_commit()) {
+        Ok(commit) => {
+            repo.reset_default(Some(commit.as_object()), escape_pathspecs(relative_paths))
+        }
+        // Nothing was committed yet, so the files are not tracked at all once unstaged
+        Err(_) => {
+            let mut index = repo.index()?;
+            remove_entries(&mut index, relative_paths)?;
+            index.write()
+        }
+    }
+}
+
+/// Paths of the files under the relative paths with changes not added to the index
+fn get_changed_files(
+    repo: &Repository,
+    relative_paths: &[String],
+) -> Result<Vec<String>, git2::Error> {
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(false)
+        .disable_pathspec_match(true);
+    for relative_path in relative_paths {
+        options.pathspec(relative_path);
+    }
+    let statuses = repo.statuses(Some(&mut options))?;
+    Ok(statuses
+        .iter()
+        .filter(|entry| {
+            entry
+                .status()
+                .intersects(Status::WT_MODIFIED | Status::WT_DELETED | Status::WT_TYPECHANGE)
+        })
+        .filter_map(|entry| entry.path().map(|path| path.to_string()))
+        .filter(|path| is_affected(path, relative_paths))
+        .collect())
+}
+
+/// Add the selected or marked entries to the index
+#[derive(Clone, PartialEq, Debug)]
+pub struct StageSelection {
+    pub affected_files: Option<Vec<PathBuf>>,
+    snapshot: Option<IndexSnapshot>,
+}
+
+impl StageSelection {
+    pub fn new(mut ctx: App) -> Self {
+        Self {
+            affected_files: ctx.explorer_manager.get_affected_paths(),
+            snapshot: None,
+        }
+    }
+}
+
+impl Command for StageSelection {
+    fn execute(&mut self, app: &mut App) -> Option<Action> {
+        let paths = self.affected_files.clone()?;
+        let (repo, relative_paths) = match open_repository(app, &paths) {
+            Ok(opened) => opened,
+            Err(message) => return display_message(message),
+        };
+        self.snapshot = None;
+        let result = IndexSnapshot::take(&repo, relative_paths).and_then(|snapshot| {
+            stage_paths(&repo, &snapshot.relative_paths)?;
+            self.snapshot = Some(snapshot);
+            Ok(())
+        });
+        match result {
+            Ok(()) => Some(Action::AppAct(AppAction::SwitchMode(Mode::Normal))),
+            Err(e) => display_message(format!("Cannot stage: {}", e.message())),
+        }
+    }
+
+    fn undo(&mut self, _app: &mut App) -> Option<Action> {
+        match self.snapshot.as_ref()?.restore() {
+            Ok(()) => None,
+            Err(e) => display_message(format!("Cannot unstage: {}", e.message())),
+        }
+    }
+
+    fn is_reversible(&self) -> bool {
+        self.snapshot.is_some()
+    }
+
+    fn retarget(&self, ctx: App) -> Option<Box<dyn Command>> {
+        Some(Box::new(StageSelection::new(ctx)))
+    }
+}
+
+/// Reset the index entries of the selected or marked entries to the last commit
+#[derive(Clone, PartialEq, Debug)]
+pub struct UnstageSelection {
+    pub affected_files: Option<Vec<PathBuf>>,
+    snapshot: Option<IndexSnapshot>,
+}
+
+impl UnstageSelection {
+    pub fn new(mut ctx: App) -> Self {
+        Self {
+            affected_files: ctx.explorer_manager.get_affected_paths(),
+            snapshot: None,
+        }
+    }
+}
+
+impl Command for UnstageSelection {
+    fn execute(&mut self, app: &mut App) -> Option<Action> {
+        let paths = self.affected_files.clone()?;
+        let (repo, relative_paths) = match open_repository(app, &paths) {
+            Ok(opened) => opened,
+            Err(message) => return display_message(message),
+        };
+        self.snapshot = None;
+        let result = IndexSnapshot::take(&repo, relative_paths).and_then(|snapshot| {
+            unstage_paths(&repo, &snapshot.relative_paths)?;
+            self.snapshot = Some(snapshot);
+            Ok(())
+        });
+        match result {
+            Ok(()) => Some(Action::AppAct(AppAction::SwitchMode(Mode::Normal))),
+            Err(e) => display_message(format!("Cannot unstage: {}", e.message())),
+        }
+    }
+
+    fn undo(&mut self, _app: &mut App) -> Option<Action> {
+        match self.snapshot.as_ref()?.restore() {
+            Ok(()) => None,
+            Err(e) => display_message(format!("Cannot stage: {}", e.message())),
+        }
+    }
+
+    fn is_reversible(&self) -> bool {
+        self.snapshot.is_some()
+    }
+
+    fn retarget(&self, ctx: App) -> Option<Box<dyn Command>> {
+        Some(Box::new(UnstageSelection::new(ctx)))
+    }
+}
+
+/// Replace the changes not added to the index of the selected or marked entries with the content
+/// of the index. The changed files are backed up first.
+#[derive(Clone, PartialEq, Debug)]
+pub struct DiscardChanges {
+    pub affected_files: Option<Vec<PathBuf>>,
+    /// Changed files with their backup, None for the files which were deleted
+    backups: Option<Vec<(PathBuf, Option<PathBuf>)>>,
+}
+
+impl DiscardChanges {
+    pub fn new(mut ctx: App) -> Self {
+        Self {
+            affected_files: ctx.explorer_manager.get_affected_paths(),
+            backups: None,
+        }
+    }
+}
+
+impl Command for DiscardChanges {
+    fn execute(&mut self, app: &mut App) -> Option<Action> {
+        let paths = self.affected_files.clone()?;
+        let (repo, relative_paths) = match open_repository(app, &paths) {
+            Ok(opened) => opened,
+            Err(message) => return display_message(message),
+        };
+        self.backups = None;
+        let changed_files = match get_changed_files(&repo, &relative_paths) {
+            Ok(changed_files) => changed_files,
+            Err(e) => return display_message(format!("Cannot discard: {}", e.message())),
+        };
+        if changed_files.is_empty() {
+            return display_message("No changes to discard".to_string());
+        }
+        let workdir = repo.workdir()?.to_path_buf();
+        let _ = fs::create_dir_all(app.project_dir.cache_dir());
+        let mut backups = Vec::new();
+        for changed_file in &changed_files {
+            let path = workdir.join(changed_file);
+            let backup_path = match path.symlink_metadata().is_ok() {
+                true => {
+                    let backup_path = get_backup_dir(&app.project_dir, false);
+                    if let Err(e) = fs::copy(&path, &backup_path) {
+                        return display_message(format!("Cannot back up {}: {}", changed_file, e));
+                    }
+                    Some(backup_path)
+                }
+                false => None,
+            };
+            backups.push((path, backup_path));
+        }
+        let mut checkout = CheckoutBuilder::new();
+        checkout.force();
+        for changed_file in &changed_files {
+            checkout.path(escape_pathspec(changed_file));
+        }
+        if let Err(e) = repo.checkout_index(None, Some(&mut checkout)) {
+            return display_message(format!("Cannot discard: {}", e.message()));
+        }
+        self.backups = Some(backups);
+        Some(Action::AppAct(AppAction::SwitchMode(Mode::Normal)))
+    }
+
+    fn undo(&mut self, _app: &mut App) -> Option<Action> {
+        for (path, backup_path) in self.backups.as_ref()? {
+            let result = match backup_path {
+                Some(backup_path) => fs::copy(backup_path, path).map(|_| ()),
+                None => fs::remove_file(path),
+            };
+            if let Err(e) = result {
+                return display_message(format!("Cannot restore {}: {}", path.display(), e));
+            }
+        }
+        None
+    }
+
+    fn is_reversible(&self) -> bool {
+        self.backups.is_some()
+    }
+
+    fn retarget(&self, ctx: App) -> Option<Box<dyn Command>> {
+        Some(Box::new(DiscardChanges::new(ctx)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::env;
+
+    use git2::Signature;
+
+    use super::*;
+    use crate::action::CommandAction;
+    use crate::testing_utils::create_custom_testing_folder;
+
+    /// Repository with the paths committed
+    fn init_repo(root_dir: &Path, paths: &[&str]) -> Repository {
+        let repo = Repository::init(root_dir).unwrap();
+        {
+            let mut index = repo.index().unwrap();
+            index
+                .add_all(paths.iter(), IndexAddOption::DEFAULT, None)
+                .unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let signature = Signature::now("test", "test@example.com").unwrap();
+            repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[])
+                .unwrap();
+        }
+        repo
+    }
+
+    fn get_status(repo: &Repository, path: &str) -> Status {
+        repo.status_file(Path::new(path)).unwrap()
+    }
+
+    fn run_action(app: &mut App, action: Action) {
+        app.action_list.push_back(action);
+        let _ = app.handle_new_actions();
+    }
+
+    #[test]
+    fn test_stage_and_unstage() {
+        let testing_folder =
+            create_custom_testing_folder(vec!["a.txt", "lib/b.rs", "new.txt"]).unwrap();
+        let starting_path = env::current_dir().unwrap();
+        let root_dir = testing_folder.root_dir.path().to_path_buf();
+        let repo = init_repo(&root_dir, &["a.txt", "lib"]);
+        fs::write(root_dir.join("a.txt"), "changed").unwrap();
+        fs::write(root_dir.join("lib/b.rs"), "changed").unwrap();
+        let mut app = App::new().unwrap();
+        app.move_directory(root_dir.clone(), Some("a.txt".to_string()));
+
+        run_action(&mut app, Action::AppAct(AppAction::Stage));
+        assert_eq!(get_status(&repo, "a.txt"), Status::INDEX_MODIFIED);
+        assert_eq!(get_status(&repo, "lib/b.rs"), Status::WT_MODIFIED);
+        // The colours are computed again once the index is reported to change
+        let changed_paths = HashSet::from([repo.path().join("index")]);
+        app.explorer_manager.refresh_paths(&changed_paths);
+        assert_eq!(
+            app.explorer_manager.get_git_status("a.txt"),
+            Some(Status::INDEX_MODIFIED)
+        );
+
+        // Directories are staged as a whole
+        app.update_path(root_dir.clone(), Some("lib".to_string()));
+        run_action(&mut app, Action::AppAct(AppAction::Stage));
+        assert_eq!(get_status(&repo, "lib/b.rs"), Status::INDEX_MODIFIED);
+        run_action(&mut app, Action::CommandAct(CommandAction::Undo));
+        assert_eq!(get_status(&repo, "lib/b.rs"), Status::WT_MODIFIED);
+        assert_eq!(get_status(&repo, "a.txt"), Status::INDEX_MODIFIED);
+
+        app.update_path(root_dir.clone(), Some("a.txt".to_string()));
+        run_action(&mut app, Action::AppAct(AppAction::Unstage));
+        assert_eq!(get_status(&repo, "a.txt"), Status::WT_MODIFIED);
+        run_action(&mut app, Action::CommandAct(CommandAction::Undo));
+        assert_eq!(get_status(&repo, "a.txt"), Status::INDEX_MODIFIED);
+
+        // New files are unstaged by removing them from the index
+        app.update_path(root_dir.clone(), Some("new.txt".to_string()));
+        run_action(&mut app, Action::AppAct(AppAction::Stage));
+        assert_eq!(get_status(&repo, "new.txt"), Status::INDEX_NEW);
+        run_action(&mut app, Action::AppAct(AppAction::Unstage));
+        assert_eq!(get_status(&repo, "new.txt"), Status::WT_NEW);
+        app.move_directory(starting_path, None);
+    }
+
+    #[test]
+    fn test_discard_changes() {
+        let testing_folder = create_custom_testing_folder(vec!["a.txt", "lib/b.rs"]).unwrap();
+        let starting_path = env::current_dir().unwrap();
+        let root_dir = testing_folder.root_dir.path().to_path_buf();
+        let repo = init_repo(&root_dir, &["a.txt", "lib"]);
+        fs::write(root_dir.join("a.txt"), "changed").unwrap();
+        fs::write(root_dir.join("lib/b.rs"), "changed").unwrap();
+        fs::write(root_dir.join("lib/new.rs"), "").unwrap();
+        let mut app = App::new().unwrap();
+        app.move_directory(root_dir.clone(), Some("lib".to_string()));
+
+        // Untracked files are kept
+        run_action(&mut app, Action::AppAct(AppAction::DiscardChanges));
+        assert_eq!(
+            fs::read_to_string(root_dir.join("lib/b.rs")).unwrap(),
+            "Hello, world!"
+        );
+        assert!(root_dir.join("lib/new.rs").exists());
+        assert_eq!(get_status(&repo, "a.txt"), Status::WT_MODIFIED);
+
+        run_action(&mut app, Action::CommandAct(CommandAction::Undo));
+        assert_eq!(
+            fs::read_to_string(root_dir.join("lib/b.rs")).unwrap(),
+            "changed"
+        );
+        assert_eq!(get_status(&repo, "lib/b.rs"), Status::WT_MODIFIED);
+
+        // Nothing to discard
+        let mut command = DiscardChanges::new(app.clone());
+        command.affected_files = Some(vec![root_dir.join("lib/new.rs")]);
+        assert_eq!(
+            command.execute(&mut app),
+            Some(Action::AppAct(AppAction::DisplayMessage(
+                "No changes to discard".to_string()
+            )))
+        );
+        assert!(!command.is_reversible());
+        app.move_directory(starting_path, None);
+    }
+
+    #[test]
+    fn test_discard_changes_with_pattern_characters() {
+        let testing_folder = create_custom_testing_folder(vec!["a*.txt", "ab.txt"]).unwrap();
+        let starting_path = env::current_dir().unwrap();
+        let root_dir = testing_folder.root_dir.path().to_path_buf();
+        let repo = init_repo(&root_dir, &["a\\*.txt", "ab.txt"]);
+        fs::write(root_dir.join("a*.txt"), "changed").unwrap();
+        fs::write(root_dir.join("ab.txt"), "changed").unwrap();
+        let mut app = App::new().unwrap();
+        app.move_directory(root_dir.clone(), Some("a*.txt".to_string()));
+
+        // Only the selected file is discarded, not every file matching its name as a pattern
+        run_action(&mut app, Action::AppAct(AppAction::DiscardChanges));
+        assert_eq!(get_status(&repo, "a*.txt"), Status::CURRENT);
+        assert_eq!(
+            fs::read_to_string(root_dir.join("ab.txt")).unwrap(),
+            "changed"
+        );
+        assert_eq!(get_status(&repo, "ab.txt"), Status::WT_MODIFIED);
+        app.move_directory(starting_path, None);
+    }
+
+    #[test]
+    fn test_unstage_with_pattern_characters() {
+        let testing_folder =
+            create_custom_testing_folder(vec!["a*.txt", "ab.txt", "d*/c.txt", "dx/c.txt"]).unwrap();
+        let starting_path = env::current_dir().unwrap();
+        let root_dir = testing_folder.root_dir.path().to_path_buf();
+        let repo = init_repo(&root_dir, &["a\\*.txt", "ab.txt", "d\\*/*", "dx"]);
+        for file in ["a*.txt", "ab.txt", "d*/c.txt", "dx/c.txt"] {
+            fs::write(root_dir.join(file), "changed").unwrap();
+        }
+        let mut app = App::new().unwrap();
+
+        // Only the selected entries are staged and unstaged, not every path matching their names
+        // as patterns
+        for (selected, other) in [("a*.txt", "ab.txt"), ("d*", "dx/c.txt")] {
+            app.move_directory(root_dir.clone(), Some(selected.to_string()));
+            run_action(&mut app, Action::AppAct(AppAction::Stage));
+            assert_eq!(get_status(&repo, other), Status::WT_MODIFIED);
+            app.update_path(
+                root_dir.clone(),
+                Some(other.split('/').next().unwrap().to_string()),
+            );
+            run_action(&mut app, Action::AppAct(AppAction::Stage));
+            app.update_path(root_dir.clone(), Some(selected.to_string()));
+            run_action(&mut app, Action::AppAct(AppAction::Unstage));
+            assert_eq!(get_status(&repo, other), Status::INDEX_MODIFIED);
+        }
+        assert_eq!(get_status(&repo, "a*.txt"), Status::WT_MODIFIED);
+        assert_eq!(get_status(&repo, "d*/c.txt"), Status::WT_MODIFIED);
+        app.move_directory(starting_path, None);
+    }
+}
@@ -0,0 +1,170 @@
+use crate::action::{Action, AppAction, RegisterAction};
+use crate::app::App;
+
+use super::Command;
+
+/// Stop the ongoing recording, or wait for the register to record a macro to
+#[derive(Clone, PartialEq, Debug)]
+pub struct RecordMacro {}
+
+impl RecordMacro {
+    pub fn new(_ctx: App) -> Self {
+        Self {}
+    }
+}
+
+impl Command for RecordMacro {
+    fn execute(&mut self, app: &mut App) -> Option<Action> {
+        if app.macro_registers.stop_recording().is_none() {
+            app.input_machine
+                .await_register(RegisterAction::RecordMacro);
+            return None;
+        }
+        match app.save_macros() {
+            Ok(()) => None,
+            Err(e) => Some(Action::AppAct(AppAction::DisplayMessage(format!(
+                "Failed to save the macros: {}",
+                e
+            )))),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct StartRecording {
+    register: char,
+}
+
+impl StartRecording {
+    pub fn new(_ctx: App, register: char) -> Self {
+        Self { register }
+    }
+}
+
+impl Command for StartRecording {
+    fn execute(&mut self, app: &mut App) -> Option<Action> {
+        app.macro_registers.start_recording(self.register);
+        None
+    }
+}
+
+/// Wait for the register to replay a macro from
+#[derive(Clone, PartialEq, Debug)]
+pub struct ReplayMacro {}
+
+impl ReplayMacro {
+    pub fn new(_ctx: App) -> Self {
+        Self {}
+    }
+}
+
+impl Command for ReplayMacro {
+    fn execute(&mut self, app: &mut App) -> Option<Action> {
+        app.input_machine
+            .await_register(RegisterAction::ReplayMacro);
+        None
+    }
+}
+
+/// Queue the keys of the macro in the register, or of the macro replayed last if no register is
+/// given
+#[derive(Clone, PartialEq, Debug)]
+pub struct ReplayRegister {
+    register: Option<char>,
+}
+
+impl ReplayRegister {
+    pub fn new(_ctx: App, register: Option<char>) -> Self {
+        Self { register }
+    }
+}
+
+impl Command for ReplayRegister {
+    fn execute(&mut self, app: &mut App) -> Option<Action> {
+        let keys = match self.register {
+            Some(register) => app.macro_registers.replay(register),
+            None => app.macro_registers.replay_last(),
+        };
+        match (keys, self.register) {
+            (Some(keys), _) => {
+                app.execute_keys(keys);
+                None
+            }
+            (None, Some(register)) => Some(Action::AppAct(AppAction::DisplayMessage(format!(
+                "No macro recorded in register {}",
+                register
+            )))),
+            (None, None) => Some(Action::AppAct(AppAction::DisplayMessage(
+                "No macro was replayed yet".to_string(),
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use tempdir::TempDir;
+
+    use crate::{
+        input_machine::input_machine_helpers::convert_str_to_events,
+        macro_registers::MacroRegisters, testing_utils::create_custom_testing_folder,
+    };
+
+    use super::*;
+
+    fn press_keys(app: &mut App, keys: &str) {
+        for key in convert_str_to_events(keys) {
+            app.process_key_event(key);
+            let _ = app.handle_new_actions();
+        }
+    }
+
+    /// Process the keys queued by the app, as the app does before reading any typed keys
+    fn process_queued_keys(app: &mut App) {
+        while let Some(key) = app.key_queue.pop_front() {
+            app.process_queued_key_event(key);
+            let _ = app.handle_new_actions();
+        }
+    }
+
+    #[test]
+    fn test_record_and_replay_macro() {
+        let mut app = App::new_test().unwrap();
+        let testing_folder = create_custom_testing_folder(vec!["a.txt", "b.txt", "c.txt"]).unwrap();
+        let starting_path = env::current_dir().unwrap();
+        app.move_directory(testing_folder.root_dir.path().to_path_buf(), None);
+        press_keys(&mut app, "qajjq");
+        assert_eq!(app.macro_registers.get_recording_register(), None);
+        assert_eq!(
+            app.macro_registers.registers.get(&'a'),
+            Some(&convert_str_to_events("jj"))
+        );
+        let data_dir = TempDir::new("macros").unwrap();
+        let macros_path = data_dir.path().join("macros.json");
+        app.save_macros_to(macros_path.clone()).unwrap();
+        let registers = app.macro_registers.registers.clone();
+        app.macro_registers = MacroRegisters::default();
+        app.load_macros_from(macros_path);
+        assert_eq!(app.macro_registers.registers, registers);
+
+        press_keys(&mut app, "@a");
+        assert_eq!(app.key_queue, convert_str_to_events("jj"));
+        process_queued_keys(&mut app);
+        press_keys(&mut app, "@@");
+        assert_eq!(app.key_queue, convert_str_to_events("jj"));
+        process_queued_keys(&mut app);
+
+        // Replaying a macro while recording records the replaying keys only, and the keys typed
+        // afterwards
+        press_keys(&mut app, "qb@a");
+        process_queued_keys(&mut app);
+        press_keys(&mut app, "kq");
+        assert_eq!(
+            app.macro_registers.registers.get(&'b'),
+            Some(&convert_str_to_events("@ak"))
+        );
+        app.move_directory(starting_path, None);
+    }
+}
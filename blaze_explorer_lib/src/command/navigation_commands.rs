@@ -39,48 +39,73 @@ impl Command for ParentDirectory {
 }
 
 #[derive(Clone, PartialEq, Debug)]
-pub struct SelectUp {}
+pub struct SelectUp {
+    count: usize,
+}
 
 impl SelectUp {
-    pub fn new(_ctx: App) -> Self {
-        Self {}
+    pub fn new(ctx: App) -> Self {
+        Self::new_with_count(ctx, 1)
+    }
+
+    pub fn new_with_count(_ctx: App, count: usize) -> Self {
+        Self { count }
     }
 }
 impl Command for SelectUp {
     fn execute(&mut self, app: &mut App) -> Option<Action> {
-        app.explorer_manager.previous();
+        app.explorer_manager.previous_by(self.count);
         None
     }
 }
 
 #[derive(Clone, PartialEq, Debug)]
-pub struct SelectDown {}
+pub struct SelectDown {
+    count: usize,
+}
 
 impl SelectDown {
-    pub fn new(_ctx: App) -> Self {
-        Self {}
+    pub fn new(ctx: App) -> Self {
+        Self::new_with_count(ctx, 1)
+    }
+
+    pub fn new_with_count(_ctx: App, count: usize) -> Self {
+        Self { count }
     }
 }
 impl Command for SelectDown {
     fn execute(&mut self, app: &mut App) -> Option<Action> {
-        app.explorer_manager.next();
+        app.explorer_manager.next_by(self.count);
         None
     }
 }
 #[derive(Clone, PartialEq, Debug)]
 pub struct JumpToId {
     id: usize,
+    /// Whether the id is a row of the table rather than the id of an entry
+    is_row: bool,
 }
 
 impl JumpToId {
     pub fn new(mut _ctx: App, id: usize) -> Self {
-        Self { id }
+        Self { id, is_row: false }
+    }
+
+    /// Jump to the row with the given (1-based) line number, or to the last row if there are fewer
+    /// rows, e.g. `10G`
+    pub fn new_with_line_number(ctx: App, line_number: usize) -> Self {
+        let count = ctx.explorer_manager.find_elements("").len();
+        let id = line_number.min(count).saturating_sub(1);
+        Self { id, is_row: true }
     }
 }
 
 impl Command for JumpToId {
     fn execute(&mut self, app: &mut App) -> Option<Action> {
-        app.explorer_manager.jump_to_id(self.id);
+        match self.is_row {
+            true => app.explorer_manager.jump_to_row(self.id),
+            false => app.explorer_manager.jump_to_id(self.id),
+        }
         None
     }
 }
@@ -121,7 +146,7 @@ impl JumpToStart {
 
 impl Command for JumpToStart {
     fn execute(&mut self, app: &mut App) -> Option<Action> {
-        app.explorer_manager.jump_to_id(0);
+        app.explorer_manager.jump_to_row(0);
         None
     }
 }
@@ -141,7 +166,7 @@ impl Command for JumpToEnd {
             0 => 0,
             _ => count - 1,
         };
-        app.explorer_manager.jump_to_id(id);
+        app.explorer_manager.jump_to_row(id);
         None
     }
 }
@@ -152,7 +177,8 @@ mod tests {
         action::{Action, ExplorerAction},
         app::App,
         command::Command,
-        testing_utils::create_testing_folder,
+        search_pattern::{FilterPattern, SearchSettings},
+        testing_utils::{create_custom_testing_folder, create_testing_folder},
     };
 
     use super::JumpToStart;
@@ -216,10 +242,70 @@ mod tests {
     fn test_jump_to_id() {
         let mut app = App::new().unwrap();
         let starting_path = env::current_dir().unwrap();
+        app.move_directory(starting_path.clone(), None);
         app.action_list
             .push_back(Action::ExplorerAct(ExplorerAction::JumpToId(2)));
         let _ = app.handle_new_actions();
         assert_eq!(app.explorer_manager.get_selected(), Some(2));
+
+        // Huge counts wrap around the list without stepping through it
+        app.action_list.push_back(Action::CountAct(
+            usize::MAX,
+            Box::new(Action::ExplorerAct(ExplorerAction::SelectDown)),
+        ));
+        let _ = app.handle_new_actions();
+        let n_rows = app.explorer_manager.find_elements("").len();
+        assert_eq!(
+            app.explorer_manager.get_selected(),
+            Some((2 + usize::MAX % n_rows) % n_rows)
+        );
+        app.action_list.push_back(Action::CountAct(
+            usize::MAX,
+            Box::new(Action::ExplorerAct(ExplorerAction::SelectUp)),
+        ));
+        let _ = app.handle_new_actions();
+        assert_eq!(app.explorer_manager.get_selected(), Some(2));
+        app.move_directory(starting_path, None);
+    }
+
+    #[test]
+    fn test_jump_with_filter() {
+        let mut app = App::new().unwrap();
+        let testing_folder =
+            create_custom_testing_folder(vec!["a.txt", "b.md", "c.txt", "d.md"]).unwrap();
+        let starting_path = env::current_dir().unwrap();
+        app.move_directory(testing_folder.root_dir.path().to_path_buf(), None);
+        app.explorer_manager
+            .set_filter(Some(FilterPattern::new(".md", &SearchSettings::default())));
+
+        // Jump labels refer to the entries, line numbers to the rows
+        let last_id = app.explorer_manager.find_elements("d.md")[0].id;
+        app.action_list
+            .push_back(Action::ExplorerAct(ExplorerAction::JumpToStart));
+        app.action_list
+            .push_back(Action::ExplorerAct(ExplorerAction::JumpToId(last_id)));
+        let _ = app.handle_new_actions();
+        assert_eq!(
+            app.explorer_manager.get_selected_string(),
+            Some("d.md".to_string())
+        );
+        app.action_list.push_back(Action::CountAct(
+            1,
+            Box::new(Action::ExplorerAct(ExplorerAction::JumpToStart)),
+        ));
+        let _ = app.handle_new_actions();
+        assert_eq!(
+            app.explorer_manager.get_selected_string(),
+            Some("b.md".to_string())
+        );
+
+        // Entries hidden by the filter cannot be jumped to, e.g. `a.txt`, the first one by name
+        assert!(app.explorer_manager.find_elements("a.txt").is_empty());
+        app.explorer_manager.jump_to_id(0);
+        assert_eq!(
+            app.explorer_manager.get_selected_string(),
+            Some("b.md".to_string())
+        );
         app.move_directory(starting_path, None);
     }
 
@@ -241,6 +327,45 @@ mod tests {
         app.move_directory(starting_path, None);
     }
 
+    #[test]
+    fn test_counted_navigation() {
+        let testing_folder = create_testing_folder().unwrap();
+        let mut app = App::new().unwrap();
+        let starting_path = env::current_dir().unwrap();
+        let root_path = testing_folder.root_dir.path().to_path_buf();
+        app.move_directory(root_path.clone(), None);
+
+        app.action_list.push_back(Action::CountAct(
+            2,
+            Box::new(Action::ExplorerAct(ExplorerAction::SelectDown)),
+        ));
+        let _ = app.handle_new_actions();
+        assert_eq!(app.explorer_manager.get_selected(), Some(2));
+
+        app.action_list.push_back(Action::CountAct(
+            2,
+            Box::new(Action::ExplorerAct(ExplorerAction::SelectUp)),
+        ));
+        let _ = app.handle_new_actions();
+        assert_eq!(app.explorer_manager.get_selected(), Some(0));
+
+        app.action_list.push_back(Action::CountAct(
+            2,
+            Box::new(Action::ExplorerAct(ExplorerAction::JumpToEnd)),
+        ));
+        let _ = app.handle_new_actions();
+        assert_eq!(app.explorer_manager.get_selected(), Some(1));
+
+        // Line numbers past the last row select the last row
+        app.action_list.push_back(Action::CountAct(
+            10,
+            Box::new(Action::ExplorerAct(ExplorerAction::JumpToStart)),
+        ));
+        let _ = app.handle_new_actions();
+        assert_eq!(app.explorer_manager.get_selected(), Some(2));
+        app.move_directory(starting_path, None);
+    }
+
     #[test]
     fn test_jump_to_start() {
         let testing_folder = create_testing_folder().unwrap();
@@ -0,0 +1,189 @@
+// Resolves operators combined with motions, e.g. `dG` or `y3j`, into the commands acting on the
+// rows covered by the motion
+use std::path::PathBuf;
+
+use crate::action::{Motion, Operator};
+use crate::app::App;
+
+use super::file_commands::{CopyToClipboard, CutToClipboard, DeleteSelection, MoveToDirectory};
+use super::{Command, DisplayMessage, Noop};
+
+/// Paths of the rows between the selected row and the row the motion leads to. The motion is
+/// repeated `count` times, except for `gg` and `G`, for which the count is the line number to go
+/// to instead.
+pub fn get_motion_paths(ctx: &mut App, motion: &Motion, count: Option<usize>) -> Vec<PathBuf> {
+    let Some(selected) = ctx.explorer_manager.get_selected() else {
+        return Vec::new();
+    };
+    let repeats = count.unwrap_or(1);
+    let other_end = match motion {
+        Motion::Line => selected.saturating_add(repeats - 1),
+        Motion::Down => selected.saturating_add(repeats),
+        Motion::Up => selected.saturating_sub(repeats),
+        Motion::FirstRow => count.map_or(0, |line| line.saturating_sub(1)),
+        // The range is limited to the last row
+        Motion::LastRow => count.map_or(usize::MAX, |line| line.saturating_sub(1)),
+        Motion::NextSearchResult | Motion::PreviousSearchResult => {
+            for _ in 0..repeats {
+                match motion {
+                    Motion::NextSearchResult => ctx.explorer_manager.next_search_result(),
+                    _ => ctx.explorer_manager.previous_search_result(),
+                }
+            }
+            match ctx.explorer_manager.get_selected() {
+                Some(result) if result != selected => result,
+                // No search result to go to
+                _ => return Vec::new(),
+            }
+        }
+    };
+    ctx.explorer_manager.get_paths_in_range(selected, other_end)
+}
+
+/// Command applying the operator to the rows covered by the motion. All the rows are handled by a
+/// single command, so that they are restored with a single undo.
+pub fn get_operator_command(
+    mut ctx: App,
+    operator: Operator,
+    motion: Motion,
+    count: Option<usize>,
+) -> Box<dyn Command> {
+    let affected_files = get_motion_paths(&mut ctx, &motion, count);
+    if affected_files.is_empty() {
+        return Box::new(Noop::new());
+    }
+    match operator {
+        Operator::Delete => Box::new(DeleteSelection::new_with_motion(
+            affected_files,
+            motion,
+            count,
+        )),
+        Operator::Yank => Box::new(CopyToClipboard::new_with_paths(affected_files)),
+        Operator::Cut => Box::new(CutToClipboard::new(affected_files)),
+        Operator::Move => match ctx.explorer_manager.get_other_paths().into_iter().next() {
+            Some(target_directory) => {
+                Box::new(MoveToDirectory::new(affected_files, target_directory))
+            }
+            None => Box::new(DisplayMessage::new(
+                "Open another split to move the files to".to_string(),
+            )),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use crate::{
+        action::{Action, AppAction, CommandAction, ExplorerAction},
+        testing_utils::create_custom_testing_folder,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_motion_paths() {
+        let mut app = App::new().unwrap();
+        let testing_folder =
+            create_custom_testing_folder(vec!["a.txt", "b.txt", "c.txt", "d.txt", "e.txt"])
+                .unwrap();
+        let starting_path = env::current_dir().unwrap();
+        app.move_directory(testing_folder.root_dir.path().to_path_buf(), None);
+        let all_paths = app.explorer_manager.get_paths_in_range(0, usize::MAX);
+        app.explorer_manager.jump_to_row(1);
+
+        let mut ctx = app.clone();
+        assert_eq!(
+            get_motion_paths(&mut ctx, &Motion::Line, None),
+            all_paths[1..2]
+        );
+        assert_eq!(
+            get_motion_paths(&mut ctx, &Motion::Down, Some(3)),
+            all_paths[1..5]
+        );
+        assert_eq!(
+            get_motion_paths(&mut ctx, &Motion::Up, Some(3)),
+            all_paths[0..2]
+        );
+        assert_eq!(
+            get_motion_paths(&mut ctx, &Motion::LastRow, None),
+            all_paths[1..]
+        );
+        assert_eq!(
+            get_motion_paths(&mut ctx, &Motion::FirstRow, Some(3)),
+            all_paths[1..3]
+        );
+        // No search was made
+        assert!(get_motion_paths(&mut ctx, &Motion::NextSearchResult, None).is_empty());
+        app.move_directory(starting_path, None);
+    }
+
+    #[test]
+    fn test_delete_operator() {
+        let mut app = App::new().unwrap();
+        let testing_folder =
+            create_custom_testing_folder(vec!["a.txt", "b.txt", "c.txt", "d.txt"]).unwrap();
+        let starting_path = env::current_dir().unwrap();
+        let root_dir = testing_folder.root_dir.path().to_path_buf();
+        app.move_directory(root_dir.clone(), None);
+        app.explorer_manager.jump_to_row(1);
+        let expected = app.explorer_manager.get_paths_in_range(1, usize::MAX);
+
+        app.action_list
+            .push_back(Action::AppAct(AppAction::ApplyOperator(
+                Operator::Delete,
+                Motion::LastRow,
+            )));
+        let _ = app.handle_new_actions();
+        for path in expected.iter() {
+            assert!(!path.exists());
+        }
+        assert_eq!(fs::read_dir(&root_dir).unwrap().count(), 1);
+
+        app.action_list
+            .push_back(Action::CommandAct(CommandAction::Undo));
+        let _ = app.handle_new_actions();
+        for path in expected.iter() {
+            assert!(path.exists());
+        }
+        app.move_directory(starting_path, None);
+    }
+
+    #[test]
+    fn test_move_operator() {
+        let mut app = App::new().unwrap();
+        let testing_folder =
+            create_custom_testing_folder(vec!["a.txt", "b.txt", "target/"]).unwrap();
+        let starting_path = env::current_dir().unwrap();
+        let root_dir = testing_folder.root_dir.path().to_path_buf();
+        let target_dir = root_dir.join("target");
+        app.move_directory(root_dir.clone(), Some("a.txt".to_string()));
+        let command = get_operator_command(app.clone(), Operator::Move, Motion::Line, None);
+        assert!(command.dyn_eq(&DisplayMessage::new(
+            "Open another split to move the files to".to_string()
+        )));
+
+        // The other split shows the target directory
+        app.move_directory(target_dir.clone(), None);
+        app.action_list
+            .push_back(Action::ExplorerAct(ExplorerAction::SplitVertically));
+        let _ = app.handle_new_actions();
+        app.move_directory(root_dir.clone(), Some("a.txt".to_string()));
+        app.action_list
+            .push_back(Action::AppAct(AppAction::ApplyOperator(
+                Operator::Move,
+                Motion::Line,
+            )));
+        let _ = app.handle_new_actions();
+        assert!(!root_dir.join("a.txt").exists());
+        assert!(target_dir.join("a.txt").exists());
+
+        app.action_list
+            .push_back(Action::CommandAct(CommandAction::Undo));
+        let _ = app.handle_new_actions();
+        assert!(root_dir.join("a.txt").exists());
+        assert!(!target_dir.join("a.txt").exists());
+        app.move_directory(starting_path, None);
+    }
+}
@@ -0,0 +1,267 @@
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::mode::Mode;
+
+/// Number of lines kept for each mode
+pub const HISTORY_LIMIT: usize = 100;
+
+/// Entry being browsed with Up/Down
+#[derive(Debug, PartialEq, Clone)]
+struct Browsing {
+    /// Index of the entry shown in the command line
+    index: usize,
+    /// Line typed before browsing, which entries are filtered by and which is restored after the
+    /// newest entry
+    typed: String,
+    /// Line shown in the command line, browsing starts over once it is edited
+    shown: String,
+}
+
+/// Lines confirmed in command and search mode, oldest first. The history is stored in
+/// `history.json` in the data directory, so that it survives restarts.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
+#[serde(default)]
+pub struct CommandLineHistory {
+    pub commands: Vec<String>,
+    pub searches: Vec<String>,
+    #[serde(skip)]
+    browsing: Option<Browsing>,
+}
+
+impl CommandLineHistory {
+    /// Load the history from the given path. A missing file results in an empty history.
+    pub fn try_load_from_file<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let file = File::open(path);
+        match file {
+            Ok(mut file) => {
+                let mut contents = String::new();
+                file.read_to_string(&mut contents)?;
+                let history: CommandLineHistory = serde_json::from_str(&contents)?;
+                Ok(history)
+            }
+            Err(_) => Ok(CommandLineHistory::default()),
+        }
+    }
+
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(path)?;
+        file.write_all(json.as_bytes())?;
+        Ok(())
+    }
+
+    /// Lines of the mode, search and filter mode sharing their own history
+    fn get_entries(&mut self, mode: &Mode) -> &mut Vec<String> {
+        match mode {
+            Mode::Search | Mode::Filter => &mut self.searches,
+            _ => &mut self.commands,
+        }
+    }
+
+    /// Add a confirmed line as the newest entry, dropping its previous occurrence
+    pub fn add(&mut self, mode: &Mode, line: String) {
+        self.stop_browsing();
+        if line.trim().is_empty() {
+            return;
+        }
+        let entries = self.get_entries(mode);
+        entries.retain(|entry| *entry != line);
+        entries.push(line);
+        if entries.len() > HISTORY_LIMIT {
+            entries.drain(..entries.len() - HISTORY_LIMIT);
+        }
+    }
+
+    pub fn stop_browsing(&mut self) {
+        self.browsing = None;
+    }
+
+    /// Entry being browsed, unless the line in the command line was edited since it was shown
+    fn get_browsing(&mut self, contents: &str) -> Option<Browsing> {
+        self.browsing.take_if(|browsing| browsing.shown != contents);
+        self.browsing.clone()
+    }
+
+    /// Entry older than the one shown, starting with the line typed before browsing
+    pub fn previous(&mut self, mode: &Mode, contents: &str) -> Option<String> {
+        let browsing = self.get_browsing(contents).unwrap_or(Browsing {
+            index: self.get_entries(mode).len(),
+            typed: contents.to_string(),
+            shown: contents.to_string(),
+        });
+        let index = self.get_entries(mode)[..browsing.index]
+            .iter()
+            .rposition(|entry| entry.starts_with(&browsing.typed))?;
+        let entry = self.get_entries(mode)[index].clone();
+        self.browsing = Some(Browsing {
+            index,
+            shown: entry.clone(),
+            ..browsing
+        });
+        Some(entry)
+    }
+
+    /// Entry newer than the one shown, or the line typed before browsing after the newest entry
+    pub fn next(&mut self, mode: &Mode, contents: &str) -> Option<String> {
+        let browsing = self.get_browsing(contents)?;
+        let entries = self.get_entries(mode);
+        match entries[browsing.index + 1..]
+            .iter()
+            .position(|entry| entry.starts_with(&browsing.typed))
+        {
+            Some(offset) => {
+                let index = browsing.index + 1 + offset;
+                let entry = entries[index].clone();
+                self.browsing = Some(Browsing {
+                    index,
+                    shown: entry.clone(),
+                    ..browsing
+                });
+                Some(entry)
+            }
+            None => {
+                self.browsing = None;
+                Some(browsing.typed)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing_utils::create_custom_testing_folder;
+
+    use super::*;
+
+    fn add_lines(history: &mut CommandLineHistory, mode: &Mode, lines: &[&str]) {
+        for line in lines {
+            history.add(mode, line.to_string());
+        }
+    }
+
+    #[test]
+    fn test_add_to_history() {
+        let mut history = CommandLineHistory::default();
+        add_lines(&mut history, &Mode::Command, &["cd ..", "q", "", "cd .."]);
+        add_lines(&mut history, &Mode::Search, &["main"]);
+        assert_eq!(history.commands, vec!["q", "cd .."]);
+        assert_eq!(history.searches, vec!["main"]);
+
+        for index in 0..HISTORY_LIMIT + 5 {
+            history.add(&Mode::Command, index.to_string());
+        }
+        assert_eq!(history.commands.len(), HISTORY_LIMIT);
+        assert_eq!(history.commands[0], "5");
+    }
+
+    #[test]
+    fn test_browse_history() {
+        let mut history = CommandLineHistory::default();
+        add_lines(
+            &mut history,
+            &Mode::Command,
+            &["cd ..", "mkdir a", "cd docs"],
+        );
+        assert_eq!(history.next(&Mode::Command, ""), None);
+        assert_eq!(
+            history.previous(&Mode::Command, ""),
+            Some("cd docs".to_string())
+        );
+        assert_eq!(
+            history.previous(&Mode::Command, "cd docs"),
+            Some("mkdir a".to_string())
+        );
+        assert_eq!(
+            history.next(&Mode::Command, "mkdir a"),
+            Some("cd docs".to_string())
+        );
+        // Going past the newest entry restores the typed line
+        assert_eq!(
+            history.next(&Mode::Command, "cd docs"),
+            Some("".to_string())
+        );
+        assert_eq!(history.next(&Mode::Command, ""), None);
+
+        // Entries are filtered by the typed line
+        assert_eq!(
+            history.previous(&Mode::Command, "cd"),
+            Some("cd docs".to_string())
+        );
+        assert_eq!(
+            history.previous(&Mode::Command, "cd docs"),
+            Some("cd ..".to_string())
+        );
+        assert_eq!(history.previous(&Mode::Command, "cd .."), None);
+        assert_eq!(
+            history.next(&Mode::Command, "cd .."),
+            Some("cd docs".to_string())
+        );
+        assert_eq!(
+            history.next(&Mode::Command, "cd docs"),
+            Some("cd".to_string())
+        );
+        assert_eq!(history.previous(&Mode::Search, ""), None);
+    }
+
+    #[test]
+    fn test_browse_history_after_edit() {
+        let mut history = CommandLineHistory::default();
+        add_lines(
+            &mut history,
+            &Mode::Command,
+            &["mkdir a", "cd ..", "cd docs"],
+        );
+        assert_eq!(
+            history.previous(&Mode::Command, ""),
+            Some("cd docs".to_string())
+        );
+        // Browsing starts over from the edited line, filtered by it
+        assert_eq!(
+            history.previous(&Mode::Command, "mk"),
+            Some("mkdir a".to_string())
+        );
+        assert_eq!(
+            history.next(&Mode::Command, "mkdir a"),
+            Some("mk".to_string())
+        );
+
+        // Newer entries are not browsed into from an edited line, so the edit is kept
+        assert_eq!(
+            history.previous(&Mode::Command, "cd"),
+            Some("cd docs".to_string())
+        );
+        assert_eq!(
+            history.previous(&Mode::Command, "cd docs"),
+            Some("cd ..".to_string())
+        );
+        assert_eq!(history.next(&Mode::Command, "cd ../src"), None);
+        assert_eq!(history.previous(&Mode::Command, "cd ../src"), None);
+        assert_eq!(
+            history.previous(&Mode::Command, "cd "),
+            Some("cd docs".to_string())
+        );
+    }
+
+    #[test]
+    fn test_save_load_history() {
+        let test_dir = create_custom_testing_folder(vec![]).unwrap();
+        let history_path = test_dir.root_dir.path().join("history.json");
+        let missing = CommandLineHistory::try_load_from_file(&history_path).unwrap();
+        assert_eq!(missing, CommandLineHistory::default());
+
+        let mut history = CommandLineHistory::default();
+        add_lines(&mut history, &Mode::Command, &["vsplit ..", "!git status"]);
+        add_lines(&mut history, &Mode::Search, &["toml"]);
+        history.save_to_file(&history_path).unwrap();
+        let loaded = CommandLineHistory::try_load_from_file(&history_path).unwrap();
+        assert_eq!(loaded, history);
+    }
+}
@@ -0,0 +1,263 @@
+// Completion of the command line with Tab: ex command names and their arguments, e.g. paths or
+// plugin functionalities, and executables from `PATH` for terminal commands
+use std::collections::BTreeSet;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use crate::app::App;
+use crate::ex_commands::{
+    ExCompletion, find_ex_command, get_ex_commands, get_options, resolve_path,
+};
+
+/// Candidates for the end of the line, replacing the text from `start` (in chars) onwards
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completions {
+    pub start: usize,
+    pub candidates: Vec<String>,
+}
+
+/// Completions of the line typed in command mode, up to the cursor
+pub fn get_completions(app: &mut App, line: &str) -> Completions {
+    if let Some(terminal_command) = line.strip_prefix('!') {
+        let last_space = terminal_command
+            .char_indices()
+            .rev()
+            .find(|(_, ch)| ch.is_whitespace());
+        return match last_space {
+            // The program is completed first, its arguments are likely to be paths
+            None => Completions {
+                start: 1,
+                candidates: get_executables(terminal_command),
+            },
+            Some((index, space)) => {
+                // Whitespace may take several bytes, e.g. a full-width space typed by an IME
+                let argument_start = index + space.len_utf8();
+                Completions {
+                    start: terminal_command[..argument_start].chars().count() + 1,
+                    candidates: get_paths(app, &terminal_command[argument_start..], false),
+                }
+            }
+        };
+    }
+
+    let name_end = line
+        .find(|ch: char| !ch.is_ascii_alphabetic())
+        .unwrap_or(line.len());
+    let (name, argument) = line.split_at(name_end);
+    if argument.is_empty() {
+        return Completions {
+            start: 0,
+            candidates: get_command_names(name),
+        };
+    }
+    let Ok(ex_command) = find_ex_command(name) else {
+        return Completions {
+            start: 0,
+            candidates: Vec::new(),
+        };
+    };
+    let typed = argument.trim_start();
+    let start = line.chars().count() - typed.chars().count();
+    let candidates = match ex_command.completion {
+        ExCompletion::None => Vec::new(),
+        ExCompletion::Paths => get_paths(app, typed, false),
+        ExCompletion::Directories => get_paths(app, typed, true),
+        ExCompletion::Commands => get_command_names(typed),
+        ExCompletion::Options => get_options()
+            .into_iter()
+            .map(|option| option.name.to_string())
+            .filter(|name| name.starts_with(typed))
+            .collect(),
+        ExCompletion::PluginFunctionalities => get_plugin_functionalities(app, typed),
+    };
+    Completions { start, candidates }
+}
+
+fn get_command_names(typed: &str) -> Vec<String> {
+    get_ex_commands()
+        .iter()
+        .map(|command| command.full_name())
+        .filter(|name| name.starts_with(typed))
+        .collect()
+}
+
+fn get_plugin_functionalities(app: &App, typed: &str) -> Vec<String> {
+    let mut candidates = app
+        .plugins
+        .iter()
+        .flat_map(|(plugin_name, plugin)| {
+            plugin
+                .get_functionality_map()
+                .into_keys()
+                .map(move |functionality| format!("{} {}", plugin_name, functionality))
+        })
+        .filter(|candidate| candidate.starts_with(typed))
+        .collect::<Vec<String>>();
+    candidates.sort();
+    candidates
+}
+
+/// Entries of the directory the typed path leads to, starting with its last component.
+/// Directories end with a separator, so that completing them again goes inside. Hidden entries
+/// are left out unless their name is being typed.
+fn get_paths(app: &mut App, typed: &str, directories_only: bool) -> Vec<String> {
+    let (directory, name) = match typed.rfind(['/', '\\']) {
+        Some(index) => typed.split_at(index + 1),
+        None => ("", typed),
+    };
+    let Ok(entries) = fs::read_dir(resolve_path(app, directory)) else {
+        return Vec::new();
+    };
+    let mut candidates = entries
+        .flatten()
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if !file_name.starts_with(name)
+                || (file_name.starts_with('.') && !name.starts_with('.'))
+            {
+                return None;
+            }
+            match entry.path().is_dir() {
+                true => Some(format!("{}{}/", directory, file_name)),
+                false if !directories_only => Some(format!("{}{}", directory, file_name)),
+                false => None,
+            }
+        })
+        .collect::<Vec<String>>();
+    candidates.sort();
+    candidates
+}
+
+/// Programs found in the directories of `PATH`, starting with the typed name
+fn get_executables(typed: &str) -> Vec<String> {
+    let Some(paths) = env::var_os("PATH") else {
+        return Vec::new();
+    };
+    let mut executables = BTreeSet::new();
+    for directory in env::split_paths(&paths) {
+        let Ok(entries) = fs::read_dir(directory) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if file_name.starts_with(typed) && is_executable(&entry.path()) {
+                executables.insert(file_name);
+            }
+        }
+    }
+    executables.into_iter().collect()
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path)
+        .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+        && path.extension().is_some_and(|extension| {
+            ["exe", "bat", "cmd", "com"]
+                .iter()
+                .any(|executable| extension.eq_ignore_ascii_case(executable))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use crate::testing_utils::create_custom_testing_folder;
+
+    use super::*;
+
+    #[test]
+    fn test_complete_command_names() {
+        let mut app = App::new().unwrap();
+        assert_eq!(
+            get_completions(&mut app, "s"),
+            Completions {
+                start: 0,
+                candidates: vec!["split".to_string(), "set".to_string(), "sort".to_string()]
+            }
+        );
+        assert_eq!(
+            get_completions(&mut app, "help vs").candidates,
+            vec!["vsplit".to_string()]
+        );
+        assert_eq!(
+            get_completions(&mut app, "set ti"),
+            Completions {
+                start: 4,
+                candidates: vec!["timeout".to_string()]
+            }
+        );
+        assert!(get_completions(&mut app, "nope ").candidates.is_empty());
+    }
+
+    #[test]
+    fn test_complete_paths() {
+        let mut app = App::new().unwrap();
+        let testing_folder =
+            create_custom_testing_folder(vec!["docs/", "docs/notes.md", "data.csv", ".hidden"])
+                .unwrap();
+        let starting_path = env::current_dir().unwrap();
+        app.move_directory(testing_folder.root_dir.path().to_path_buf(), None);
+
+        assert_eq!(
+            get_completions(&mut app, "e d"),
+            Completions {
+                start: 2,
+                candidates: vec!["data.csv".to_string(), "docs/".to_string()]
+            }
+        );
+        assert_eq!(
+            get_completions(&mut app, "cd d").candidates,
+            vec!["docs/".to_string()]
+        );
+        assert_eq!(
+            get_completions(&mut app, "e docs/").candidates,
+            vec!["docs/notes.md".to_string()]
+        );
+        assert_eq!(
+            get_completions(&mut app, "e .").candidates,
+            vec![".hidden".to_string()]
+        );
+        assert_eq!(
+            get_completions(&mut app, "!cat docs/n"),
+            Completions {
+                start: 5,
+                candidates: vec!["docs/notes.md".to_string()]
+            }
+        );
+        for space in ['\u{00A0}', '\u{3000}'] {
+            assert_eq!(
+                get_completions(&mut app, &format!("!cat{}docs/n", space)),
+                Completions {
+                    start: 5,
+                    candidates: vec!["docs/notes.md".to_string()]
+                }
+            );
+        }
+        app.move_directory(starting_path, None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_complete_executables() {
+        let mut app = App::new().unwrap();
+        let completions = get_completions(&mut app, "!s");
+        assert_eq!(completions.start, 1);
+        assert!(completions.candidates.contains(&"sh".to_string()));
+        assert!(
+            completions
+                .candidates
+                .iter()
+                .all(|candidate| candidate.starts_with('s'))
+        );
+    }
+}
@@ -1,8 +1,9 @@
 use color_eyre::eyre::Result;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
-    text::{Line, Text},
-    widgets::{Block, Clear, Paragraph},
+    style::{Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, Clear, List, ListItem, Paragraph},
     Frame,
 };
 use std::cmp::min;
@@ -15,9 +16,29 @@ use crate::{
 
 use super::Component;
 
+/// Number of completion candidates displayed at once
+const COMPLETION_LIST_HEIGHT: usize = 10;
+
+/// Candidates cycled through with Tab/Shift-Tab, replacing the typed text
+#[derive(Clone, Debug, PartialEq)]
+pub struct Completion {
+    /// Contents before and after the completed text
+    before: String,
+    after: String,
+    typed: String,
+    pub candidates: Vec<String>,
+    /// Candidate in the command line, None standing for the typed text
+    pub selected: Option<usize>,
+    /// Contents the completion resulted in, which are edited once they differ
+    shown: String,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct CommandLine {
     contents: String,
+    /// Position of the cursor in the contents, in chars
+    cursor: usize,
+    completion: Option<Completion>,
     focused: bool,
     message_queue: Option<Vec<String>>,
     pub current_message: Option<Vec<String>>,
@@ -26,19 +47,29 @@ pub struct CommandLine {
 
 impl LineEntry for CommandLine {
     fn pop_contents(&mut self) -> String {
+        self.cursor = 0;
         self.contents.drain(..).collect()
     }
 
+    /// Insert the char at the cursor
     fn append_char(&mut self, c: char) {
-        self.contents.push(c);
+        let index = self.get_byte_index(self.cursor);
+        self.contents.insert(index, c);
+        self.cursor += 1;
     }
 
     fn clear_contents(&mut self) {
         self.contents = String::new();
+        self.cursor = 0;
     }
 
+    /// Remove the char before the cursor
     fn drop_char(&mut self) {
-        self.contents.pop();
+        if self.cursor > 0 {
+            let index = self.get_byte_index(self.cursor - 1);
+            self.contents.remove(index);
+            self.cursor -= 1;
+        }
     }
 
     fn remove_char(&mut self) -> Option<Action> {
@@ -56,6 +87,7 @@ impl LineEntry for CommandLine {
     }
 
     fn set_contents(&mut self, contents: String) {
+        self.cursor = contents.chars().count();
         self.contents = contents;
     }
 }
@@ -65,6 +97,8 @@ impl CommandLine {
     pub fn new() -> Self {
         CommandLine {
             contents: String::new(),
+            cursor: 0,
+            completion: None,
             focused: false,
             message_queue: None,
             current_message: None,
@@ -78,7 +112,140 @@ impl CommandLine {
 
     pub fn focus(&mut self) {
         self.focused = true;
-        self.contents = String::new();
+        self.clear_contents();
+        self.completion = None;
+    }
+
+    /// Contents up to the cursor, which are completed
+    pub fn get_contents_before_cursor(&self) -> String {
+        self.contents.chars().take(self.cursor).collect()
+    }
+
+    /// Completion being cycled through, unless the contents were edited since
+    pub fn get_completion(&self) -> Option<&Completion> {
+        self.completion
+            .as_ref()
+            .filter(|completion| completion.shown == self.contents)
+    }
+
+    /// Complete the text between `start` (in chars) and the cursor. A single candidate is
+    /// accepted right away, several ones are then cycled through with `cycle_completion`.
+    pub fn start_completion(&mut self, start: usize, candidates: Vec<String>) {
+        let (start, end) = (self.get_byte_index(start), self.get_byte_index(self.cursor));
+        let mut completion = Completion {
+            before: self.contents[..start].to_string(),
+            after: self.contents[end..].to_string(),
+            typed: self.contents[start..end].to_string(),
+            candidates,
+            selected: None,
+            shown: self.contents.clone(),
+        };
+        match completion.candidates.len() {
+            0 => self.completion = None,
+            1 => {
+                completion.selected = Some(0);
+                self.show_completion(&mut completion);
+                self.completion = None;
+            }
+            _ => self.completion = Some(completion),
+        }
+    }
+
+    /// Show the next or previous candidate, going through the typed text after the last one
+    pub fn cycle_completion(&mut self, forward: bool) {
+        let Some(mut completion) = self.get_completion().cloned() else {
+            return;
+        };
+        let last = completion.candidates.len() - 1;
+        completion.selected = match (completion.selected, forward) {
+            (None, true) => Some(0),
+            (None, false) => Some(last),
+            (Some(index), true) if index < last => Some(index + 1),
+            (Some(index), false) if index > 0 => Some(index - 1),
+            _ => None,
+        };
+        self.show_completion(&mut completion);
+        self.completion = Some(completion);
+    }
+
+    fn show_completion(&mut self, completion: &mut Completion) {
+        let text = match completion.selected {
+            Some(index) => &completion.candidates[index],
+            None => &completion.typed,
+        };
+        self.cursor = completion.before.chars().count() + text.chars().count();
+        self.contents = format!("{}{}{}", completion.before, text, completion.after);
+        completion.shown = self.contents.clone();
+    }
+
+    fn get_byte_index(&self, cursor: usize) -> usize {
+        self.contents
+            .char_indices()
+            .nth(cursor)
+            .map_or(self.contents.len(), |(index, _)| index)
+    }
+
+    pub fn move_cursor_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn move_cursor_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.contents.chars().count());
+    }
+
+    pub fn move_cursor_to_start(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_cursor_to_end(&mut self) {
+        self.cursor = self.contents.chars().count();
+    }
+
+    /// Remove the word before the cursor along with the whitespace following it, as `<C-w>` does
+    /// in Vim
+    pub fn drop_word(&mut self) {
+        let before_cursor = self
+            .contents
+            .chars()
+            .take(self.cursor)
+            .collect::<Vec<char>>();
+        let mut start = before_cursor.len();
+        while start > 0 && before_cursor[start - 1].is_whitespace() {
+            start -= 1;
+        }
+        let is_word_char = |ch: char| ch.is_alphanumeric() || ch == '_';
+        match start
+            .checked_sub(1)
+            .map(|index| is_word_char(before_cursor[index]))
+        {
+            Some(true) => {
+                while start > 0 && is_word_char(before_cursor[start - 1]) {
+                    start -= 1;
+                }
+            }
+            // A run of other chars, e.g. `../`, is a word on its own
+            Some(false) => {
+                while start > 0
+                    && !is_word_char(before_cursor[start - 1])
+                    && !before_cursor[start - 1].is_whitespace()
+                {
+                    start -= 1;
+                }
+            }
+            None => {}
+        }
+        self.drop_before_cursor(start);
+    }
+
+    /// Remove everything before the cursor
+    pub fn drop_to_start(&mut self) {
+        self.drop_before_cursor(0);
+    }
+
+    fn drop_before_cursor(&mut self, start: usize) {
+        let range = self.get_byte_index(start)..self.get_byte_index(self.cursor);
+        self.contents.replace_range(range, "");
+        self.cursor = start;
     }
 
     /// Clear contents of the command line and save a message to a message_queue field, so that
@@ -90,6 +257,19 @@ impl CommandLine {
         self.get_message_batch();
     }
 
+    /// Add a message after the ones which are still shown or waiting to be shown, e.g. to report
+    /// several problems at once
+    pub fn append_message(&mut self, msg: String) {
+        if self.current_message.is_none() {
+            return self.command_line_message(msg);
+        }
+        let mut lines = self.current_message.take().unwrap_or_default();
+        lines.extend(self.message_queue.take().unwrap_or_default());
+        lines.extend(msg.lines().map(|f| f.to_string()));
+        self.message_queue = Some(lines);
+        self.get_message_batch();
+    }
+
     /// get a batch consisting of line_limit and pop it off the message. If the remaining message
     /// is empty, make it None
     pub fn get_message_batch(&mut self) {
@@ -130,11 +310,16 @@ impl Component for CommandLine {
                 frame.render_widget(Clear, actual_area);
                 frame.render_widget(text, actual_area);
                 if self.focused {
+                    // Wide chars, e.g. CJK ones, take two columns
+                    let before_cursor = &self.contents[..self.get_byte_index(self.cursor)];
                     frame.set_cursor(
-                        actual_area.x + self.contents.len() as u16,
-                        actual_area.y + 1,
+                        actual_area.x + Span::raw(before_cursor).width() as u16,
+                        actual_area.y,
                     )
                 }
+                if let Some(completion) = self.get_completion() {
+                    draw_completion(frame, actual_area, completion);
+                }
             }
             Some(contents) => {
                 actual_area = Layout::default()
@@ -157,3 +342,152 @@ impl Component for CommandLine {
         Ok(())
     }
 }
+
+/// List the candidates above the command line, starting at the column of the completed text
+fn draw_completion(frame: &mut Frame, command_bar: Rect, completion: &Completion) {
+    let height = completion
+        .candidates
+        .len()
+        .min(COMPLETION_LIST_HEIGHT)
+        .min(command_bar.y as usize);
+    if height == 0 {
+        return;
+    }
+    // Scrolled so that the selected candidate is visible
+    let first = completion
+        .selected
+        .map_or(0, |index| (index + 1).saturating_sub(height));
+    let visible = &completion.candidates[first..first + height];
+    let width = visible
+        .iter()
+        .map(|candidate| Span::raw(candidate).width())
+        .max()
+        .unwrap_or(0) as u16
+        + 2;
+    let column = Span::raw(&completion.before).width() as u16;
+    let width = width.min(command_bar.width);
+    let x = command_bar.x + column.min(command_bar.width - width);
+    let area = Rect::new(x, command_bar.y - height as u16, width, height as u16);
+    let items = visible
+        .iter()
+        .enumerate()
+        .map(|(index, candidate)| {
+            let item = ListItem::new(format!(" {} ", candidate));
+            match completion.selected == Some(first + index) {
+                true => item.style(Style::default().add_modifier(Modifier::REVERSED)),
+                false => item,
+            }
+        })
+        .collect::<Vec<ListItem>>();
+    frame.render_widget(Clear, area);
+    frame.render_widget(List::new(items), area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_line(command_line: &mut CommandLine, line: &str) {
+        for ch in line.chars() {
+            command_line.append_char(ch);
+        }
+    }
+
+    #[test]
+    fn test_edit_at_cursor() {
+        let mut command_line = CommandLine::new();
+        type_line(&mut command_line, "cd doc");
+        command_line.move_cursor_to_start();
+        command_line.move_cursor_right();
+        command_line.move_cursor_right();
+        command_line.drop_char();
+        type_line(&mut command_line, "é日");
+        assert_eq!(command_line.get_contents(), "cé日 doc");
+        command_line.move_cursor_to_end();
+        type_line(&mut command_line, "s");
+        assert_eq!(command_line.get_contents(), "cé日 docs");
+
+        command_line.move_cursor_left();
+        command_line.move_cursor_left();
+        command_line.drop_to_start();
+        assert_eq!(command_line.get_contents(), "cs");
+        command_line.move_cursor_left();
+        command_line.move_cursor_left();
+        command_line.drop_char();
+        assert_eq!(command_line.get_contents(), "cs");
+    }
+
+    #[test]
+    fn test_drop_word() {
+        let mut command_line = CommandLine::new();
+        command_line.set_contents("vsplit ../my_docs  ".to_string());
+        command_line.drop_word();
+        assert_eq!(command_line.get_contents(), "vsplit ../");
+        command_line.drop_word();
+        assert_eq!(command_line.get_contents(), "vsplit ");
+        command_line.drop_word();
+        assert_eq!(command_line.get_contents(), "");
+        command_line.drop_word();
+        assert_eq!(command_line.get_contents(), "");
+    }
+
+    #[test]
+    fn test_cycle_completion() {
+        let mut command_line = CommandLine::new();
+        command_line.set_contents("cd d end".to_string());
+        for _ in 0..4 {
+            command_line.move_cursor_left();
+        }
+        command_line.start_completion(3, vec!["data/".to_string(), "docs/".to_string()]);
+        assert_eq!(command_line.get_contents(), "cd d end");
+        command_line.cycle_completion(true);
+        assert_eq!(command_line.get_contents(), "cd data/ end");
+        command_line.cycle_completion(true);
+        assert_eq!(command_line.get_contents(), "cd docs/ end");
+        // The typed text comes after the last candidate
+        command_line.cycle_completion(true);
+        assert_eq!(command_line.get_contents(), "cd d end");
+        command_line.cycle_completion(false);
+        assert_eq!(command_line.get_contents(), "cd docs/ end");
+        assert_eq!(command_line.get_completion().unwrap().selected, Some(1));
+
+        // Editing the contents ends the completion
+        command_line.append_char('x');
+        assert_eq!(command_line.get_completion(), None);
+
+        command_line.set_contents("vs".to_string());
+        command_line.start_completion(0, vec!["vsplit".to_string()]);
+        assert_eq!(command_line.get_contents(), "vsplit");
+        assert_eq!(command_line.get_completion(), None);
+    }
+
+    #[test]
+    fn test_append_message() {
+        let mut command_line = CommandLine::new();
+        command_line.set_contents("cd".to_string());
+        command_line.append_message("first".to_string());
+        assert_eq!(command_line.get_contents(), "");
+        command_line.append_message("second\nthird".to_string());
+        assert_eq!(
+            command_line.current_message,
+            Some(vec![
+                "first".to_string(),
+                "second".to_string(),
+                "third".to_string()
+            ])
+        );
+
+        // Lines past the limit wait for the next batch
+        command_line.line_limit = 2;
+        command_line.append_message("fourth".to_string());
+        assert_eq!(
+            command_line.current_message,
+            Some(vec!["first".to_string(), "second".to_string()])
+        );
+        command_line.get_message_batch();
+        assert_eq!(
+            command_line.current_message,
+            Some(vec!["third".to_string(), "fourth".to_string()])
+        );
+    }
+}
@@ -1,18 +1,22 @@
 use core::panic;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 
+use git2::Status;
 use ratatui::Frame;
 use ratatui::crossterm::event::KeyEvent;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 
+use super::explorer_table::explorer_sorting::SortOrder;
 use super::explorer_table::explorer_utils::FileConfig;
 use super::explorer_table::{ExplorerTable, FileData, GlobalStyling};
 use crate::explorer_helpers::{
     calculate_distance, convert_sequence_to_string, delegate_to_focused,
 };
+use crate::git_helpers::GitStatusCache;
 use crate::history_stack::directory_history::DirectoryHistory;
 use crate::mode::Mode;
+use crate::search_pattern::FilterPattern;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum ParentRelationship {
@@ -40,6 +44,11 @@ pub struct ExplorerManager {
     pub focused_id: usize,
     pub next_id: usize,
     pub last_layout: HashMap<usize, Rect>,
+    /// Whether the sort order chosen in a directory is used again when entering it
+    pub remember_sort_orders: bool,
+    pub directory_sort_orders: HashMap<PathBuf, SortOrder>,
+    /// Git statuses computed by the explorers
+    git_cache: GitStatusCache,
 }
 
 impl Default for ExplorerManager {
@@ -50,13 +59,18 @@ impl Default for ExplorerManager {
 
 impl ExplorerManager {
     pub fn new() -> Self {
+        let git_cache = GitStatusCache::default();
+        let table = ExplorerTable::new_with_git_cache(git_cache.clone());
         let mut explorer_map = HashMap::new();
-        explorer_map.insert(0, ExplorerNode::new(0));
+        explorer_map.insert(0, ExplorerNode::new_with_explorer(0, table));
         Self {
             explorers: explorer_map,
             focused_id: 0,
             next_id: 1,
             last_layout: HashMap::new(),
+            remember_sort_orders: false,
+            directory_sort_orders: HashMap::new(),
+            git_cache,
         }
     }
 
@@ -297,9 +311,34 @@ impl ExplorerManager {
     }
 
     pub fn update_path(&mut self, path: PathBuf, filename: Option<String>) {
+        if self.remember_sort_orders
+            && let Some(sort_order) = self.directory_sort_orders.get(&path)
+        {
+            delegate_to_focused!(self, set_sort_order, *sort_order);
+        }
         delegate_to_focused!(self, update_path, path, filename);
     }
 
+    pub fn set_sort_order(&mut self, sort_order: SortOrder) {
+        delegate_to_focused!(self, set_sort_order, sort_order);
+        if self.remember_sort_orders {
+            let current_path = self.get_current_path();
+            self.directory_sort_orders.insert(current_path, sort_order);
+        }
+    }
+
+    pub fn get_sort_order(&mut self) -> SortOrder {
+        delegate_to_focused!(self, get_sort_order)
+    }
+
+    pub fn toggle_hidden(&mut self) {
+        delegate_to_focused!(self, toggle_hidden)
+    }
+
+    pub fn toggle_ignored(&mut self) {
+        delegate_to_focused!(self, toggle_ignored)
+    }
+
     pub fn get_current_path(&mut self) -> PathBuf {
         delegate_to_focused!(self, get_current_path)
     }
@@ -329,6 +368,10 @@ impl ExplorerManager {
         delegate_to_focused!(self, get_selected_string)
     }
 
+    pub fn get_git_status(&mut self, filename: &str) -> Option<Status> {
+        delegate_to_focused!(self, get_git_status, filename)
+    }
+
     pub fn get_search_phrase(&mut self) -> Option<String> {
         delegate_to_focused!(self, get_search_phrase)
     }
@@ -341,6 +384,18 @@ impl ExplorerManager {
         delegate_to_focused!(self, next_search_result);
     }
 
+    pub fn previous_search_result(&mut self) {
+        delegate_to_focused!(self, previous_search_result);
+    }
+
+    pub fn set_filter(&mut self, filter: Option<FilterPattern>) {
+        delegate_to_focused!(self, set_filter, filter);
+    }
+
+    pub fn get_filter(&mut self) -> Option<String> {
+        delegate_to_focused!(self, get_filter)
+    }
+
     pub fn clear_search_query(&mut self) {
         delegate_to_focused!(self, clear_search_query);
     }
@@ -353,9 +408,21 @@ impl ExplorerManager {
         delegate_to_focused!(self, previous);
     }
 
+    pub fn next_by(&mut self, count: usize) {
+        delegate_to_focused!(self, next_by, count);
+    }
+
+    pub fn previous_by(&mut self, count: usize) {
+        delegate_to_focused!(self, previous_by, count);
+    }
+
     pub fn jump_to_id(&mut self, id: usize) {
         delegate_to_focused!(self, jump_to_id, id);
     }
+
+    pub fn jump_to_row(&mut self, row: usize) {
+        delegate_to_focused!(self, jump_to_row, row);
+    }
     pub fn refresh_contents(&mut self) {
         delegate_to_focused!(self, refresh_contents);
     }
@@ -372,6 +439,10 @@ impl ExplorerManager {
         delegate_to_focused!(self, get_affected_paths)
     }
 
+    pub fn get_paths_in_range(&mut self, first_row: usize, last_row: usize) -> Vec<PathBuf> {
+        delegate_to_focused!(self, get_paths_in_range, first_row, last_row)
+    }
+
     pub fn reset_marked_rows(&mut self) {
         delegate_to_focused!(self, reset_marked_rows);
     }
@@ -386,6 +457,58 @@ impl ExplorerManager {
     pub fn set_highlighting_rule(&mut self, highlighting_rule: GlobalStyling) {
         delegate_to_focused!(self, set_highlighting_rule, highlighting_rule);
     }
+    /// Directories shown in the splits
+    pub fn get_visible_paths(&self) -> HashSet<PathBuf> {
+        self.explorers
+            .values()
+            .filter_map(|node| match &node.split {
+                Split::Single(table) => Some(table.get_current_path()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Git index files of the repositories shown in the splits
+    pub fn get_index_paths(&self) -> HashSet<PathBuf> {
+        self.explorers
+            .values()
+            .filter_map(|node| match &node.split {
+                Split::Single(table) => table.get_index_path(),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Read the given directories again, with their git status, in the splits showing them. The
+    /// splits in a repository whose index is given only compute their git status again.
+    pub fn refresh_paths(&mut self, paths: &HashSet<PathBuf>) {
+        self.git_cache.invalidate();
+        for node in self.explorers.values_mut() {
+            let Split::Single(table) = &mut node.split else {
+                continue;
+            };
+            if paths.contains(&table.get_current_path()) {
+                table.update_git_status();
+                table.refresh_contents();
+            } else if table
+                .get_index_path()
+                .is_some_and(|index_path| paths.contains(&index_path))
+            {
+                table.update_git_status();
+            }
+        }
+    }
+
+    /// Read the directories of all splits again before the next render
+    pub fn invalidate_listings(&mut self) {
+        self.git_cache.invalidate();
+        for node in self.explorers.values_mut() {
+            if let Split::Single(table) = &mut node.split {
+                table.invalidate_listing();
+            }
+        }
+    }
+
     pub fn set_plugin_display(&mut self, plugin_display: Option<String>) {
         delegate_to_focused!(self, set_plugin_display, plugin_display);
     }
@@ -393,6 +516,21 @@ impl ExplorerManager {
     pub fn get_directory_history(&mut self) -> &mut DirectoryHistory {
         delegate_to_focused!(self, get_directory_history)
     }
+
+    /// Directories shown in the splits other than the focused one, ordered by their ids
+    pub fn get_other_paths(&self) -> Vec<PathBuf> {
+        let mut other_explorers = self
+            .explorers
+            .iter()
+            .filter(|(id, _)| **id != self.focused_id)
+            .filter_map(|(id, node)| match &node.split {
+                Split::Single(table) => Some((*id, table.get_current_path())),
+                _ => None,
+            })
+            .collect::<Vec<(usize, PathBuf)>>();
+        other_explorers.sort();
+        other_explorers.into_iter().map(|(_, path)| path).collect()
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
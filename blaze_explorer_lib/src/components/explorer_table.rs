@@ -1,15 +1,19 @@
+pub mod explorer_sorting;
 pub mod explorer_styling;
 pub mod explorer_utils;
 use chrono::{DateTime, offset::Utc};
+use explorer_sorting::{SortOrder, sort_file_data};
 use explorer_styling::ExplorerStyle;
 use explorer_utils::FileConfig;
-use git2::{Repository, Status, StatusOptions};
+use git2::{Repository, Status};
 use layout::Alignment;
 use std::collections::HashMap;
+use std::ffi::OsString;
+use std::ops::Range;
 use std::path::PathBuf;
 use std::{
     fmt::Debug,
-    fs,
+    fs, io,
     path::{self, Path},
 };
 
@@ -22,22 +26,33 @@ use ratatui::{
 };
 
 use crate::explorer_helpers::{highlight_search_result, jump_highlight};
-use crate::git_helpers::{assign_git_styling, get_repo};
+use crate::git_helpers::{GitState, GitStatusCache, assign_git_styling, get_repo};
 use crate::history_stack::directory_history::DirectoryHistory;
+use crate::search_pattern::{FilterPattern, SearchPattern};
 use crate::{mode::Mode, themes::CustomTheme};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct FileData {
     pub id: usize,
+    /// Name shown in the table, with the characters which are not valid UTF-8 replaced
     pub filename: String,
+    /// Name of the entry on disk
+    pub os_filename: OsString,
     pub size: u64,
     pub modified: Option<DateTime<Utc>>,
+    pub is_dir: bool,
+    /// Symbolic link whose target does not exist
+    pub is_broken_link: bool,
 }
 
 impl FileData {
     pub fn contains(&self, query: &str) -> bool {
         self.filename.contains(query)
     }
+
+    pub fn matches(&self, pattern: &SearchPattern) -> bool {
+        pattern.is_match(&self.filename)
+    }
 }
 pub(crate) const SUFFIXES: [&str; 5] = ["B", "K", "M", "G", "T"];
 pub fn format_file_size(size: u64) -> String {
@@ -58,54 +73,47 @@ pub fn format_last_time(last_time: &Option<DateTime<Utc>>) -> String {
         String::from("")
     }
 }
-pub fn get_file_data(path: &PathBuf) -> Vec<FileData> {
-    let paths = fs::read_dir(path).unwrap();
-    let dir_entries = paths.map(|entry| entry.unwrap());
-    let data = dir_entries
-        .map(|entry| {
-            (
-                entry.file_name().to_str().unwrap().to_string(),
-                entry.metadata().unwrap().len(),
-                entry.metadata().unwrap().modified(),
-            )
-        })
+/// Entries of the directory. Entries which cannot be read are left out, while missing metadata
+/// is shown as empty.
+pub fn get_file_data(path: &PathBuf) -> io::Result<Vec<FileData>> {
+    let data = fs::read_dir(path)?
+        .flatten()
         .enumerate()
-        .map(|(id, (file_name, file_size, modified))| {
-            let modified_time: Option<DateTime<Utc>>;
-            if let Ok(system_time) = modified {
-                modified_time = Some(system_time.into());
-            } else {
-                modified_time = None;
-            };
+        .map(|(id, entry)| {
+            let entry_path = entry.path();
+            let metadata = entry.metadata().ok();
+            let is_link = entry
+                .file_type()
+                .is_ok_and(|file_type| file_type.is_symlink());
+            let os_filename = entry.file_name();
             FileData {
                 id,
-                filename: file_name,
-                size: file_size,
-                modified: modified_time,
+                filename: os_filename.to_string_lossy().to_string(),
+                os_filename,
+                size: metadata.as_ref().map_or(0, |metadata| metadata.len()),
+                modified: metadata
+                    .and_then(|metadata| metadata.modified().ok())
+                    .map(|system_time| system_time.into()),
+                is_dir: entry_path.is_dir(),
+                is_broken_link: is_link && !entry_path.exists(),
             }
         })
         .collect::<Vec<FileData>>();
-    data
+    Ok(data)
 }
 
-fn get_line_numbers(n_lines: usize, current_line: usize) -> Vec<String> {
-    //create all string labels before the selected line
-    let before_selected = (1..current_line)
-        .rev()
-        .map(|number| number.to_string())
-        .collect::<Vec<String>>();
-    let mut current_lines = before_selected;
-    let n_lines_after = n_lines - current_line;
-    let after_selected_iter = (1..n_lines_after + 1).map(|number| number.to_string());
-    let current_line_string = format!("{} ", current_line);
-    current_lines.append(&mut vec![current_line_string]);
-    current_lines.extend(after_selected_iter);
-    current_lines
+/// Labels of the rows: the number of the selected row, and the distance to it for the others
+fn get_line_numbers(rows: Range<usize>, selected: usize) -> Vec<String> {
+    rows.map(|row| match row == selected {
+        true => format!("{} ", row + 1),
+        false => row.abs_diff(selected).to_string(),
+    })
+    .collect()
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum GlobalStyling {
-    HighlightSearch(String), //highlight background of the text with the search query
+    HighlightSearch(SearchPattern), //highlight background of the text matching the search pattern
     HighlightJump(String, HashMap<char, usize>), //highlight foreground text with search query
     //+ the char used for the jumping action
     None, //no styling
@@ -114,7 +122,16 @@ pub enum GlobalStyling {
 pub struct ExplorerTable {
     state: TableState,
     current_path: PathBuf,
+    /// Rows shown, i.e. the entries of the directory which match the filter. Ids index
+    /// `all_elements`, so they keep pointing at the same entries when the filter changes.
     elements_list: Vec<FileData>,
+    all_elements: Vec<FileData>,
+    /// Whether the directory has to be read again before the next render
+    listing_outdated: bool,
+    filter: Option<FilterPattern>,
+    sort_order: SortOrder,
+    show_hidden: bool,
+    show_ignored: bool,
     mode: Mode,
     marked_ids: Option<Vec<usize>>,
     theme: CustomTheme,
@@ -124,6 +141,8 @@ pub struct ExplorerTable {
     directory_history: DirectoryHistory,
     repo: Option<Repository>,
     git_map: Option<HashMap<String, Status>>,
+    /// Shared with the other explorers of the manager
+    git_cache: GitStatusCache,
 }
 impl Default for ExplorerTable {
     fn default() -> Self {
@@ -137,6 +156,12 @@ impl Clone for ExplorerTable {
             state: self.state.clone(),
             current_path: self.current_path.clone(),
             elements_list: self.elements_list.clone(),
+            all_elements: self.all_elements.clone(),
+            listing_outdated: self.listing_outdated,
+            filter: self.filter.clone(),
+            sort_order: self.sort_order,
+            show_hidden: self.show_hidden,
+            show_ignored: self.show_ignored,
             mode: self.mode.clone(),
             marked_ids: self.marked_ids.clone(),
             theme: self.theme.clone(),
@@ -146,6 +171,7 @@ impl Clone for ExplorerTable {
             directory_history: self.directory_history.clone(),
             repo: get_repo(self.current_path.clone()),
             git_map: self.git_map.clone(),
+            git_cache: self.git_cache.clone(),
         }
     }
 }
@@ -160,6 +186,11 @@ impl Debug for ExplorerTable {
             .field("state", &self.state)
             .field("current_path", &self.current_path)
             .field("elements_list", &self.elements_list)
+            .field("listing_outdated", &self.listing_outdated)
+            .field("filter", &self.filter)
+            .field("sort_order", &self.sort_order)
+            .field("show_hidden", &self.show_hidden)
+            .field("show_ignored", &self.show_ignored)
             .field("mode", &self.mode)
             .field("selected_ids", &self.marked_ids)
             .field("theme", &self.theme)
@@ -177,6 +208,12 @@ impl PartialEq for ExplorerTable {
         self.state == other.state
             && self.current_path == other.current_path
             && self.elements_list == other.elements_list
+            && self.all_elements == other.all_elements
+            && self.listing_outdated == other.listing_outdated
+            && self.filter == other.filter
+            && self.sort_order == other.sort_order
+            && self.show_hidden == other.show_hidden
+            && self.show_ignored == other.show_ignored
             && self.mode == other.mode
             && self.marked_ids == other.marked_ids
             && self.theme == other.theme
@@ -190,11 +227,21 @@ impl PartialEq for ExplorerTable {
 
 impl ExplorerTable {
     pub fn new() -> Self {
+        Self::new_with_git_cache(GitStatusCache::default())
+    }
+
+    pub fn new_with_git_cache(git_cache: GitStatusCache) -> Self {
         let starting_path = path::absolute("./").unwrap();
         let mut new_self = Self {
             state: TableState::default().with_selected(0),
             current_path: starting_path.clone(),
             elements_list: Vec::new(),
+            all_elements: Vec::new(),
+            listing_outdated: true,
+            filter: None,
+            sort_order: SortOrder::default(),
+            show_hidden: true,
+            show_ignored: true,
             mode: Mode::Normal,
             marked_ids: None,
             theme: CustomTheme::default(),
@@ -204,6 +251,7 @@ impl ExplorerTable {
             directory_history: DirectoryHistory::default(),
             repo: get_repo(starting_path),
             git_map: None,
+            git_cache,
         };
         new_self.git_map = new_self.get_git_map();
         new_self
@@ -225,113 +273,195 @@ impl ExplorerTable {
 
     pub fn update_path(&mut self, path: PathBuf, selected: Option<String>) {
         self.current_path = path;
-        let elements = get_file_data(&self.current_path);
-        self.elements_list = elements;
+        self.repo = get_repo(self.current_path.clone());
+        self.git_map = self.get_git_map();
+        self.load_elements();
+        // The filter applies to the directory it was typed in
+        self.filter = None;
+        self.elements_list.clear();
+        self.state = TableState::default().with_selected(0);
+        self.apply_filter();
         if let Some(to_select) = selected {
             let position_of_prev = self
                 .elements_list
                 .iter()
                 .position(|x| x.filename.as_str() == to_select.as_str());
             self.state.select(position_of_prev);
-        } else {
-            self.state = TableState::default().with_selected(0);
         }
-        self.repo = get_repo(self.current_path.clone());
-        self.git_map = self.get_git_map();
     }
 
     pub fn get_git_map(&self) -> Option<HashMap<String, Status>> {
-        let mut map: HashMap<String, Status> = HashMap::new();
-        if let Some(repo) = &self.repo {
-            let statuses = match repo.statuses(Some(
-                StatusOptions::new()
-                    .include_ignored(true)
-                    .include_untracked(true),
-            )) {
-                Ok(statuses) => statuses,
-                // Give up on error
-                Err(_) => return None,
-            };
-            let root_path = repo.path().parent().unwrap();
-            for status_entry in statuses.iter() {
-                // create an absolute path made of the git root path and the status_entry path
-                let abs_path = root_path.join(status_entry.path().unwrap());
-                let parent_path = abs_path.parent().unwrap();
-                if parent_path != self.current_path {
-                    continue;
-                }
-                //insert if the parent of the status entry is the same as explorer table path
-                //extract the name of the file
-                let filename = abs_path.file_name().unwrap().to_str().unwrap().to_string();
-                map.insert(filename, status_entry.status());
-            }
-            //ensure .git is always untracked
-            map.insert(".git".to_string(), Status::IGNORED);
-            return Some(map);
-        }
-        None
+        self.git_cache
+            .get_statuses(self.repo.as_ref()?, &self.current_path)
     }
 
+    /// Read the directory again
     pub fn refresh_contents(&mut self) {
+        self.load_elements();
+        self.apply_filter();
+    }
+
+    /// Compute the git status of the entries again, e.g. after they changed
+    pub fn update_git_status(&mut self) {
+        self.git_map = self.get_git_map();
+    }
+
+    /// Git status shown for the entry
+    pub fn get_git_status(&self, filename: &str) -> Option<Status> {
+        self.git_map.as_ref()?.get(filename).copied()
+    }
+
+    /// Index file of the repository containing the directory, changed by e.g. `git add`
+    pub fn get_index_path(&self) -> Option<PathBuf> {
+        self.repo.as_ref().map(|repo| repo.path().join("index"))
+    }
+
+    /// Read the directory again before the next render, e.g. after its files changed
+    pub fn invalidate_listing(&mut self) {
+        self.listing_outdated = true;
+    }
+
+    fn load_elements(&mut self) {
+        // A directory which cannot be read anymore, e.g. after being deleted, is shown empty
+        self.all_elements = get_file_data(&self.current_path).unwrap_or_default();
+        sort_file_data(&mut self.all_elements, &self.sort_order);
+        self.listing_outdated = false;
+    }
+
+    /// Sort the rows again, keeping the same entries selected and marked
+    pub fn set_sort_order(&mut self, sort_order: SortOrder) {
+        let marked_filenames = self.marked_ids.as_ref().map(|ids| {
+            ids.iter()
+                .filter_map(|id| self.all_elements.get(*id))
+                .map(|element| element.filename.clone())
+                .collect::<Vec<String>>()
+        });
+        self.sort_order = sort_order;
+        sort_file_data(&mut self.all_elements, &self.sort_order);
+        self.marked_ids = marked_filenames.map(|filenames| {
+            self.all_elements
+                .iter()
+                .filter(|element| filenames.contains(&element.filename))
+                .map(|element| element.id)
+                .collect()
+        });
+        self.apply_filter();
+    }
+
+    pub fn get_sort_order(&self) -> SortOrder {
+        self.sort_order
+    }
+
+    /// Show the entries matching the filter and not hidden by the toggles, keeping the selected
+    /// entry selected if it is still shown
+    fn apply_filter(&mut self) {
         //get currently selected item
+        let selected = self.state.selected().unwrap_or(0);
+        let selected_element_path = self.get_selected_string();
+        self.elements_list = self
+            .all_elements
+            .iter()
+            .filter(|element| self.is_shown(element))
+            .cloned()
+            .collect();
+        // Hidden rows are not search results
+        if let GlobalStyling::HighlightSearch(_) = self.style.highlighting_rule() {
+            self.search_elements();
+        }
         if self.elements_list.is_empty() {
             self.state.select(None);
             return;
         }
-        let mut selected = self.state.selected().unwrap();
-        let selected_element_path = self.elements_list[selected].filename.clone();
         // if that element still exists, select it once more
-        let elements = get_file_data(&self.current_path);
-        self.elements_list = elements;
         if let Some(index) = self
             .elements_list
             .iter()
-            .position(|x| x.filename == selected_element_path)
+            .position(|x| Some(&x.filename) == selected_element_path.as_ref())
         {
             self.state.select(Some(index));
             return;
         }
-        //otherwise, select an item with the same id unless it was the last item
-        if selected >= self.elements_list.len() {
-            selected = self.elements_list.len() - 1;
-        }
-        self.state.select(Some(selected));
+        //otherwise, select the row with the same number unless it was the last row
+        self.state
+            .select(Some(selected.min(self.elements_list.len() - 1)));
+    }
+
+    fn is_shown(&self, element: &FileData) -> bool {
+        let is_ignored = || {
+            self.git_map
+                .as_ref()
+                .and_then(|map| map.get(&element.filename))
+                .is_some_and(|status| status.contains(Status::IGNORED))
+        };
+        (self.show_hidden || !element.filename.starts_with('.'))
+            && (self.show_ignored || !is_ignored())
+            && self
+                .filter
+                .as_ref()
+                .is_none_or(|filter| filter.is_match(&element.filename))
+    }
+
+    /// Show or hide the dotfiles
+    pub fn toggle_hidden(&mut self) {
+        self.show_hidden = !self.show_hidden;
+        self.apply_filter();
+    }
+
+    /// Show or hide the entries ignored by git
+    pub fn toggle_ignored(&mut self) {
+        self.show_ignored = !self.show_ignored;
+        self.apply_filter();
+    }
+
+    /// Hide the rows not matching the filter, or show all of them again
+    pub fn set_filter(&mut self, filter: Option<FilterPattern>) {
+        self.filter = filter.filter(|filter| !filter.query().is_empty());
+        self.apply_filter();
+    }
+
+    pub fn get_filter(&self) -> Option<String> {
+        self.filter
+            .as_ref()
+            .map(|filter| filter.query().to_string())
     }
     pub fn next(&mut self) {
+        self.next_by(1);
+    }
+    pub fn previous(&mut self) {
+        self.previous_by(1);
+    }
+
+    /// Move the selection down by `count` rows, wrapping around the end of the list
+    pub fn next_by(&mut self, count: usize) {
+        let n_rows = self.elements_list.len();
+        if n_rows == 0 || count == 0 {
+            return;
+        }
         let i = match self.state.selected() {
-            Some(i) => {
-                if i >= self.elements_list.len() - 1 {
-                    0
-                } else {
-                    i + 1
-                }
-            }
-            None => 0,
+            Some(i) => (i.min(n_rows - 1) + count % n_rows) % n_rows,
+            // The first step selects the first row
+            None => (count - 1) % n_rows,
         };
         self.state.select(Some(i));
     }
-    pub fn previous(&mut self) {
+
+    /// Move the selection up by `count` rows, wrapping around the start of the list
+    pub fn previous_by(&mut self, count: usize) {
+        let n_rows = self.elements_list.len();
+        if n_rows == 0 || count == 0 {
+            return;
+        }
         let i = match self.state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.elements_list.len() - 1
-                } else {
-                    i - 1
-                }
-            }
-            None => 0,
+            Some(i) => (i.min(n_rows - 1) + n_rows - count % n_rows) % n_rows,
+            // The first step selects the first row
+            None => (n_rows - (count - 1) % n_rows) % n_rows,
         };
         self.state.select(Some(i));
     }
 
     pub fn select_directory(&self) -> Option<PathBuf> {
-        if let Some(index) = self.state.selected() {
-            let chosen_element = &self.elements_list[index];
-            let created_path = Path::new(&self.current_path).join(&chosen_element.filename);
-            Some(created_path)
-        } else {
-            None
-        }
+        let chosen_element = self.elements_list.get(self.state.selected()?)?;
+        Some(Path::new(&self.current_path).join(&chosen_element.os_filename))
     }
 
     pub fn get_affected_paths(&self) -> Option<Vec<PathBuf>> {
@@ -339,18 +469,40 @@ impl ExplorerTable {
             Mode::Normal => self.select_directory().map(|x| vec![x]),
             Mode::Visual => self.get_marked_ids().as_ref().map(|ids| {
                 ids.iter()
-                    .map(|x| {
-                        self.current_path
-                            .join(self.elements_list[*x].filename.clone())
-                    })
+                    .filter_map(|x| self.all_elements.get(*x))
+                    .map(|element| self.current_path.join(&element.os_filename))
                     .collect()
             }),
             _ => panic!("Impossible call in this mode"),
         }
     }
 
+    /// Paths of the shown rows between the two row indices (both inclusive, in any order), limited
+    /// to the rows of the table. The indices are positions in the table, not `FileData::id`s,
+    /// which differ while a filter is active.
+    pub fn get_paths_in_range(&self, first_row: usize, last_row: usize) -> Vec<PathBuf> {
+        if self.elements_list.is_empty() {
+            return Vec::new();
+        }
+        let max_row = self.elements_list.len() - 1;
+        let start = first_row.min(last_row).min(max_row);
+        let end = first_row.max(last_row).min(max_row);
+        self.elements_list[start..=end]
+            .iter()
+            .map(|element| self.current_path.join(&element.os_filename))
+            .collect()
+    }
+
+    /// Select the entry with the id, e.g. the one of a jump label. Entries which are not shown are
+    /// not selected.
     pub fn jump_to_id(&mut self, id: usize) {
-        self.state.select(Some(id));
+        if let Some(row) = self.elements_list.iter().position(|e| e.id == id) {
+            self.state.select(Some(row));
+        }
+    }
+
+    pub fn jump_to_row(&mut self, row: usize) {
+        self.state.select(Some(row));
     }
 
     pub fn update_search_query(&mut self) {
@@ -359,44 +511,51 @@ impl ExplorerTable {
 
     pub fn get_search_phrase(&self) -> Option<String> {
         match &self.style.highlighting_rule() {
-            GlobalStyling::HighlightSearch(query) => Some(query.clone()),
+            GlobalStyling::HighlightSearch(pattern) => Some(pattern.query().to_string()),
             _ => None,
         }
     }
 
     pub fn next_search_result(&mut self) {
-        if let Some(selected_ids) = &self.marked_ids {
-            if selected_ids.len() < 2 {
-                return;
-            }
-            let i = match self.state.selected() {
-                Some(i) => {
-                    // select the next search result whose id is bigger than id of selected element
-                    // if there is no such element then start from id 0 and check again
-                    let mut j = i;
-                    loop {
-                        j += 1;
-                        if j == self.elements_list.len() {
-                            j = 0;
-                        }
-                        if selected_ids.contains(&j) {
-                            break;
-                        }
-                    }
-                    j
-                }
-                None => 0,
-            };
-            self.state.select(Some(i));
+        self.step_search_result(true);
+    }
+
+    pub fn previous_search_result(&mut self) {
+        self.step_search_result(false);
+    }
+
+    /// Select the closest search result after (or before) the selected row, wrapping around the
+    /// ends of the list. Without a selection the first (or last) result is selected.
+    fn step_search_result(&mut self, forward: bool) {
+        let Some(result_ids) = &self.marked_ids else {
+            return;
+        };
+        let count = self.elements_list.len();
+        let rows: Vec<usize> = match (self.state.selected(), forward) {
+            (Some(selected), true) => (1..=count)
+                .map(|offset| (selected + offset) % count)
+                .collect(),
+            (Some(selected), false) => (1..=count)
+                .map(|offset| (selected + count - offset) % count)
+                .collect(),
+            (None, true) => (0..count).collect(),
+            (None, false) => (0..count).rev().collect(),
+        };
+        let found = rows
+            .into_iter()
+            .find(|row| result_ids.contains(&self.elements_list[*row].id));
+        if let Some(row) = found {
+            self.state.select(Some(row));
         }
     }
+
     pub fn search_elements(&mut self) {
         let element_ids =
-            if let GlobalStyling::HighlightSearch(query) = &self.style.highlighting_rule() {
+            if let GlobalStyling::HighlightSearch(pattern) = &self.style.highlighting_rule() {
                 Some(
                     self.elements_list
                         .iter()
-                        .filter(|x| x.filename.contains(query))
+                        .filter(|x| x.matches(pattern))
                         .map(|x| x.id)
                         .collect::<Vec<usize>>(),
                 )
@@ -420,7 +579,7 @@ impl ExplorerTable {
         let filename = path.file_name().unwrap();
         self.update_path(
             folder.to_path_buf(),
-            Some(filename.to_str().unwrap().to_string()),
+            Some(filename.to_string_lossy().to_string()),
         );
     }
 
@@ -457,7 +616,12 @@ impl ExplorerTable {
     }
 
     pub fn toggle_mark(&mut self) {
-        if let Some(selected) = self.state.selected() {
+        let selected_id = self
+            .state
+            .selected()
+            .and_then(|row| self.elements_list.get(row))
+            .map(|element| element.id);
+        if let Some(selected) = selected_id {
             if let Some(selected_ids) = &mut self.marked_ids {
                 if selected_ids.contains(&selected) {
                     selected_ids.retain(|x| x != &selected);
@@ -510,11 +674,12 @@ impl ExplorerTable {
         query: &'a str,
         inverted_map: HashMap<usize, char>,
         element_id: usize,
+        is_broken_link: bool,
     ) -> Cell<'a> {
-        let file_name_cell = Cell::from(match self.style.highlighting_rule() {
+        let mut line = match self.style.highlighting_rule() {
             GlobalStyling::None => Line::from(filename.clone()),
-            GlobalStyling::HighlightSearch(_) => {
-                highlight_search_result(filename.clone(), query, self.theme.search_result)
+            GlobalStyling::HighlightSearch(pattern) => {
+                highlight_search_result(filename.clone(), &pattern, self.theme.search_result)
             }
             GlobalStyling::HighlightJump(_, _) => jump_highlight(
                 filename.clone(),
@@ -523,8 +688,14 @@ impl ExplorerTable {
                 self.theme.highlight_query,
                 self.theme.highlight_jump_char,
             ),
-        });
-        file_name_cell
+        };
+        if is_broken_link {
+            line.push_span(Span::styled(
+                " -> broken link",
+                Style::default().fg(tailwind::RED.c400),
+            ));
+        }
+        Cell::from(line)
     }
     pub fn convert_filedata_to_row<'a>(
         &self,
@@ -539,44 +710,77 @@ impl ExplorerTable {
             query,
             inverted_map,
             element.id,
+            element.is_broken_link,
+        );
+        let git_status = self
+            .git_map
+            .as_ref()
+            .and_then(|map| map.get(&element.filename))
+            .copied();
+        let git_state_cell = Cell::from(
+            git_status
+                .and_then(GitState::from_status)
+                .map_or("", |state| state.glyph()),
         );
         let file_size_cell = Cell::from(Text::from(format_file_size(element.size)));
         let last_modified_cell = Cell::from(Text::from(format_last_time(&element.modified)));
         let row = Row::new(vec![
             row_number_cell,
+            git_state_cell,
             file_name_cell,
             file_size_cell,
             last_modified_cell,
         ]);
 
         let marked_ids = &self.marked_ids.clone().unwrap_or_default();
-        let mut style = match (
+        let selected_id = self
+            .state
+            .selected()
+            .and_then(|row| self.elements_list.get(row))
+            .map(|element| element.id);
+        let style = match (
             marked_ids.contains(&element.id),
-            Some(&element.id) == self.state.selected().as_ref(),
+            Some(element.id) == selected_id,
         ) {
             (true, true) => self.theme.marked_selected_row,
             (true, false) => self.theme.marked_row,
             (false, true) => self.theme.selected_row,
             (false, false) => self.theme.row,
         };
-        if let Some(map) = &self.git_map {
-            if let Some(status) = map.get(&element.filename) {
-                style = assign_git_styling(style, *status);
-            }
+        match git_status {
+            Some(status) => row.style(assign_git_styling(style, status)),
+            None => row.style(style),
         }
-        row.style(style)
+    }
+
+    /// Rows of the table fitting the given height, scrolled just enough to show the selected row
+    fn scroll_to_selected(&mut self, height: usize) -> Range<usize> {
+        let n_rows = self.elements_list.len();
+        let selected = self.state.selected().unwrap_or(0);
+        let mut offset = self.state.offset().min(n_rows.saturating_sub(height));
+        if selected < offset {
+            offset = selected;
+        } else if height > 0 && selected >= offset + height {
+            offset = selected + 1 - height;
+        }
+        *self.state.offset_mut() = offset;
+        offset..(offset + height).min(n_rows)
     }
 
     pub fn draw(&mut self, frame: &mut Frame, area: Rect, file_config: &FileConfig) -> Result<()> {
-        // get table block
-        self.refresh_contents();
+        // The listing is cached until invalidated
+        if self.listing_outdated {
+            self.update_git_status();
+            self.refresh_contents();
+        }
         let widths = [
             Constraint::Percentage(5),
+            Constraint::Length(1),
             Constraint::Fill(1),
             Constraint::Length(6),
             Constraint::Length(20),
         ];
-        let header = ["", "Name", "Size", "Last modified"]
+        let header = ["", "", "Name", "Size", "Last modified"]
             .into_iter()
             .map(Cell::from)
             .collect::<Row>()
@@ -586,14 +790,22 @@ impl ExplorerTable {
             GlobalStyling::HighlightJump(query, map) => {
                 (query.clone(), map.iter().map(|(k, v)| (*v, *k)).collect())
             }
-            GlobalStyling::HighlightSearch(query) => (query.clone(), HashMap::new()),
+            GlobalStyling::HighlightSearch(_) => (String::new(), HashMap::new()),
             GlobalStyling::None => (String::new(), HashMap::new()),
         };
+        //divide the available area into one for the table and one for the paragraph
+        let explorer_area_blocks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Fill(1), Constraint::Length(1)])
+            .split(area);
+        // Only the rows fitting between the borders, below the header, are built
+        let visible_rows =
+            self.scroll_to_selected(explorer_area_blocks[0].height.saturating_sub(3) as usize);
         let rows = match self.elements_list.is_empty() {
             false => {
                 let line_numbers =
-                    get_line_numbers(self.elements_list.len(), self.state.selected().unwrap() + 1);
-                self.elements_list
+                    get_line_numbers(visible_rows.clone(), self.state.selected().unwrap_or(0));
+                self.elements_list[visible_rows.clone()]
                     .iter()
                     .zip(line_numbers)
                     .map(|(element, row_number)| {
@@ -627,7 +839,7 @@ impl ExplorerTable {
                 })
                 .fg(tailwind::BLACK),
         );
-        let path_span = Span::from(self.current_path.to_str().unwrap());
+        let path_span = Span::from(self.current_path.to_string_lossy());
         let star_span = Span::from(match file_config.favourites.contains(&self.current_path) {
             true => "★",
             false => "☆",
@@ -645,9 +857,19 @@ impl ExplorerTable {
         let sequence_line =
             Line::from(file_config.string_sequence.to_owned()).alignment(Alignment::Right);
 
+        let filter_span = match &self.filter {
+            Some(filter) => Span::styled(
+                format!(" filter: {} ", filter.query()),
+                Style::default()
+                    .bg(tailwind::AMBER.c200)
+                    .fg(tailwind::BLACK),
+            ),
+            None => Span::from(String::from("")),
+        };
+
         let status_line = match self.focused {
-            true => Line::from(vec![mode_span, plugin_span, path_span]),
-            false => Line::from(vec![path_span]),
+            true => Line::from(vec![mode_span, plugin_span, filter_span, path_span]),
+            false => Line::from(vec![filter_span, path_span]),
         };
 
         let status_bar = match self.focused {
@@ -669,12 +891,12 @@ impl ExplorerTable {
             ),
         };
 
-        //divide the available area into one for the table and one for the paragraph
-        let explorer_area_blocks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Fill(1), Constraint::Length(1)])
-            .split(area);
-        frame.render_stateful_widget(t, explorer_area_blocks[0], &mut self.state);
+        let mut visible_state = TableState::default().with_selected(
+            self.state
+                .selected()
+                .map(|row| row.saturating_sub(visible_rows.start)),
+        );
+        frame.render_stateful_widget(t, explorer_area_blocks[0], &mut visible_state);
         frame.render_widget(status_bar, explorer_area_blocks[1]);
 
         Ok(())
@@ -684,17 +906,20 @@ impl ExplorerTable {
 #[cfg(test)]
 mod tests {
     use std::env;
+    use std::time::{Duration, Instant};
+
+    use ratatui::backend::TestBackend;
+    use tempdir::TempDir;
 
     use crate::app::App;
+    use crate::search_pattern::SearchSettings;
+    use crate::testing_utils::create_custom_testing_folder;
 
     use super::*;
 
     #[test]
     fn test_get_line_numbers() {
-        let current_line = 3_usize;
-        let line_length = 6_usize;
-
-        let result = get_line_numbers(line_length, current_line);
+        let result = get_line_numbers(0..6, 2);
 
         let expected_result = vec![
             String::from("2"),
@@ -705,6 +930,7 @@ mod tests {
             String::from("3"),
         ];
         assert_eq!(result, expected_result);
+        assert_eq!(get_line_numbers(3..5, 2), vec!["1", "2"]);
     }
 
     #[test]
@@ -749,4 +975,266 @@ mod tests {
         let affected_directories = app.explorer_manager.get_affected_paths();
         assert_eq!(affected_directories.unwrap().len(), 2);
     }
+
+    #[test]
+    fn test_filter_rows() {
+        let mut app = App::new().unwrap();
+        let testing_folder =
+            create_custom_testing_folder(vec!["notes.md", "Cargo.toml", "readme.md", "main.rs"])
+                .unwrap();
+        let starting_path = env::current_dir().unwrap();
+        let root_dir = testing_folder.root_dir.path().to_path_buf();
+        app.move_directory(root_dir.clone(), None);
+        let settings = SearchSettings::default();
+
+        app.explorer_manager
+            .set_filter(Some(FilterPattern::new(".md", &settings)));
+        assert_eq!(app.explorer_manager.find_elements("").len(), 2);
+        assert_eq!(app.explorer_manager.get_filter(), Some(".md".to_string()));
+
+        // Marks keep pointing at the same files while the filter changes
+        app.enter_visual_mode();
+        app.explorer_manager.toggle_mark();
+        let marked = app.explorer_manager.get_affected_paths().unwrap();
+        assert!(
+            marked[0]
+                .extension()
+                .is_some_and(|extension| extension == "md")
+        );
+        app.explorer_manager
+            .set_filter(Some(FilterPattern::new("cargo", &settings)));
+        assert_eq!(
+            app.explorer_manager.get_selected_string(),
+            Some("Cargo.toml".to_string())
+        );
+        app.explorer_manager.toggle_mark();
+        app.explorer_manager.set_filter(None);
+        let mut affected = app.explorer_manager.get_affected_paths().unwrap();
+        affected.sort();
+        let mut expected = vec![marked[0].clone(), root_dir.join("Cargo.toml")];
+        expected.sort();
+        assert_eq!(affected, expected);
+        assert_eq!(app.explorer_manager.find_elements("").len(), 4);
+
+        // Nothing is selected when no row matches
+        app.explorer_manager
+            .set_filter(Some(FilterPattern::new("nothing", &settings)));
+        assert_eq!(app.explorer_manager.get_selected(), None);
+        app.explorer_manager.next();
+        app.move_directory(starting_path, None);
+    }
+
+    #[test]
+    fn test_search_without_selection() {
+        let testing_folder =
+            create_custom_testing_folder(vec!["a.md", "b.txt", "c.md", "d.txt"]).unwrap();
+        let mut table = ExplorerTable::new();
+        table.update_path(testing_folder.root_dir.path().to_path_buf(), None);
+        let pattern = SearchPattern::new(".md", &SearchSettings::default());
+        table.set_highlighting_rule(GlobalStyling::HighlightSearch(pattern));
+
+        table.state.select(None);
+        table.next_search_result();
+        assert_eq!(table.get_selected_string(), Some("a.md".to_string()));
+        table.state.select(None);
+        table.previous_search_result();
+        assert_eq!(table.get_selected_string(), Some("c.md".to_string()));
+    }
+
+    /// Average time of a render after the first one, in a directory with the given number of
+    /// files
+    fn time_render(n_files: usize) -> Duration {
+        let testing_folder = TempDir::new("render").unwrap();
+        for i in 0..n_files {
+            fs::write(testing_folder.path().join(format!("file_{}.txt", i)), "").unwrap();
+        }
+        let mut table = ExplorerTable::new();
+        table.update_path(testing_folder.path().to_path_buf(), None);
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        let file_config = FileConfig::new(Vec::new(), String::new());
+        let mut render = || {
+            terminal
+                .draw(|frame| {
+                    let _ = table.draw(frame, frame.size(), &file_config);
+                })
+                .unwrap();
+        };
+        render();
+        let n_renders = 20;
+        let start = Instant::now();
+        for _ in 0..n_renders {
+            render();
+        }
+        start.elapsed() / n_renders
+    }
+
+    /// Rendering neither reads the directory again nor builds the rows out of sight
+    #[test]
+    fn test_render_cost() {
+        let testing_folder = TempDir::new("render").unwrap();
+        for i in 0..5000 {
+            fs::write(testing_folder.path().join(format!("file_{}.txt", i)), "").unwrap();
+        }
+        let mut table = ExplorerTable::new();
+        table.update_path(testing_folder.path().to_path_buf(), None);
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        let file_config = FileConfig::new(Vec::new(), String::new());
+        let mut render = |table: &mut ExplorerTable| {
+            terminal
+                .draw(|frame| {
+                    let _ = table.draw(frame, frame.size(), &file_config);
+                })
+                .unwrap();
+        };
+        render(&mut table);
+        fs::write(testing_folder.path().join("new.txt"), "").unwrap();
+        table.state.select(Some(4000));
+        render(&mut table);
+        assert_eq!(table.find_elements("").len(), 5000);
+        // 30 lines without the status line, the borders and the header
+        assert_eq!(table.scroll_to_selected(26), 3975..4001);
+    }
+
+    /// Rendering takes about as long in a big directory as in a small one. Timing dependent, so
+    /// only run on demand.
+    #[test]
+    #[ignore]
+    fn bench_render_cost() {
+        let small = time_render(10);
+        let large = time_render(5000);
+        assert!(
+            large < small * 4,
+            "rendering 5000 files took {:?}, 10 files {:?}",
+            large,
+            small
+        );
+    }
+
+    #[test]
+    fn test_git_state_column() {
+        let testing_folder =
+            create_custom_testing_folder(vec!["lib/", "lib/a.rs", "lib/b.rs", "new.txt"]).unwrap();
+        let root_dir = testing_folder.root_dir.path().to_path_buf();
+        let repo = Repository::init(&root_dir).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("lib/a.rs")).unwrap();
+        index.write().unwrap();
+        let mut table = ExplorerTable::new();
+        table.update_path(root_dir, None);
+
+        let mut terminal = Terminal::new(TestBackend::new(60, 10)).unwrap();
+        let file_config = FileConfig::new(Vec::new(), String::new());
+        terminal
+            .draw(|frame| {
+                let _ = table.draw(frame, frame.size(), &file_config);
+            })
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let lines = (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer.get(x, y).symbol())
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>();
+        // The directory shows its strongest status, a staged file rather than an untracked one
+        assert!(lines.iter().any(|line| line.contains("A lib")));
+        assert!(lines.iter().any(|line| line.contains("? new.txt")));
+        assert!(lines.iter().any(|line| line.contains("! .git")));
+    }
+
+    #[test]
+    fn test_cached_listing() {
+        let testing_folder = create_custom_testing_folder(vec!["a.txt"]).unwrap();
+        let root_dir = testing_folder.root_dir.path().to_path_buf();
+        let mut table = ExplorerTable::new();
+        table.update_path(root_dir.clone(), None);
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        let file_config = FileConfig::new(Vec::new(), String::new());
+        let mut render = |table: &mut ExplorerTable| {
+            terminal
+                .draw(|frame| {
+                    let _ = table.draw(frame, frame.size(), &file_config);
+                })
+                .unwrap();
+        };
+        fs::write(root_dir.join("b.txt"), "").unwrap();
+        render(&mut table);
+        assert_eq!(table.find_elements("").len(), 1);
+        table.invalidate_listing();
+        render(&mut table);
+        assert_eq!(table.find_elements("").len(), 2);
+    }
+
+    #[test]
+    fn test_scroll_to_selected() {
+        let testing_folder = TempDir::new("scroll").unwrap();
+        for i in 0..10 {
+            fs::write(testing_folder.path().join(format!("file_{}.txt", i)), "").unwrap();
+        }
+        let mut table = ExplorerTable::new();
+        table.update_path(testing_folder.path().to_path_buf(), None);
+        assert_eq!(table.scroll_to_selected(4), 0..4);
+        table.state.select(Some(6));
+        assert_eq!(table.scroll_to_selected(4), 3..7);
+        table.state.select(Some(4));
+        assert_eq!(table.scroll_to_selected(4), 3..7);
+        table.state.select(Some(1));
+        assert_eq!(table.scroll_to_selected(4), 1..5);
+        // A taller table shows the rows above rather than leaving space below
+        assert_eq!(table.scroll_to_selected(20), 0..10);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_get_unusual_file_data() {
+        use std::ffi::OsStr;
+        use std::os::unix::{ffi::OsStrExt, fs::symlink};
+
+        let testing_folder = create_custom_testing_folder(vec!["a.txt"]).unwrap();
+        let root_dir = testing_folder.root_dir.path().to_path_buf();
+        let non_utf8_name = OsStr::from_bytes(b"b\xffc.txt");
+        fs::write(root_dir.join(non_utf8_name), "").unwrap();
+        symlink(root_dir.join("missing"), root_dir.join("link")).unwrap();
+
+        let mut elements = get_file_data(&root_dir).unwrap();
+        elements.sort_by(|a, b| a.filename.cmp(&b.filename));
+        assert_eq!(
+            elements
+                .iter()
+                .map(|element| (element.filename.as_str(), element.is_broken_link))
+                .collect::<Vec<_>>(),
+            vec![("a.txt", false), ("b\u{FFFD}c.txt", false), ("link", true)]
+        );
+        assert_eq!(elements[1].os_filename, non_utf8_name);
+
+        // The entry is operated on by its real name
+        let mut table = ExplorerTable::new();
+        table.update_path(root_dir.clone(), Some("b\u{FFFD}c.txt".to_string()));
+        assert_eq!(table.select_directory(), Some(root_dir.join(non_utf8_name)));
+        assert!(get_file_data(&root_dir.join("missing")).is_err());
+
+        // A directory with such a name can be entered and shown
+        let non_utf8_dir = OsStr::from_bytes(b"d\xffr");
+        fs::create_dir(root_dir.join(non_utf8_dir)).unwrap();
+        fs::write(root_dir.join(non_utf8_dir).join("inner.txt"), "").unwrap();
+        table.update_path(root_dir.clone(), Some("d\u{FFFD}r".to_string()));
+        let selected_dir = table.select_directory().unwrap();
+        assert_eq!(selected_dir, root_dir.join(non_utf8_dir));
+        table.update_path(selected_dir, None);
+        let mut terminal = Terminal::new(TestBackend::new(100, 10)).unwrap();
+        let file_config = FileConfig::new(Vec::new(), String::new());
+        terminal
+            .draw(|frame| {
+                let _ = table.draw(frame, frame.size(), &file_config);
+            })
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        let screen = (0..buffer.area.height)
+            .flat_map(|y| (0..buffer.area.width).map(move |x| (x, y)))
+            .map(|(x, y)| buffer.get(x, y).symbol())
+            .collect::<String>();
+        assert!(screen.contains("inner.txt"));
+        assert!(screen.contains("d\u{FFFD}r"));
+    }
 }
@@ -0,0 +1,220 @@
+use std::cmp::Ordering;
+use std::fmt::Display;
+use std::path::Path;
+
+use super::FileData;
+
+/// Property the rows of a table are sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SortKey {
+    /// Natural order, e.g. `file2` before `file10`
+    Name,
+    Size,
+    Modified,
+    Extension,
+    /// Directories before files, each by name
+    Type,
+}
+
+impl SortKey {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "name" => Some(SortKey::Name),
+            "size" => Some(SortKey::Size),
+            "modified" => Some(SortKey::Modified),
+            "extension" => Some(SortKey::Extension),
+            "type" => Some(SortKey::Type),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SortKey::Name => "name",
+            SortKey::Size => "size",
+            SortKey::Modified => "modified",
+            SortKey::Extension => "extension",
+            SortKey::Type => "type",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SortOrder {
+    pub key: SortKey,
+    pub descending: bool,
+}
+
+impl SortOrder {
+    pub fn new(key: SortKey, descending: bool) -> Self {
+        Self { key, descending }
+    }
+
+    pub fn reversed(&self) -> Self {
+        Self {
+            descending: !self.descending,
+            ..*self
+        }
+    }
+
+    fn compare(&self, a: &FileData, b: &FileData) -> Ordering {
+        let ordering = match self.key {
+            SortKey::Name => Ordering::Equal,
+            SortKey::Size => a.size.cmp(&b.size),
+            SortKey::Modified => a.modified.cmp(&b.modified),
+            SortKey::Extension => get_extension(&a.filename).cmp(&get_extension(&b.filename)),
+            SortKey::Type => b.is_dir.cmp(&a.is_dir),
+        }
+        // Rows with the same value are sorted by name
+        .then_with(|| compare_natural(&a.filename, &b.filename));
+        match self.descending {
+            true => ordering.reverse(),
+            false => ordering,
+        }
+    }
+}
+
+impl Default for SortOrder {
+    fn default() -> Self {
+        Self::new(SortKey::Type, false)
+    }
+}
+
+impl Display for SortOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.descending {
+            true => write!(f, "{} (descending)", self.key.name()),
+            false => write!(f, "{}", self.key.name()),
+        }
+    }
+}
+
+fn get_extension(filename: &str) -> String {
+    Path::new(filename)
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+/// Compare the names ignoring case, with the numbers in them compared by value
+pub fn compare_natural(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+    loop {
+        match (a_chars.peek(), b_chars.peek()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a_ch), Some(b_ch)) if a_ch.is_ascii_digit() && b_ch.is_ascii_digit() => {
+                let a_number = take_number(&mut a_chars);
+                let b_number = take_number(&mut b_chars);
+                let ordering = a_number
+                    .len()
+                    .cmp(&b_number.len())
+                    .then_with(|| a_number.cmp(&b_number));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            (Some(a_ch), Some(b_ch)) => {
+                let ordering = a_ch.to_lowercase().cmp(b_ch.to_lowercase());
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+                a_chars.next();
+                b_chars.next();
+            }
+        }
+    }
+}
+
+/// Digits at the start of the iterator, without leading zeros
+fn take_number(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut number = String::new();
+    while let Some(ch) = chars.next_if(char::is_ascii_digit) {
+        if !(number.is_empty() && ch == '0') {
+            number.push(ch);
+        }
+    }
+    number
+}
+
+/// Sort the entries, numbering them in their new order
+pub fn sort_file_data(elements: &mut [FileData], sort_order: &SortOrder) {
+    elements.sort_by(|a, b| sort_order.compare(a, b));
+    for (id, element) in elements.iter_mut().enumerate() {
+        element.id = id;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    fn file_data(filename: &str, size: u64, modified: i64, is_dir: bool) -> FileData {
+        FileData {
+            id: 0,
+            filename: filename.to_string(),
+            os_filename: filename.into(),
+            size,
+            modified: Utc.timestamp_opt(modified, 0).single(),
+            is_dir,
+            is_broken_link: false,
+        }
+    }
+
+    fn sorted_names(elements: &mut [FileData], sort_order: SortOrder) -> Vec<String> {
+        sort_file_data(elements, &sort_order);
+        elements
+            .iter()
+            .map(|element| element.filename.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_compare_natural() {
+        assert_eq!(compare_natural("file2", "file10"), Ordering::Less);
+        assert_eq!(compare_natural("file010", "file9"), Ordering::Greater);
+        assert_eq!(compare_natural("Beta", "alpha"), Ordering::Greater);
+        assert_eq!(compare_natural("a", "ab"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_sort_file_data() {
+        let mut elements = vec![
+            file_data("notes10.md", 30, 3, false),
+            file_data("src", 4096, 1, true),
+            file_data("notes2.md", 10, 2, false),
+            file_data("Cargo.toml", 20, 4, false),
+        ];
+        assert_eq!(
+            sorted_names(&mut elements, SortOrder::default()),
+            vec!["src", "Cargo.toml", "notes2.md", "notes10.md"]
+        );
+        assert_eq!(
+            elements
+                .iter()
+                .map(|element| element.id)
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2, 3]
+        );
+        assert_eq!(
+            sorted_names(&mut elements, SortOrder::new(SortKey::Size, true)),
+            vec!["src", "notes10.md", "Cargo.toml", "notes2.md"]
+        );
+        assert_eq!(
+            sorted_names(&mut elements, SortOrder::new(SortKey::Modified, false)),
+            vec!["src", "notes2.md", "notes10.md", "Cargo.toml"]
+        );
+        assert_eq!(
+            sorted_names(&mut elements, SortOrder::new(SortKey::Extension, false)),
+            vec!["src", "notes2.md", "notes10.md", "Cargo.toml"]
+        );
+        assert_eq!(
+            sorted_names(&mut elements, SortOrder::new(SortKey::Name, true)),
+            vec!["src", "notes10.md", "notes2.md", "Cargo.toml"]
+        );
+    }
+}
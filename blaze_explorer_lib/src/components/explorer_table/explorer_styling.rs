@@ -49,11 +49,14 @@ impl Default for ExplorerStyle {
 
 #[cfg(test)]
 mod tests {
+    use crate::search_pattern::{SearchPattern, SearchSettings};
+
     use super::*;
 
     #[test]
     fn test_explorer_style() {
-        let gs = GlobalStyling::HighlightSearch("abc".into());
+        let gs =
+            GlobalStyling::HighlightSearch(SearchPattern::new("abc", &SearchSettings::default()));
         let vf = VisualFunction::Toggle;
 
         let mut explorer_style = ExplorerStyle::new(gs.clone(), vf.clone());
@@ -1,3 +1,4 @@
 pub mod favourites;
 pub mod add;
 pub mod rename;
+pub mod which_key;
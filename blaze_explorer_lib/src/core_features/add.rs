@@ -121,6 +121,7 @@ impl PluginPopUp for AddPopUp {
 
 #[cfg(test)]
 mod tests {
+    use std::env;
 
     use crate::testing_utils::create_custom_testing_folder;
 
@@ -128,12 +129,14 @@ mod tests {
     #[test]
     fn test_add_popup() {
         let mut app = App::new().unwrap();
+        let starting_path = env::current_dir().unwrap();
         let test_folder = create_custom_testing_folder(vec!["test_folder/"]).unwrap();
         let root_dir = test_folder.root_dir.path().to_path_buf();
         app.update_path(root_dir.clone(), Some("test_folder".to_string()));
         open_add_popup(&mut app);
         let expected_popup: Box<dyn PluginPopUp> = Box::new(AddPopUp::new(root_dir.clone()));
-        let actual_popup = app.popup.unwrap();
-        assert!(expected_popup == actual_popup.clone());
+        let actual_popup = app.popup.clone().unwrap();
+        assert!(expected_popup == actual_popup);
+        app.move_directory(starting_path, None);
     }
 }
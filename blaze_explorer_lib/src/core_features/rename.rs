@@ -24,20 +24,22 @@ use crate::{
 /// * `Option<Action>`
 pub fn open_generic_rename_popup(app: &mut App, copy: bool) -> Option<Action> {
     let mut ctx = app.clone();
-    let dir = ctx.explorer_manager.select_directory().unwrap().clone();
+    let Some(dir) = ctx.explorer_manager.select_directory() else {
+        // Nothing to rename in an empty directory
+        return None;
+    };
 
+    // The names are only shown, the entry itself is renamed by its path
     let initial_name = dir
-        .file_name()
-        .unwrap()
-        .to_str()
-        .unwrap()
+        .file_name()?
+        .to_string_lossy()
         .split('.')
         .next()
         .unwrap()
         .to_string();
     let extension = dir
         .extension()
-        .map_or("".to_string(), |e| format!(".{}", e.to_str().unwrap()));
+        .map_or("".to_string(), |e| format!(".{}", e.to_string_lossy()));
 
     let query = Query::new_with_contents("".to_string(), initial_name.clone(), extension.clone());
     let keymap = get_default_popup_keymap();
@@ -160,4 +162,48 @@ mod tests {
         assert!(attached_popup == expected);
         assert!(action.is_none());
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rename_non_utf8_name() {
+        use std::ffi::OsStr;
+        use std::fs;
+        use std::os::unix::ffi::OsStrExt;
+
+        let test_folder = create_custom_testing_folder(vec![]).unwrap();
+        let root_dir = test_folder.root_dir.path().to_path_buf();
+        let mut app = App::new().unwrap();
+
+        // Nothing is selected in an empty directory
+        app.explorer_manager.update_path(root_dir.clone(), None);
+        assert!(open_rename_popup(&mut app).is_none());
+        assert!(app.popup.is_none());
+
+        let initial_path = root_dir.join(OsStr::from_bytes(b"f\xffle.txt"));
+        fs::write(&initial_path, "").unwrap();
+        app.explorer_manager.show_in_folder(initial_path.clone());
+        open_rename_popup(&mut app);
+        let behaviour = RenameBehaviour {
+            initial_path: initial_path.clone(),
+            initial_name: "f\u{FFFD}le".into(),
+            extension: ".txt".into(),
+            copy: false,
+        };
+        let base = BasePopUp {
+            should_quit: false,
+            query: Query::new_with_contents("".into(), "f\u{FFFD}le".into(), ".txt".into()),
+            keymap: get_default_popup_keymap(),
+        };
+        let expected = Box::new(GenericPopUp {
+            base,
+            behaviour: behaviour.clone(),
+        }) as Box<dyn PluginPopUp>;
+        assert!(app.popup.take().unwrap() == expected);
+
+        app.action_list
+            .push_back(behaviour.confirm_action("renamed.txt".to_string()));
+        let _ = app.handle_new_actions();
+        assert!(!initial_path.exists());
+        assert!(root_dir.join("renamed.txt").exists());
+    }
 }
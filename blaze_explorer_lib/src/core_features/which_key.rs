@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+
+use color_eyre::eyre::Result;
+use itertools::Itertools;
+use ratatui::{
+    Frame,
+    crossterm::event::KeyEvent,
+    layout::Rect,
+    widgets::{Block, Borders, Clear, List},
+};
+
+use crate::{
+    action::{Action, get_action_name},
+    app::App,
+    explorer_helpers::convert_sequence_to_string,
+    mode::Mode,
+    plugin::plugin_popup::PluginPopUp,
+};
+
+/// Name of the action, as used in the keymap file. Plugin actions are named after the plugin
+/// functionality they come from.
+pub fn describe_action(app: &App, action: &Action) -> String {
+    if let Some(name) = get_action_name(action) {
+        return name;
+    }
+    app.plugins
+        .iter()
+        .sorted_by_key(|(plugin_name, _)| plugin_name.to_string())
+        .find_map(|(plugin_name, plugin)| {
+            plugin
+                .get_functionality_map()
+                .into_iter()
+                .find(|(_, functionality)| functionality == action)
+                .map(|(name, _)| format!("{}: {}", plugin_name, name))
+        })
+        .unwrap_or_else(|| format!("{:?}", action))
+}
+
+/// Key sequences completing the pending sequence, paired with the names of the actions they lead
+/// to, sorted by the keys
+pub fn get_sequence_hints(app: &App) -> Vec<(String, String)> {
+    app.input_machine
+        .get_continuations(&app.mode, &app.current_sequence)
+        .into_iter()
+        .map(|(sequence, action)| {
+            (
+                convert_sequence_to_string(sequence),
+                describe_action(app, &action),
+            )
+        })
+        .sorted()
+        .collect()
+}
+
+/// Popup listing the bindings available after the pending key sequence. It is only displayed:
+/// the keys keep going to the mode the sequence was typed in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WhichKeyPopUp {
+    pub should_quit: bool,
+    sequence: String,
+    hints: Vec<(String, String)>,
+}
+
+impl WhichKeyPopUp {
+    pub fn new(sequence: String, hints: Vec<(String, String)>) -> Self {
+        Self {
+            should_quit: false,
+            sequence,
+            hints,
+        }
+    }
+}
+
+impl PluginPopUp for WhichKeyPopUp {
+    fn draw(&mut self, frame: &mut Frame, area: Rect) -> Result<()> {
+        let keys_width = self
+            .hints
+            .iter()
+            .map(|(keys, _)| keys.chars().count())
+            .max()
+            .unwrap_or(0);
+        let lines = self
+            .hints
+            .iter()
+            .map(|(keys, name)| format!("{:<width$}  {}", keys, name, width = keys_width))
+            .collect::<Vec<String>>();
+        let line_width = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        // Placed in the bottom right corner, above the command line
+        let width = (line_width as u16 + 2).max(20).min(area.width);
+        let height = (lines.len() as u16 + 2).min(area.height.saturating_sub(1));
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+        let hints_area = Rect::new(
+            area.x + area.width - width,
+            area.y + area.height.saturating_sub(height + 1),
+            width,
+            height,
+        );
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(self.display_details());
+        let list = List::new(lines).block(block);
+
+        frame.render_widget(Clear, hints_area);
+        frame.render_widget(list, hints_area);
+
+        Ok(())
+    }
+
+    fn push_search_char(&mut self, _ch: char) -> Option<Action> {
+        None
+    }
+
+    fn drop_search_char(&mut self) -> Option<Action> {
+        None
+    }
+
+    fn quit(&mut self) {
+        self.should_quit = true;
+    }
+
+    fn should_quit(&self) -> bool {
+        self.should_quit
+    }
+
+    fn erase_text(&mut self) -> Option<Action> {
+        None
+    }
+
+    fn get_search_query(&self) -> String {
+        String::new()
+    }
+
+    fn context(&self) -> String {
+        self.sequence.clone()
+    }
+
+    fn display_details(&self) -> String {
+        self.sequence.clone()
+    }
+
+    fn get_own_keymap(&self) -> HashMap<(Mode, Vec<KeyEvent>), Action> {
+        HashMap::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{Terminal, backend::TestBackend};
+
+    use crate::{
+        command::Noop,
+        input_machine::input_machine_helpers::convert_str_to_events,
+        plugin::{Plugin, plugin_action::PluginAction},
+    };
+
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct TestPlugin {}
+
+    impl Plugin for TestPlugin {
+        fn get_plugin_bindings(&self) -> HashMap<(Mode, Vec<KeyEvent>), String> {
+            let mut bindings = HashMap::new();
+            bindings.insert(
+                (Mode::Normal, convert_str_to_events("<space>hj")),
+                "Jump".to_string(),
+            );
+            bindings
+        }
+
+        fn get_popup_bindings(&self) -> HashMap<(Mode, Vec<KeyEvent>), String> {
+            HashMap::new()
+        }
+
+        fn get_functionality_map(&self) -> HashMap<String, Action> {
+            let mut functionality_map = HashMap::new();
+            functionality_map.insert(
+                "Jump".to_string(),
+                Action::PluginAct(PluginAction::new(Box::new(Noop::new()))),
+            );
+            functionality_map
+        }
+    }
+
+    #[test]
+    fn test_sequence_hints() {
+        let mut app = App::new().unwrap();
+        app.current_sequence = convert_str_to_events("<space>h");
+        let hints = get_sequence_hints(&app);
+        assert!(hints.contains(&("t".to_string(), "ParseCommand !git status".to_string())));
+        assert!(hints.contains(&("P".to_string(), "PushCurrentBranch".to_string())));
+
+        // Plugin bindings are named after the plugin functionality
+        let plugin = TestPlugin {};
+        app.input_machine
+            .attach_from_hashmap(plugin.get_plugin_keymap());
+        app.plugins.insert("Flash".to_string(), Box::new(plugin));
+        let hints = get_sequence_hints(&app);
+        assert!(hints.contains(&("j".to_string(), "Flash: Jump".to_string())));
+
+        app.current_sequence = convert_str_to_events("<space>");
+        assert!(get_sequence_hints(&app).contains(&("hj".to_string(), "Flash: Jump".to_string())));
+        app.current_sequence = convert_str_to_events("Z");
+        assert!(get_sequence_hints(&app).is_empty());
+    }
+
+    #[test]
+    fn test_draw_in_small_terminal() {
+        let hints = vec![("t".to_string(), "ParseCommand !git status".to_string())];
+        for (width, height) in [(10, 10), (0, 0), (30, 1)] {
+            let mut popup = WhichKeyPopUp::new("<space>h".to_string(), hints.clone());
+            let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+            terminal
+                .draw(|frame| {
+                    let area = frame.size();
+                    popup.draw(frame, area).unwrap()
+                })
+                .unwrap();
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, Sender, channel};
+use std::time::{Duration, Instant};
+
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+
+/// Time without new events after which a burst of changes is reported
+pub const COALESCE_DELAY: Duration = Duration::from_millis(100);
+
+/// Longest time changes are held back while events keep coming, e.g. during a build
+pub const MAX_COALESCE_DELAY: Duration = Duration::from_secs(1);
+
+/// Watches the directories shown in the splits, collecting the ones whose entries changed. Other
+/// paths, e.g. the git index, are watched through their directory, so that they are still watched
+/// after being created or replaced. The watcher is only started once paths are watched, so that
+/// cloning the app stays cheap.
+pub struct DirectoryWatcher {
+    watcher: Option<RecommendedWatcher>,
+    sender: Sender<PathBuf>,
+    receiver: Receiver<PathBuf>,
+    watched: HashSet<PathBuf>,
+    /// Directories registered with the watcher
+    targets: HashSet<PathBuf>,
+    changed: HashSet<PathBuf>,
+    /// Times of the first and the last event of the burst being collected
+    burst: Option<(Instant, Instant)>,
+}
+
+impl Default for DirectoryWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for DirectoryWatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DirectoryWatcher")
+            .field("watched", &self.watched)
+            .field("changed", &self.changed)
+            .finish()
+    }
+}
+
+impl DirectoryWatcher {
+    pub fn new() -> Self {
+        let (sender, receiver) = channel();
+        Self {
+            watcher: None,
+            sender,
+            receiver,
+            watched: HashSet::new(),
+            targets: HashSet::new(),
+            changed: HashSet::new(),
+            burst: None,
+        }
+    }
+
+    fn create_watcher(&self) -> Option<RecommendedWatcher> {
+        let sender = self.sender.clone();
+        notify::recommended_watcher(move |event: notify::Result<Event>| {
+            if let Ok(event) = event {
+                // Changes to an entry change the listing of its directory
+                for path in event.paths {
+                    if let Some(parent) = path.parent() {
+                        let _ = sender.send(parent.to_path_buf());
+                    }
+                    let _ = sender.send(path);
+                }
+            }
+        })
+        .ok()
+    }
+
+    /// Watch exactly the given directories and files, e.g. the directories shown in the splits
+    pub fn watch_paths(&mut self, paths: HashSet<PathBuf>) {
+        if paths == self.watched {
+            return;
+        }
+        if self.watcher.is_none() {
+            self.watcher = self.create_watcher();
+        }
+        let Some(watcher) = &mut self.watcher else {
+            return;
+        };
+        let mut watched = HashSet::new();
+        let mut targets = HashSet::new();
+        for path in paths {
+            let target = match path.is_dir() {
+                true => path.clone(),
+                false => path.parent().map(Path::to_path_buf).unwrap_or(path.clone()),
+            };
+            if targets.contains(&target)
+                || self.targets.contains(&target)
+                || watcher.watch(&target, RecursiveMode::NonRecursive).is_ok()
+            {
+                targets.insert(target);
+                watched.insert(path);
+            }
+        }
+        for target in self.targets.difference(&targets) {
+            let _ = watcher.unwatch(target);
+        }
+        // Paths which failed to be watched are tried again next time
+        self.watched = watched;
+        self.targets = targets;
+    }
+
+    pub fn get_watched_paths(&self) -> &HashSet<PathBuf> {
+        &self.watched
+    }
+
+    /// Watched directories which changed, once no new events came in for `COALESCE_DELAY` (or
+    /// events kept coming for `MAX_COALESCE_DELAY`)
+    pub fn take_changes(&mut self) -> Option<HashSet<PathBuf>> {
+        let now = Instant::now();
+        for path in self.receiver.try_iter() {
+            if self.watched.contains(&path) {
+                self.changed.insert(path);
+                self.burst = Some(match self.burst {
+                    Some((first, _)) => (first, now),
+                    None => (now, now),
+                });
+            }
+        }
+        let (first, last) = self.burst?;
+        if now - last < COALESCE_DELAY && now - first < MAX_COALESCE_DELAY {
+            return None;
+        }
+        self.burst = None;
+        Some(std::mem::take(&mut self.changed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::thread::sleep;
+
+    use tempdir::TempDir;
+
+    use super::*;
+
+    /// Changes reported within a couple of seconds
+    fn wait_for_changes(watcher: &mut DirectoryWatcher) -> Option<HashSet<PathBuf>> {
+        let started = Instant::now();
+        while started.elapsed() < Duration::from_secs(2) {
+            if let Some(changes) = watcher.take_changes() {
+                return Some(changes);
+            }
+            sleep(Duration::from_millis(10));
+        }
+        None
+    }
+
+    #[test]
+    fn test_watch_changes() {
+        let first_dir = TempDir::new("first").unwrap();
+        let second_dir = TempDir::new("second").unwrap();
+        let first_path = first_dir.path().to_path_buf();
+        let second_path = second_dir.path().to_path_buf();
+        let mut watcher = DirectoryWatcher::new();
+        watcher.watch_paths(HashSet::from([first_path.clone(), second_path.clone()]));
+        assert_eq!(watcher.get_watched_paths().len(), 2);
+
+        // A burst of changes is reported at once
+        for i in 0..20 {
+            fs::write(first_path.join(format!("file_{}.txt", i)), "").unwrap();
+        }
+        assert_eq!(
+            wait_for_changes(&mut watcher),
+            Some(HashSet::from([first_path.clone()]))
+        );
+        sleep(COALESCE_DELAY);
+        assert_eq!(watcher.take_changes(), None);
+
+        // Directories which are not shown anymore are not reported
+        watcher.watch_paths(HashSet::from([first_path.clone()]));
+        fs::write(second_path.join("ignored.txt"), "").unwrap();
+        fs::remove_file(first_path.join("file_0.txt")).unwrap();
+        assert_eq!(
+            wait_for_changes(&mut watcher),
+            Some(HashSet::from([first_path.clone()]))
+        );
+
+        // Files are watched before being created and after being replaced
+        let watched_file = second_path.join("index");
+        watcher.watch_paths(HashSet::from([first_path, watched_file.clone()]));
+        for _ in 0..2 {
+            fs::write(second_path.join("index.lock"), "").unwrap();
+            fs::rename(second_path.join("index.lock"), &watched_file).unwrap();
+            assert_eq!(
+                wait_for_changes(&mut watcher),
+                Some(HashSet::from([watched_file.clone()]))
+            );
+        }
+    }
+}
@@ -0,0 +1,590 @@
+// Commands typed in command mode, e.g. `:vsplit ../docs` or `:mkdir build`. Each command is
+// resolved into the actions bound to keys, so that the file operations can be undone.
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use directories::BaseDirs;
+
+use crate::action::{Action, AppAction, ExplorerAction};
+use crate::app::App;
+use crate::command::file_commands::AddDir;
+use crate::components::explorer_table::explorer_sorting::{SortKey, SortOrder};
+use crate::create_plugin_action;
+use crate::custom_action;
+use crate::plugin::plugin_action::PluginAction;
+use crate::search_pattern::{FilterKind, SearchKind};
+
+/// Argument taken by an ex command, named after what it stands for
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExArguments {
+    None,
+    Optional(&'static str),
+    Required(&'static str),
+}
+
+/// Candidates completing the argument of an ex command
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExCompletion {
+    None,
+    Paths,
+    Directories,
+    /// Names of the ex commands
+    Commands,
+    /// Names of the options changed with `:set`
+    Options,
+    /// Functionalities of the plugins, written as the plugin name followed by the functionality
+    PluginFunctionalities,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExCommand {
+    /// Name in Vim notation: the part in brackets can be left out, e.g. `vs[plit]`
+    pub name: &'static str,
+    pub arguments: ExArguments,
+    pub completion: ExCompletion,
+    pub description: &'static str,
+    get_actions: fn(&mut App, Option<&str>) -> Result<Vec<Action>, String>,
+}
+
+impl ExCommand {
+    /// Whether the typed name refers to the command, i.e. it is at least the required part of the
+    /// name and at most the full name
+    pub fn matches(&self, name: &str) -> bool {
+        let required = self.name.split('[').next().unwrap_or_default();
+        name.starts_with(required) && self.full_name().starts_with(name)
+    }
+
+    pub fn full_name(&self) -> String {
+        self.name.replace(['[', ']'], "")
+    }
+
+    /// Name of the command followed by its argument, e.g. `vs[plit] [path]`
+    pub fn usage(&self) -> String {
+        match self.arguments {
+            ExArguments::None => self.name.to_string(),
+            ExArguments::Optional(argument) => format!("{} [{}]", self.name, argument),
+            ExArguments::Required(argument) => format!("{} <{}>", self.name, argument),
+        }
+    }
+}
+
+/// Commands available in command mode. Typed names are matched in order, so an abbreviation
+/// shared by several commands goes to the first one.
+pub fn get_ex_commands() -> Vec<ExCommand> {
+    vec![
+        ExCommand {
+            name: "q[uit]",
+            arguments: ExArguments::None,
+            completion: ExCompletion::None,
+            description: "Close the current split, or the app if it is the last one",
+            get_actions: |_, _| Ok(vec![Action::ExplorerAct(ExplorerAction::DeleteSplit)]),
+        },
+        ExCommand {
+            name: "qa[ll]",
+            arguments: ExArguments::None,
+            completion: ExCompletion::None,
+            description: "Close the app",
+            get_actions: |_, _| Ok(vec![Action::AppAct(AppAction::Quit)]),
+        },
+        ExCommand {
+            name: "w[rite]",
+            arguments: ExArguments::None,
+            completion: ExCompletion::None,
+            description: "Save the config, e.g. the favourites",
+            get_actions: |_, _| Ok(vec![custom_action!(write_config)]),
+        },
+        ExCommand {
+            name: "wq",
+            arguments: ExArguments::None,
+            completion: ExCompletion::None,
+            description: "Save the config and close the current split",
+            get_actions: |_, _| {
+                Ok(vec![
+                    custom_action!(write_config),
+                    Action::ExplorerAct(ExplorerAction::DeleteSplit),
+                ])
+            },
+        },
+        ExCommand {
+            name: "cd",
+            arguments: ExArguments::Optional("path"),
+            completion: ExCompletion::Directories,
+            description: "Go to the directory, or to the home directory",
+            get_actions: |app, path| {
+                let path = resolve_directory(app, path.unwrap_or("~"))?;
+                Ok(vec![Action::ExplorerAct(ExplorerAction::ChangeDirectory(
+                    path,
+                ))])
+            },
+        },
+        ExCommand {
+            name: "e[dit]",
+            arguments: ExArguments::Required("path"),
+            completion: ExCompletion::Paths,
+            description: "Open the file with the default program, or go to the directory",
+            get_actions: |app, path| {
+                let path = resolve_path(app, path.unwrap_or_default());
+                match path.is_dir() {
+                    true => Ok(vec![Action::ExplorerAct(ExplorerAction::ChangeDirectory(
+                        path,
+                    ))]),
+                    false if path.exists() => Ok(vec![Action::AppAct(AppAction::OpenFile(path))]),
+                    false => Err(format!("No such file: {}", path.display())),
+                }
+            },
+        },
+        ExCommand {
+            name: "mkd[ir]",
+            arguments: ExArguments::Required("name"),
+            completion: ExCompletion::Paths,
+            description: "Create a directory in the current directory",
+            get_actions: |app, name| {
+                let name = name.unwrap_or_default().trim_end_matches(['/', '\\']);
+                let current_path = app.explorer_manager.get_current_path();
+                Ok(vec![create_plugin_action!(
+                    AddDir,
+                    current_path,
+                    format!("{}/", name)
+                )])
+            },
+        },
+        ExCommand {
+            name: "touch",
+            arguments: ExArguments::Required("name"),
+            completion: ExCompletion::Paths,
+            description: "Create a file in the current directory",
+            get_actions: |app, name| {
+                let name = name.unwrap_or_default();
+                if name.ends_with(['/', '\\']) {
+                    return Err(format!("Not a file name: {}", name));
+                }
+                let current_path = app.explorer_manager.get_current_path();
+                Ok(vec![create_plugin_action!(
+                    AddDir,
+                    current_path,
+                    name.to_string()
+                )])
+            },
+        },
+        ExCommand {
+            name: "sp[lit]",
+            arguments: ExArguments::Optional("path"),
+            completion: ExCompletion::Directories,
+            description: "Split the current window horizontally, showing the directory",
+            get_actions: |app, path| split_actions(app, path, ExplorerAction::SplitHorizontally),
+        },
+        ExCommand {
+            name: "vs[plit]",
+            arguments: ExArguments::Optional("path"),
+            completion: ExCompletion::Directories,
+            description: "Split the current window vertically, showing the directory",
+            get_actions: |app, path| split_actions(app, path, ExplorerAction::SplitVertically),
+        },
+        ExCommand {
+            name: "se[t]",
+            arguments: ExArguments::Optional("option[=value]"),
+            completion: ExCompletion::Options,
+            description: "Set an option, show its value or list all the options",
+            get_actions: |app, option| match option {
+                None => Ok(vec![Action::AppAct(AppAction::DisplayMessage(
+                    get_options()
+                        .iter()
+                        .map(|option| format!("{}={}", option.name, (option.get_value)(app)))
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                ))]),
+                Some(option) => match option.split_once('=') {
+                    Some((name, value)) => Ok(vec![Action::AppAct(AppAction::SetOption(
+                        name.trim().to_string(),
+                        value.trim().to_string(),
+                    ))]),
+                    None => {
+                        let option = find_option(option)?;
+                        Ok(vec![Action::AppAct(AppAction::DisplayMessage(format!(
+                            "{}={}",
+                            option.name,
+                            (option.get_value)(app)
+                        )))])
+                    }
+                },
+            },
+        },
+        ExCommand {
+            name: "sor[t]",
+            arguments: ExArguments::Optional("[!] [key]"),
+            completion: ExCompletion::None,
+            description: "Sort by name, size, modified, extension or type, descending with !",
+            get_actions: |app, argument| {
+                let Some(argument) = argument else {
+                    return Ok(vec![Action::AppAct(AppAction::DisplayMessage(format!(
+                        "sort={}",
+                        app.explorer_manager.get_sort_order()
+                    )))]);
+                };
+                let (descending, key) = match argument.strip_prefix('!') {
+                    Some(key) => (true, key.trim()),
+                    None => (false, argument),
+                };
+                let key = match key {
+                    "" => app.explorer_manager.get_sort_order().key,
+                    key => SortKey::from_name(key)
+                        .ok_or_else(|| format!("Invalid sort key: {}", key))?,
+                };
+                Ok(vec![Action::ExplorerAct(ExplorerAction::SortBy(
+                    SortOrder::new(key, descending),
+                ))])
+            },
+        },
+        ExCommand {
+            name: "fil[ter]",
+            arguments: ExArguments::Optional("query"),
+            completion: ExCompletion::None,
+            description: "Show only the items matching the query, or all of them again",
+            get_actions: |_app, query| match query {
+                Some(query) => Ok(vec![Action::ExplorerAct(ExplorerAction::UpdateFilter(
+                    query.to_string(),
+                ))]),
+                None => Ok(vec![Action::ExplorerAct(ExplorerAction::ClearFilter)]),
+            },
+        },
+        ExCommand {
+            name: "book[mark]",
+            arguments: ExArguments::None,
+            completion: ExCompletion::None,
+            description: "Add the current directory to the favourites, or remove it",
+            get_actions: |_, _| {
+                Ok(vec![Action::ExplorerAct(
+                    ExplorerAction::ToggleToFavourites,
+                )])
+            },
+        },
+        ExCommand {
+            name: "pl[ugin]",
+            arguments: ExArguments::Required("plugin functionality"),
+            completion: ExCompletion::PluginFunctionalities,
+            description: "Run a functionality of a plugin",
+            get_actions: |app, argument| {
+                let (plugin_name, functionality) = argument
+                    .unwrap_or_default()
+                    .split_once(' ')
+                    .ok_or("Expected the plugin name followed by the functionality")?;
+                let plugin = app
+                    .plugins
+                    .get(plugin_name)
+                    .ok_or_else(|| format!("No plugin named {}", plugin_name))?;
+                let action = plugin
+                    .get_functionality_map()
+                    .remove(functionality.trim())
+                    .ok_or_else(|| {
+                        format!("{} has no functionality {}", plugin_name, functionality)
+                    })?;
+                Ok(vec![action])
+            },
+        },
+        ExCommand {
+            name: "h[elp]",
+            arguments: ExArguments::Optional("command"),
+            completion: ExCompletion::Commands,
+            description: "List the commands, or describe one",
+            get_actions: |_, name| {
+                let commands = match name {
+                    Some(name) => vec![find_ex_command(name)?],
+                    None => get_ex_commands(),
+                };
+                let usage_width = commands
+                    .iter()
+                    .map(|command| command.usage().chars().count())
+                    .max()
+                    .unwrap_or(0);
+                Ok(vec![Action::AppAct(AppAction::DisplayMessage(
+                    commands
+                        .iter()
+                        .map(|command| {
+                            format!(
+                                ":{:<width$}  {}",
+                                command.usage(),
+                                command.description,
+                                width = usage_width
+                            )
+                        })
+                        .collect::<Vec<String>>()
+                        .join("\n"),
+                ))])
+            },
+        },
+    ]
+}
+
+pub fn find_ex_command(name: &str) -> Result<ExCommand, String> {
+    get_ex_commands()
+        .into_iter()
+        .find(|command| command.matches(name))
+        .ok_or_else(|| format!("Not a supported command: {}", name))
+}
+
+/// Resolve the typed command into the actions to execute. The name of the command is made of
+/// letters, so that it does not need to be separated from the argument, e.g. `:cd..`.
+pub fn parse_ex_command(app: &mut App, command: &str) -> Result<Vec<Action>, String> {
+    let command = command.trim();
+    let name_end = command
+        .find(|ch: char| !ch.is_ascii_alphabetic())
+        .unwrap_or(command.len());
+    let (name, argument) = command.split_at(name_end);
+    if name.is_empty() {
+        return Err(format!("Not a supported command: {}", command));
+    }
+    let ex_command = find_ex_command(name)?;
+    let argument = Some(argument.trim()).filter(|argument| !argument.is_empty());
+    match (&ex_command.arguments, argument) {
+        (ExArguments::None, Some(argument)) => Err(format!(
+            "Trailing characters after :{}: {}",
+            ex_command.full_name(),
+            argument
+        )),
+        (ExArguments::Required(_), None) => {
+            Err(format!("Argument required: :{}", ex_command.usage()))
+        }
+        _ => (ex_command.get_actions)(app, argument),
+    }
+}
+
+/// Option changed with `:set`
+pub struct ExOption {
+    pub name: &'static str,
+    get_value: fn(&App) -> String,
+    set_value: fn(&mut App, &str) -> Result<(), String>,
+}
+
+pub fn get_options() -> Vec<ExOption> {
+    vec![
+        ExOption {
+            name: "timeout",
+            get_value: |app| match app.input_machine.get_timeout() {
+                Some(timeout) => timeout.as_millis().to_string(),
+                None => "0".to_string(),
+            },
+            set_value: |app, value| {
+                let timeout = value
+                    .parse::<u64>()
+                    .map_err(|_| format!("Invalid timeout: {}", value))?;
+                app.input_machine.set_timeout(
+                    Some(timeout)
+                        .filter(|ms| *ms > 0)
+                        .map(Duration::from_millis),
+                );
+                Ok(())
+            },
+        },
+        ExOption {
+            name: "search",
+            get_value: |app| app.search_settings.kind.name().to_string(),
+            set_value: |app, value| {
+                app.search_settings.kind = SearchKind::from_name(value).ok_or_else(|| {
+                    format!("Invalid search kind: {} (literal, regex or glob)", value)
+                })?;
+                Ok(())
+            },
+        },
+        ExOption {
+            name: "filter",
+            get_value: |app| app.search_settings.filter_kind.name().to_string(),
+            set_value: |app, value| {
+                app.search_settings.filter_kind =
+                    FilterKind::from_name(value).ok_or_else(|| {
+                        format!("Invalid filter kind: {} (substring or fuzzy)", value)
+                    })?;
+                Ok(())
+            },
+        },
+        ExOption {
+            name: "sortperdir",
+            get_value: |app| app.explorer_manager.remember_sort_orders.to_string(),
+            set_value: |app, value| {
+                app.explorer_manager.remember_sort_orders = value
+                    .parse::<bool>()
+                    .map_err(|_| format!("Invalid sortperdir: {}", value))?;
+                Ok(())
+            },
+        },
+        ExOption {
+            name: "smartcase",
+            get_value: |app| app.search_settings.smart_case.to_string(),
+            set_value: |app, value| {
+                app.search_settings.smart_case = value
+                    .parse::<bool>()
+                    .map_err(|_| format!("Invalid smartcase: {}", value))?;
+                Ok(())
+            },
+        },
+    ]
+}
+
+fn find_option(name: &str) -> Result<ExOption, String> {
+    get_options()
+        .into_iter()
+        .find(|option| option.name == name)
+        .ok_or_else(|| format!("Unknown option: {}", name))
+}
+
+pub fn set_option(app: &mut App, name: &str, value: &str) -> Result<(), String> {
+    (find_option(name)?.set_value)(app, value)
+}
+
+fn write_config(app: &mut App) -> Option<Action> {
+    app.save_config()
+        .map(|error| Action::AppAct(AppAction::DisplayMessage(error)))
+}
+
+fn split_actions(
+    app: &mut App,
+    path: Option<&str>,
+    split: ExplorerAction,
+) -> Result<Vec<Action>, String> {
+    let mut actions = Vec::new();
+    if let Some(path) = path {
+        // Checked before splitting, so that no split is left behind on error
+        let path = resolve_directory(app, path)?;
+        actions.push(Action::ExplorerAct(split));
+        actions.push(Action::ExplorerAct(ExplorerAction::ChangeDirectory(path)));
+    } else {
+        actions.push(Action::ExplorerAct(split));
+    }
+    Ok(actions)
+}
+
+/// Path typed in a command, relative to the current directory unless it is absolute or starts
+/// with `~`
+pub fn resolve_path(app: &mut App, path: &str) -> PathBuf {
+    let home_dir = BaseDirs::new().map(|dirs| dirs.home_dir().to_path_buf());
+    let path = match (path.strip_prefix('~'), home_dir) {
+        (Some(rest), Some(home_dir)) => home_dir.join(rest.trim_start_matches(['/', '\\'])),
+        _ => app.explorer_manager.get_current_path().join(path),
+    };
+    // Resolve `..` and symlinks where possible
+    fs::canonicalize(&path).unwrap_or(path)
+}
+
+fn resolve_directory(app: &mut App, path: &str) -> Result<PathBuf, String> {
+    let path = resolve_path(app, path);
+    match path.is_dir() {
+        true => Ok(path),
+        false => Err(format!("Not a directory: {}", path.display())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use crate::action::CommandAction;
+    use crate::testing_utils::create_custom_testing_folder;
+
+    use super::*;
+
+    #[test]
+    fn test_find_ex_command() {
+        assert_eq!(find_ex_command("q").unwrap().name, "q[uit]");
+        assert_eq!(find_ex_command("quit").unwrap().name, "q[uit]");
+        assert_eq!(find_ex_command("qa").unwrap().name, "qa[ll]");
+        assert_eq!(find_ex_command("vs").unwrap().name, "vs[plit]");
+        assert_eq!(find_ex_command("vsplit").unwrap().name, "vs[plit]");
+        assert!(find_ex_command("v").is_err());
+        assert!(find_ex_command("quitt").is_err());
+        assert_eq!(find_ex_command("mkd").unwrap().usage(), "mkd[ir] <name>");
+    }
+
+    #[test]
+    fn test_parse_ex_command() {
+        let mut app = App::new().unwrap();
+        let testing_folder = create_custom_testing_folder(vec!["a.txt", "docs/"]).unwrap();
+        let starting_path = env::current_dir().unwrap();
+        let root_dir = fs::canonicalize(testing_folder.root_dir.path()).unwrap();
+        app.move_directory(root_dir.join("docs"), None);
+
+        assert_eq!(
+            parse_ex_command(&mut app, "cd.."),
+            Ok(vec![Action::ExplorerAct(ExplorerAction::ChangeDirectory(
+                root_dir.clone()
+            ))])
+        );
+        assert_eq!(
+            parse_ex_command(&mut app, "vs ../"),
+            Ok(vec![
+                Action::ExplorerAct(ExplorerAction::SplitVertically),
+                Action::ExplorerAct(ExplorerAction::ChangeDirectory(root_dir.clone()))
+            ])
+        );
+        assert_eq!(
+            parse_ex_command(&mut app, "e ../a.txt"),
+            Ok(vec![Action::AppAct(AppAction::OpenFile(
+                root_dir.join("a.txt")
+            ))])
+        );
+        assert_eq!(
+            parse_ex_command(&mut app, "set timeout=300"),
+            Ok(vec![Action::AppAct(AppAction::SetOption(
+                "timeout".to_string(),
+                "300".to_string()
+            ))])
+        );
+        assert_eq!(
+            parse_ex_command(&mut app, "sort! size"),
+            Ok(vec![Action::ExplorerAct(ExplorerAction::SortBy(
+                SortOrder::new(SortKey::Size, true)
+            ))])
+        );
+        assert_eq!(
+            parse_ex_command(&mut app, "sor!"),
+            Ok(vec![Action::ExplorerAct(ExplorerAction::SortBy(
+                SortOrder::new(SortKey::Type, true)
+            ))])
+        );
+        assert!(parse_ex_command(&mut app, "sort colour").is_err());
+        assert!(parse_ex_command(&mut app, "cd ../a.txt").is_err());
+        assert!(parse_ex_command(&mut app, "mkdir").is_err());
+        assert!(parse_ex_command(&mut app, "q now").is_err());
+        assert!(parse_ex_command(&mut app, "set nope").is_err());
+        assert!(parse_ex_command(&mut app, "").is_err());
+        app.move_directory(starting_path, None);
+    }
+
+    #[test]
+    fn test_execute_ex_command() {
+        let mut app = App::new().unwrap();
+        let testing_folder = create_custom_testing_folder(vec!["a.txt"]).unwrap();
+        let starting_path = env::current_dir().unwrap();
+        let root_dir = testing_folder.root_dir.path().to_path_buf();
+        app.move_directory(root_dir.clone(), None);
+
+        app.execute_command("mkdir build".to_string());
+        app.execute_command("touch notes.md".to_string());
+        let _ = app.handle_new_actions();
+        assert!(root_dir.join("build").is_dir());
+        assert!(root_dir.join("notes.md").is_file());
+
+        // File commands are undone as if they were bound to keys
+        app.action_list
+            .push_back(Action::CommandAct(CommandAction::Undo));
+        let _ = app.handle_new_actions();
+        assert!(!root_dir.join("notes.md").exists());
+        assert!(root_dir.join("build").is_dir());
+
+        app.execute_command("set timeout=0".to_string());
+        let _ = app.handle_new_actions();
+        assert_eq!(app.input_machine.get_timeout(), None);
+        app.execute_command("set timeout=250".to_string());
+        let _ = app.handle_new_actions();
+        assert_eq!(
+            app.input_machine.get_timeout(),
+            Some(Duration::from_millis(250))
+        );
+        app.execute_command("set search=glob".to_string());
+        app.execute_command("set smartcase=false".to_string());
+        let _ = app.handle_new_actions();
+        assert_eq!(app.search_settings.kind, SearchKind::Glob);
+        assert!(!app.search_settings.smart_case);
+        assert!(set_option(&mut app, "search", "fuzzy").is_err());
+        app.move_directory(starting_path, None);
+    }
+}
@@ -1,35 +1,42 @@
 use ratatui::{
-    crossterm::event::{KeyCode, KeyEvent, KeyModifiers},
+    crossterm::event::KeyEvent,
     style::Style,
     text::{Line, Span},
 };
 
+use crate::key_notation::keys_to_string;
+use crate::search_pattern::SearchPattern;
+
 pub fn convert_sequence_to_string(sequence: Vec<KeyEvent>) -> String {
-    sequence
-        .iter()
-        .map(|event| match (event.code, event.modifiers) {
-            (KeyCode::Char(' '), _) => "<space>".to_string(),
-            (KeyCode::Enter, _) => "<cr>".to_string(),
-            (KeyCode::Char(c), KeyModifiers::NONE) => c.to_string(),
-            (KeyCode::Char(c), KeyModifiers::SHIFT) => format!("<S-{}>", c),
-            (KeyCode::Char(c), KeyModifiers::CONTROL) => format!("<C-{}>", c),
-            (_, _) => "".to_string(),
-        })
-        .collect::<Vec<String>>()
-        .join("")
+    keys_to_string(&sequence)
 }
 
-pub fn highlight_search_result(line_text: String, query: &str, highlighted_style: Style) -> Line {
-    if line_text.contains(query) {
-        let splits = line_text.split(&query);
-        let chunks = splits.into_iter().map(|c| Span::from(c.to_owned()));
-        let pattern = Span::styled(query, highlighted_style);
-        itertools::intersperse(chunks, pattern)
-            .collect::<Vec<Span>>()
-            .into()
-    } else {
-        Line::from(line_text)
+pub fn highlight_search_result(
+    line_text: String,
+    pattern: &SearchPattern,
+    highlighted_style: Style,
+) -> Line<'static> {
+    let mut spans = Vec::new();
+    let mut end_of_previous = 0;
+    for span in pattern.find_spans(&line_text) {
+        if span.start > end_of_previous {
+            spans.push(Span::from(
+                line_text[end_of_previous..span.start].to_string(),
+            ));
+        }
+        spans.push(Span::styled(
+            line_text[span.clone()].to_string(),
+            highlighted_style,
+        ));
+        end_of_previous = span.end;
     }
+    if spans.is_empty() {
+        return Line::from(line_text);
+    }
+    if end_of_previous < line_text.len() {
+        spans.push(Span::from(line_text[end_of_previous..].to_string()));
+    }
+    Line::from(spans)
 }
 
 pub fn jump_highlight(
@@ -75,6 +82,10 @@ pub use delegate_to_focused;
 
 #[cfg(test)]
 mod tests {
+    use ratatui::crossterm::event::{KeyCode, KeyModifiers};
+
+    use crate::search_pattern::SearchSettings;
+
     use super::*;
 
     #[test]
@@ -98,12 +109,25 @@ mod tests {
         let line_text = String::from("Hello world");
         let query = "worl";
         let highlighted_style = Style::new().fg(ratatui::style::Color::Green);
-        let line = highlight_search_result(line_text, query, highlighted_style);
+        let pattern = SearchPattern::new(query, &SearchSettings::default());
+        let line = highlight_search_result(line_text, &pattern, highlighted_style);
         let beginning = Span::from("Hello ");
         let query_span = Span::styled(query, highlighted_style);
         let ending = Span::from("d");
         let expected_line = Line::from(vec![beginning, query_span, ending]);
         assert_eq!(line, expected_line);
+
+        // Every match is highlighted, with the case of the filename
+        let pattern = SearchPattern::new("re:l+", &SearchSettings::default());
+        let line = highlight_search_result("HeLlo world".to_string(), &pattern, highlighted_style);
+        let expected_line = Line::from(vec![
+            Span::from("He"),
+            Span::styled("Ll", highlighted_style),
+            Span::from("o wor"),
+            Span::styled("l", highlighted_style),
+            Span::from("d"),
+        ]);
+        assert_eq!(line, expected_line);
     }
 
     #[test]
@@ -114,7 +138,7 @@ mod tests {
             KeyEvent::new(KeyCode::Char('b'), KeyModifiers::CONTROL),
             KeyEvent::new(KeyCode::Char('c'), KeyModifiers::SHIFT),
         ];
-        let expected_string = "<cr>a<C-b><S-c>".to_string();
+        let expected_string = "<cr>a<C-b>C".to_string();
         let actual_string = convert_sequence_to_string(sequence);
         assert_eq!(actual_string, expected_string);
     }
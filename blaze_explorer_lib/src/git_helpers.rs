@@ -1,8 +1,74 @@
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::SystemTime;
 
-use git2::{Repository, RepositoryOpenFlags, Status};
+use git2::{Repository, RepositoryOpenFlags, Status, StatusOptions};
 use ratatui::style::{Color, Style};
 
+/// Git status of the entries of a directory, by filename
+pub type GitMap = HashMap<String, Status>;
+
+/// Statuses computed in the directories of a repository, valid as long as its index stays the
+/// same and the worktree is not reported to change
+#[derive(Debug, Default)]
+struct RepoStatuses {
+    /// Modification time and size of the index when the statuses were computed
+    index_state: Option<(SystemTime, u64)>,
+    directories: HashMap<PathBuf, GitMap>,
+}
+
+/// Statuses computed by the explorers, by the path of the repository. Clones share the same
+/// statuses.
+#[derive(Debug, Default, Clone)]
+pub struct GitStatusCache {
+    repos: Arc<Mutex<HashMap<PathBuf, RepoStatuses>>>,
+}
+
+impl PartialEq for GitStatusCache {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.repos, &other.repos)
+    }
+}
+
+impl GitStatusCache {
+    /// The statuses may be left half updated by a panic while locked, so they are dropped then
+    fn lock(&self) -> MutexGuard<'_, HashMap<PathBuf, RepoStatuses>> {
+        self.repos.lock().unwrap_or_else(|poisoned| {
+            self.repos.clear_poison();
+            let mut repos = poisoned.into_inner();
+            repos.clear();
+            repos
+        })
+    }
+
+    /// Git status of the entries of the directory, taken from the cache if the index did not
+    /// change since it was computed
+    pub fn get_statuses(&self, repo: &Repository, directory: &Path) -> Option<GitMap> {
+        let index_state = get_index_state(repo);
+        let mut repos = self.lock();
+        let repo_statuses = repos.entry(repo.path().to_path_buf()).or_default();
+        if repo_statuses.index_state != index_state {
+            repo_statuses.index_state = index_state;
+            repo_statuses.directories.clear();
+        }
+        if let Some(statuses) = repo_statuses.directories.get(directory) {
+            return Some(statuses.clone());
+        }
+        let statuses = compute_git_statuses(repo, directory)?;
+        repo_statuses
+            .directories
+            .insert(directory.to_path_buf(), statuses.clone());
+        Some(statuses)
+    }
+
+    /// Forget the cached statuses, e.g. after files changed in the worktree
+    pub fn invalidate(&self) {
+        self.lock().clear();
+    }
+}
+
 pub fn get_repo(path: PathBuf) -> Option<Repository> {
     Repository::open_ext(
         path,
@@ -12,13 +78,294 @@ pub fn get_repo(path: PathBuf) -> Option<Repository> {
     .ok()
 }
 
+/// Status shown for an entry. Directories show the strongest status of the files inside them,
+/// in the order of the variants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GitState {
+    Ignored,
+    Untracked,
+    /// New file added to the index
+    Added,
+    /// Changes added to the index
+    Staged,
+    /// Changes not added to the index
+    Modified,
+    Conflicted,
+}
+
+impl GitState {
+    /// Strongest state among the statuses, None for files without changes
+    pub fn from_status(status: Status) -> Option<Self> {
+        let state = if status.is_conflicted() {
+            GitState::Conflicted
+        } else if status.intersects(
+            Status::WT_MODIFIED | Status::WT_DELETED | Status::WT_RENAMED | Status::WT_TYPECHANGE,
+        ) {
+            GitState::Modified
+        } else if status.intersects(
+            Status::INDEX_MODIFIED
+                | Status::INDEX_DELETED
+                | Status::INDEX_RENAMED
+                | Status::INDEX_TYPECHANGE,
+        ) {
+            GitState::Staged
+        } else if status.is_index_new() {
+            GitState::Added
+        } else if status.is_wt_new() {
+            GitState::Untracked
+        } else if status.is_ignored() {
+            GitState::Ignored
+        } else {
+            return None;
+        };
+        Some(state)
+    }
+
+    /// Character shown in the status column
+    pub fn glyph(&self) -> &'static str {
+        match self {
+            GitState::Ignored => "!",
+            GitState::Untracked => "?",
+            GitState::Added => "A",
+            GitState::Staged | GitState::Modified => "M",
+            GitState::Conflicted => "U",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            GitState::Ignored => Color::Rgb(128, 128, 128),
+            GitState::Untracked => Color::Rgb(152, 251, 152),
+            GitState::Added => Color::Rgb(0, 100, 0),
+            GitState::Staged => Color::Rgb(255, 140, 0),
+            GitState::Modified => Color::Rgb(255, 215, 0),
+            GitState::Conflicted => Color::Rgb(255, 20, 147),
+        }
+    }
+}
+
 pub fn assign_git_styling(style: Style, status: Status) -> Style {
-    match status {
-        Status::WT_MODIFIED => style.fg(Color::Rgb(255, 215, 0)),
-        Status::INDEX_MODIFIED => style.fg(Color::Rgb(255, 140, 0)),
-        Status::IGNORED => style.fg(Color::Rgb(128, 128, 128)),
-        Status::WT_NEW => style.fg(Color::Rgb(152, 251, 152)),
-        Status::INDEX_NEW => style.fg(Color::Rgb(0, 100, 0)),
-        _ => style.fg(Color::Rgb(255, 20, 147)),
+    match GitState::from_status(status) {
+        Some(state) => style.fg(state.color()),
+        None => style,
+    }
+}
+
+fn get_index_state(repo: &Repository) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(repo.path().join("index")).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Path relative to the worktree, with `/` separators
+pub fn get_relative_path(repo: &Repository, directory: &Path) -> Option<String> {
+    let workdir = repo.workdir()?;
+    let relative = match directory.strip_prefix(workdir) {
+        Ok(relative) => relative.to_path_buf(),
+        // Either path may go through a symbolic link
+        Err(_) => fs::canonicalize(directory)
+            .ok()?
+            .strip_prefix(fs::canonicalize(workdir).ok()?)
+            .ok()?
+            .to_path_buf(),
+    };
+    Some(
+        relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+    )
+}
+
+/// Status of the entries of the directory, with the statuses of the files inside a subdirectory
+/// combined into the status of the subdirectory. Only the entries under the directory are looked
+/// at.
+fn compute_git_statuses(repo: &Repository, directory: &Path) -> Option<GitMap> {
+    let relative_path = get_relative_path(repo, directory)?;
+    let mut options = StatusOptions::new();
+    options.include_ignored(true).include_untracked(true);
+    if !relative_path.is_empty() {
+        // The path is matched as it is, not as a pattern
+        options
+            .pathspec(&relative_path)
+            .disable_pathspec_match(true);
+    }
+    // Give up on error
+    let statuses = repo.statuses(Some(&mut options)).ok()?;
+    let prefix = match relative_path.is_empty() {
+        true => String::new(),
+        false => format!("{}/", relative_path),
+    };
+    let mut map = GitMap::new();
+    for status_entry in statuses.iter() {
+        let entry_path = String::from_utf8_lossy(status_entry.path_bytes()).to_string();
+        let Some(inner_path) = entry_path.strip_prefix(&prefix) else {
+            continue;
+        };
+        // Directories which are untracked or ignored as a whole end with a separator
+        let inner_path = inner_path.trim_end_matches('/');
+        let (filename, status) = match inner_path.split_once('/') {
+            // Ignored files do not make the directories containing them ignored
+            Some((filename, _)) => (filename, status_entry.status() - Status::IGNORED),
+            None => (inner_path, status_entry.status()),
+        };
+        if filename.is_empty() || status.is_empty() {
+            continue;
+        }
+        *map.entry(filename.to_string()).or_insert(Status::empty()) |= status;
+    }
+    //ensure .git is always untracked
+    map.insert(".git".to_string(), Status::IGNORED);
+    Some(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use git2::IndexAddOption;
+    use tempdir::TempDir;
+
+    use super::*;
+
+    fn stage(repo: &Repository, paths: &[&str]) {
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(paths.iter(), IndexAddOption::DEFAULT, None)
+            .unwrap();
+        index.write().unwrap();
+    }
+
+    #[test]
+    fn test_get_git_statuses() {
+        let root_dir = TempDir::new("git_statuses").unwrap();
+        let root_path = root_dir.path().to_path_buf();
+        let repo = Repository::init(&root_path).unwrap();
+        fs::create_dir_all(root_path.join("lib/deep")).unwrap();
+        fs::create_dir(root_path.join("docs")).unwrap();
+        for file in [
+            ".gitignore",
+            "lib/a.rs",
+            "lib/deep/b.rs",
+            "lib/c.o",
+            "docs/notes.md",
+            "d.o",
+        ] {
+            fs::write(root_path.join(file), "").unwrap();
+        }
+        fs::write(root_path.join(".gitignore"), "*.o\n").unwrap();
+        stage(&repo, &["lib/a.rs"]);
+
+        let git_cache = GitStatusCache::default();
+        let root_statuses = git_cache.get_statuses(&repo, &root_path).unwrap();
+        assert_eq!(
+            root_statuses.get("lib"),
+            Some(&(Status::INDEX_NEW | Status::WT_NEW))
+        );
+        assert_eq!(root_statuses.get("docs"), Some(&Status::WT_NEW));
+        assert_eq!(root_statuses.get("d.o"), Some(&Status::IGNORED));
+        assert_eq!(root_statuses.get(".gitignore"), Some(&Status::WT_NEW));
+
+        let lib_statuses = git_cache
+            .get_statuses(&repo, &root_path.join("lib"))
+            .unwrap();
+        assert_eq!(lib_statuses.get("a.rs"), Some(&Status::INDEX_NEW));
+        assert_eq!(lib_statuses.get("deep"), Some(&Status::WT_NEW));
+        assert_eq!(lib_statuses.get("c.o"), Some(&Status::IGNORED));
+        assert_eq!(lib_statuses.get("d.o"), None);
+
+        // The statuses are computed again once the index changes
+        fs::write(root_path.join("e.txt"), "").unwrap();
+        assert_eq!(
+            git_cache
+                .get_statuses(&repo, &root_path)
+                .unwrap()
+                .get("e.txt"),
+            None
+        );
+        stage(&repo, &["e.txt"]);
+        assert_eq!(
+            git_cache
+                .get_statuses(&repo, &root_path)
+                .unwrap()
+                .get("e.txt"),
+            Some(&Status::INDEX_NEW)
+        );
+        fs::write(root_path.join("f.txt"), "").unwrap();
+        git_cache.invalidate();
+        assert_eq!(
+            git_cache
+                .get_statuses(&repo, &root_path)
+                .unwrap()
+                .get("f.txt"),
+            Some(&Status::WT_NEW)
+        );
+    }
+
+    #[test]
+    fn test_get_git_statuses_with_pattern_characters() {
+        let root_dir = TempDir::new("git_statuses").unwrap();
+        let root_path = root_dir.path().to_path_buf();
+        let repo = Repository::init(&root_path).unwrap();
+        for directory in ["a*[1]", "ab1"] {
+            fs::create_dir(root_path.join(directory)).unwrap();
+            fs::write(root_path.join(directory).join("new.txt"), "").unwrap();
+        }
+        fs::write(root_path.join("a*[1]").join("staged.txt"), "").unwrap();
+        stage(&repo, &["a*[1]/staged.txt"]);
+
+        let git_cache = GitStatusCache::default();
+        let statuses = git_cache
+            .get_statuses(&repo, &root_path.join("a*[1]"))
+            .unwrap();
+        assert_eq!(statuses.get("new.txt"), Some(&Status::WT_NEW));
+        assert_eq!(statuses.get("staged.txt"), Some(&Status::INDEX_NEW));
+    }
+
+    #[test]
+    fn test_poisoned_git_status_cache() {
+        let root_dir = TempDir::new("git_statuses").unwrap();
+        let root_path = root_dir.path().to_path_buf();
+        let repo = Repository::init(&root_path).unwrap();
+        fs::write(root_path.join("a.txt"), "").unwrap();
+
+        let git_cache = GitStatusCache::default();
+        let shared_cache = git_cache.clone();
+        let _ = std::thread::spawn(move || {
+            let _repos = shared_cache.lock();
+            panic!("Panic while the statuses are locked");
+        })
+        .join();
+        assert_eq!(
+            git_cache
+                .get_statuses(&repo, &root_path)
+                .unwrap()
+                .get("a.txt"),
+            Some(&Status::WT_NEW)
+        );
+        git_cache.invalidate();
+    }
+
+    #[test]
+    fn test_git_state() {
+        assert_eq!(GitState::from_status(Status::CURRENT), None);
+        assert_eq!(
+            GitState::from_status(Status::WT_NEW | Status::INDEX_MODIFIED),
+            Some(GitState::Staged)
+        );
+        assert_eq!(
+            GitState::from_status(Status::INDEX_NEW | Status::WT_MODIFIED | Status::IGNORED),
+            Some(GitState::Modified)
+        );
+        assert_eq!(
+            GitState::from_status(Status::CONFLICTED | Status::WT_MODIFIED),
+            Some(GitState::Conflicted)
+        );
+        assert_eq!(
+            GitState::from_status(Status::IGNORED).map(|state| state.glyph()),
+            Some("!")
+        );
+        assert_eq!(
+            GitState::from_status(Status::INDEX_NEW | Status::WT_NEW).map(|state| state.glyph()),
+            Some("A")
+        );
     }
 }
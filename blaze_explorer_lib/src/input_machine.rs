@@ -45,6 +45,24 @@ impl<T> KeyMapNode<T> {
         current_node.action = Some(action);
     }
 
+    /// Remove the action bound to the sequence, dropping any nodes left without actions.
+    /// Returns true if an action was removed.
+    pub fn remove_sequence(&mut self, sequence: &[KeyEvent]) -> bool {
+        match sequence.split_first() {
+            None => self.action.take().is_some(),
+            Some((key, rest)) => {
+                let Some(child) = self.children.get_mut(key) else {
+                    return false;
+                };
+                let removed = child.remove_sequence(rest);
+                if child.action.is_none() && child.children.is_empty() {
+                    self.children.remove(key);
+                }
+                removed
+            }
+        }
+    }
+
     pub fn get_node(&self, sequence: &[KeyEvent]) -> Option<&KeyMapNode<T>> {
         let mut current_node = self;
         for key in sequence {
@@ -55,6 +73,41 @@ impl<T> KeyMapNode<T> {
         }
         Some(current_node)
     }
+
+    pub fn has_children(&self) -> bool {
+        !self.children.is_empty()
+    }
+
+    /// Longest prefix of the sequence bound to an action, given by its length
+    pub fn get_longest_action(&self, sequence: &[KeyEvent]) -> Option<(usize, &T)> {
+        let mut longest = None;
+        let mut current_node = self;
+        for (index, key) in sequence.iter().enumerate() {
+            match current_node.children.get(key) {
+                Some(node) => current_node = node,
+                None => break,
+            }
+            if let Some(action) = &current_node.action {
+                longest = Some((index + 1, action));
+            }
+        }
+        longest
+    }
+
+    /// Sequences leading from this node to an action, paired with the action they lead to
+    pub fn get_continuations(&self) -> Vec<(Vec<KeyEvent>, &T)> {
+        let mut continuations = Vec::new();
+        for (key, child) in self.children.iter() {
+            if let Some(action) = &child.action {
+                continuations.push((vec![*key], action));
+            }
+            for (mut sequence, action) in child.get_continuations() {
+                sequence.insert(0, *key);
+                continuations.push((sequence, action));
+            }
+        }
+        continuations
+    }
 }
 #[derive(Debug, PartialEq)]
 pub enum KeyProcessingResult<T> {
@@ -145,4 +198,48 @@ mod tests {
         );
         assert_eq!(current_sequence.len(), 0);
     }
+
+    #[test]
+    fn test_remove_sequence() {
+        let mut root = KeyMapNode::new();
+        let g_event = KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE);
+        let h_event = KeyEvent::new(KeyCode::Char('h'), KeyModifiers::NONE);
+        root.add_sequence(vec![g_event, g_event], Action::AppAct(AppAction::Quit));
+        root.add_sequence(vec![g_event, h_event], Action::AppAct(AppAction::Delete));
+
+        assert!(root.remove_sequence(&[g_event, g_event]));
+        assert!(root.get_node(&[g_event, g_event]).is_none());
+        assert!(root.get_node(&[g_event, h_event]).is_some());
+        assert!(!root.remove_sequence(&[g_event, g_event]));
+
+        assert!(root.remove_sequence(&[g_event, h_event]));
+        assert!(root.get_node(&[g_event]).is_none());
+    }
+
+    #[test]
+    fn test_get_continuations() {
+        let mut root = KeyMapNode::new();
+        let g_event = KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE);
+        let h_event = KeyEvent::new(KeyCode::Char('h'), KeyModifiers::NONE);
+        root.add_sequence(vec![g_event], Action::AppAct(AppAction::Quit));
+        root.add_sequence(vec![g_event, g_event], Action::AppAct(AppAction::Delete));
+        root.add_sequence(
+            vec![g_event, h_event, h_event],
+            Action::ExplorerAct(ExplorerAction::SelectDown),
+        );
+
+        let node = root.get_node(&[g_event]).unwrap();
+        let mut continuations = node.get_continuations();
+        continuations.sort_by_key(|(sequence, _)| sequence.len());
+        assert_eq!(
+            continuations,
+            vec![
+                (vec![g_event], &Action::AppAct(AppAction::Delete)),
+                (
+                    vec![h_event, h_event],
+                    &Action::ExplorerAct(ExplorerAction::SelectDown)
+                ),
+            ]
+        );
+    }
 }
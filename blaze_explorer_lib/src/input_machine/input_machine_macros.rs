@@ -1,4 +1,3 @@
-use crate::input_machine::input_machine_helpers::{convert_str_to_events, parse_key_sequence};
 #[macro_export]
 macro_rules! insert_binding {
     ($map:expr, $mode:expr, $binding:expr, $functionality:expr) => {{
@@ -76,10 +75,11 @@ mod tests {
 
     use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
-    use super::*;
     use crate::{
         action::{Action, AppAction},
-        input_machine::permutation_set::PermutationSet,
+        input_machine::{
+            input_machine_helpers::convert_str_to_events, permutation_set::PermutationSet,
+        },
         mode::Mode,
     };
 
@@ -0,0 +1,246 @@
+// Vim-like key notation, e.g. `<C-w>v` or `<S-Tab>`, used by the keymaps, `ParseKeyStrokes`, the
+// plugin bindings and the display of pending key sequences
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Names of the keys written between angle brackets. Names are case-insensitive, the first one of
+/// each key is used for printing.
+const KEY_NAMES: [(&str, KeyCode); 21] = [
+    ("space", KeyCode::Char(' ')),
+    ("lt", KeyCode::Char('<')),
+    ("Bar", KeyCode::Char('|')),
+    ("Bslash", KeyCode::Char('\\')),
+    ("cr", KeyCode::Enter),
+    ("Enter", KeyCode::Enter),
+    ("Return", KeyCode::Enter),
+    ("Esc", KeyCode::Esc),
+    ("BS", KeyCode::Backspace),
+    ("Tab", KeyCode::Tab),
+    ("Del", KeyCode::Delete),
+    ("Delete", KeyCode::Delete),
+    ("Insert", KeyCode::Insert),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+];
+
+/// Key press as written in the key notation. The shift modifier is dropped where it is already
+/// carried by the key, so that keys typed in the terminal match the parsed ones: shifted letters
+/// are uppercase, shifted symbols are the symbols themselves (e.g. `:`) and shift-tab is a key on
+/// its own.
+pub fn normalize_key(key: KeyEvent) -> KeyEvent {
+    if !key.modifiers.contains(KeyModifiers::SHIFT) {
+        return KeyEvent::new(key.code, key.modifiers);
+    }
+    let code = match key.code {
+        KeyCode::Char(ch) => KeyCode::Char(ch.to_ascii_uppercase()),
+        KeyCode::Tab | KeyCode::BackTab => KeyCode::BackTab,
+        code => return KeyEvent::new(code, key.modifiers),
+    };
+    KeyEvent::new(code, key.modifiers - KeyModifiers::SHIFT)
+}
+
+/// Parse the notation of a single key written between angle brackets, e.g. `C-a`, `S-Tab`,
+/// `C-A-Del` or `F5`
+pub fn parse_key(notation: &str) -> Result<KeyEvent, String> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut name = notation;
+    // The last part is the key, which can be `-` itself, as in `C--`
+    while let Some((modifier, rest)) = name.split_once('-')
+        && !rest.is_empty()
+    {
+        modifiers |= match modifier {
+            "C" | "c" => KeyModifiers::CONTROL,
+            "S" | "s" => KeyModifiers::SHIFT,
+            "A" | "a" | "M" | "m" => KeyModifiers::ALT,
+            _ => return Err(format!("Invalid modifier {} in <{}>", modifier, notation)),
+        };
+        name = rest;
+    }
+    let mut chars = name.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(ch), None) => KeyCode::Char(ch),
+        _ => get_named_key(name).ok_or_else(|| format!("Invalid key <{}>", notation))?,
+    };
+    Ok(normalize_key(KeyEvent::new(code, modifiers)))
+}
+
+fn get_named_key(name: &str) -> Option<KeyCode> {
+    if let Some((_, code)) = KEY_NAMES
+        .iter()
+        .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+    {
+        return Some(*code);
+    }
+    let number = name.strip_prefix(['F', 'f'])?.parse::<u8>().ok()?;
+    match number {
+        1..=24 => Some(KeyCode::F(number)),
+        _ => None,
+    }
+}
+
+/// Parse a key sequence, e.g. `<C-w>v` or `:!git status<CR>`. Keys which are not special are
+/// written as they are, with `<lt>` standing for `<`.
+pub fn parse_keys(notation: &str) -> Result<Vec<KeyEvent>, String> {
+    parse_each_key(notation).into_iter().collect()
+}
+
+/// Parse a key sequence key by key, so that the valid keys can be used regardless of the invalid
+/// ones. An unclosed `<` ends the sequence.
+pub fn parse_each_key(notation: &str) -> Vec<Result<KeyEvent, String>> {
+    let mut keys = Vec::new();
+    let mut remainder = notation;
+    while let Some(ch) = remainder.chars().next() {
+        if ch != '<' {
+            keys.push(Ok(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE)));
+            remainder = &remainder[ch.len_utf8()..];
+            continue;
+        }
+        let Some(mut end) = remainder
+            .char_indices()
+            .skip(2)
+            .find(|(_, ch)| *ch == '>')
+            .map(|(index, _)| index)
+        else {
+            keys.push(Err(format!(
+                "Unclosed < in {}, write <lt> for the key itself",
+                notation
+            )));
+            break;
+        };
+        // `>` can be the key itself, as in `<C->>`
+        if remainder[1..end].ends_with('-') && remainder[end + 1..].starts_with('>') {
+            end += 1;
+        }
+        keys.push(parse_key(&remainder[1..end]));
+        remainder = &remainder[end + 1..];
+    }
+    keys
+}
+
+/// Notation of a single key, parsed back by `parse_key`. Keys without a notation give an empty
+/// string.
+pub fn key_to_string(key: &KeyEvent) -> String {
+    let key = normalize_key(*key);
+    let name = match key.code {
+        KeyCode::Char(' ') => "space".to_string(),
+        KeyCode::Char('<') => "lt".to_string(),
+        KeyCode::Char(ch) => ch.to_string(),
+        KeyCode::BackTab => "S-Tab".to_string(),
+        KeyCode::F(number) => format!("F{}", number),
+        code => match KEY_NAMES.iter().find(|(_, named_code)| *named_code == code) {
+            Some((name, _)) => name.to_string(),
+            None => return String::new(),
+        },
+    };
+    let mut prefix = String::new();
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        prefix.push_str("C-");
+    }
+    if key.modifiers.contains(KeyModifiers::ALT) {
+        prefix.push_str("A-");
+    }
+    if key.modifiers.contains(KeyModifiers::SHIFT) {
+        prefix.push_str("S-");
+    }
+    match (prefix.is_empty(), key.code) {
+        (true, KeyCode::Char(ch)) if ch != ' ' && ch != '<' => name,
+        _ => format!("<{}{}>", prefix, name),
+    }
+}
+
+/// Notation of a key sequence, parsed back by `parse_keys`
+pub fn keys_to_string(keys: &[KeyEvent]) -> String {
+    keys.iter().map(key_to_string).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_parse_keys() {
+        assert_eq!(
+            parse_keys("<C-a>w<S-h>:<CR>"),
+            Ok(vec![
+                key(KeyCode::Char('a'), KeyModifiers::CONTROL),
+                key(KeyCode::Char('w'), KeyModifiers::NONE),
+                key(KeyCode::Char('H'), KeyModifiers::NONE),
+                key(KeyCode::Char(':'), KeyModifiers::NONE),
+                key(KeyCode::Enter, KeyModifiers::NONE),
+            ])
+        );
+        assert_eq!(
+            parse_keys("<F12><S-Tab><PageDown><home><lt><C-->"),
+            Ok(vec![
+                key(KeyCode::F(12), KeyModifiers::NONE),
+                key(KeyCode::BackTab, KeyModifiers::NONE),
+                key(KeyCode::PageDown, KeyModifiers::NONE),
+                key(KeyCode::Home, KeyModifiers::NONE),
+                key(KeyCode::Char('<'), KeyModifiers::NONE),
+                key(KeyCode::Char('-'), KeyModifiers::CONTROL),
+            ])
+        );
+        assert_eq!(
+            parse_keys("<C-S-a><C-A-Del><S-Up><C->>"),
+            Ok(vec![
+                key(KeyCode::Char('A'), KeyModifiers::CONTROL),
+                key(KeyCode::Delete, KeyModifiers::CONTROL | KeyModifiers::ALT),
+                key(KeyCode::Up, KeyModifiers::SHIFT),
+                key(KeyCode::Char('>'), KeyModifiers::CONTROL),
+            ])
+        );
+        assert!(parse_keys("<Nope>").is_err());
+        assert!(parse_keys("<X-a>").is_err());
+        assert!(parse_keys("<F25>").is_err());
+        assert!(parse_keys("a<b").is_err());
+        assert_eq!(parse_keys(""), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn test_keys_to_string() {
+        let notation = "<C-a>w:H<cr><space><lt><F5><S-Tab><C-A-Del><S-Up><Esc>";
+        let keys = parse_keys(notation).unwrap();
+        assert_eq!(keys_to_string(&keys), notation);
+        // Keys typed in the terminal carry the shift modifier
+        assert_eq!(
+            keys_to_string(&[
+                key(KeyCode::Char('G'), KeyModifiers::SHIFT),
+                key(KeyCode::Char(':'), KeyModifiers::SHIFT),
+                key(KeyCode::BackTab, KeyModifiers::SHIFT),
+            ]),
+            "G:<S-Tab>"
+        );
+        assert_eq!(
+            key_to_string(&key(KeyCode::CapsLock, KeyModifiers::NONE)),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_normalize_key() {
+        assert_eq!(
+            normalize_key(key(KeyCode::Char('g'), KeyModifiers::SHIFT)),
+            key(KeyCode::Char('G'), KeyModifiers::NONE)
+        );
+        assert_eq!(
+            normalize_key(key(
+                KeyCode::Char('A'),
+                KeyModifiers::CONTROL | KeyModifiers::SHIFT
+            )),
+            key(KeyCode::Char('A'), KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            normalize_key(key(KeyCode::Right, KeyModifiers::SHIFT)),
+            key(KeyCode::Right, KeyModifiers::SHIFT)
+        );
+    }
+}
@@ -0,0 +1,331 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use itertools::Itertools;
+use ratatui::crossterm::event::KeyEvent;
+use serde::{Deserialize, Serialize};
+
+use crate::action::get_action_by_name;
+use crate::app_input_machine::AppInputMachine;
+use crate::key_notation::parse_keys;
+use crate::plugin::BindingsMap;
+use crate::{action::Action, mode::Mode};
+
+/// Bindings of a single mode: key sequence -> action name. A `null` action unbinds the sequence.
+pub type ModeBindings = HashMap<String, Option<String>>;
+
+/// Custom bindings of a single plugin: mode -> key sequence -> plugin functionality name.
+pub type PluginBindings = HashMap<String, HashMap<String, String>>;
+
+/// User-editable keymap, read from `keymap.json` in the config directory, e.g.
+/// ```json
+/// {
+///   "timeout": 1000,
+///   "bindings": {
+///     "normal": { "<C-p>": "JumpToStart", "x": null, "<space>gs": "ParseCommand !git status" }
+///   },
+///   "plugins": {
+///     "Telescope": { "normal": { "<space>sf": "OpenSFS" } }
+///   }
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
+#[serde(default)]
+pub struct KeymapConfig {
+    pub bindings: HashMap<String, ModeBindings>,
+    pub plugins: HashMap<String, PluginBindings>,
+    /// Milliseconds after which a pending key sequence is resolved, 0 to wait forever
+    pub timeout: Option<u64>,
+}
+
+impl KeymapConfig {
+    /// Load the keymap from the given path. A missing file results in an empty keymap.
+    pub fn try_load_from_file<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let file = File::open(path);
+        match file {
+            Ok(mut file) => {
+                let mut contents = String::new();
+                file.read_to_string(&mut contents)?;
+                let config: KeymapConfig = serde_json::from_str(&contents)?;
+                Ok(config)
+            }
+            Err(_) => Ok(KeymapConfig::default()),
+        }
+    }
+
+    /// Apply the keymap to the input machine. Invalid entries are skipped and described in the
+    /// returned list of errors.
+    pub fn apply(&self, input_machine: &mut AppInputMachine<Action>) -> Vec<String> {
+        let mut errors = Vec::new();
+        if let Some(timeout) = self.timeout {
+            input_machine.set_timeout(match timeout {
+                0 => None,
+                timeout => Some(Duration::from_millis(timeout)),
+            });
+        }
+        for (mode_name, bindings) in self.bindings.iter() {
+            let mode = match Mode::from_str(mode_name) {
+                Ok(Mode::PopUp) => {
+                    errors.push("Bindings for the PopUp mode cannot be configured".to_string());
+                    continue;
+                }
+                Ok(mode) => mode,
+                Err(e) => {
+                    errors.push(e);
+                    continue;
+                }
+            };
+            for (keys, action_name) in bindings.iter() {
+                let sequence = match parse_binding(keys) {
+                    Ok(sequence) => sequence,
+                    Err(e) => {
+                        errors.push(e);
+                        continue;
+                    }
+                };
+                match action_name {
+                    None => {
+                        if !input_machine.detach_binding(mode.clone(), &sequence) {
+                            errors.push(format!("{}: nothing bound to {}", mode, keys));
+                        }
+                    }
+                    Some(action_name) => match get_action_by_name(action_name) {
+                        Some(action) => {
+                            input_machine.attach_binding(mode.clone(), sequence, action)
+                        }
+                        None => errors.push(format!("{}: unknown action {}", mode, action_name)),
+                    },
+                }
+            }
+        }
+        errors.sort();
+        errors
+    }
+
+    /// Custom bindings configured for the plugin with the given name. Bindings referring to names
+    /// missing from the plugin's functionality map are skipped and described in the returned
+    /// warnings.
+    pub fn get_plugin_bindings(
+        &self,
+        plugin_name: &str,
+        functionality_map: &HashMap<String, Action>,
+    ) -> (BindingsMap, Vec<String>) {
+        let mut custom_bindings = BindingsMap::new();
+        let mut warnings = Vec::new();
+        let Some(plugin_bindings) = self.plugins.get(plugin_name) else {
+            return (custom_bindings, warnings);
+        };
+        for (mode_name, bindings) in plugin_bindings.iter() {
+            let mode = match Mode::from_str(mode_name) {
+                Ok(mode) => mode,
+                Err(e) => {
+                    warnings.push(format!("{}: {}", plugin_name, e));
+                    continue;
+                }
+            };
+            for (keys, functionality) in bindings.iter() {
+                if !functionality_map.contains_key(functionality) {
+                    warnings.push(format!(
+                        "{}: unknown functionality {} (available: {})",
+                        plugin_name,
+                        functionality,
+                        functionality_map.keys().sorted().join(", ")
+                    ));
+                    continue;
+                }
+                match parse_binding(keys) {
+                    Ok(sequence) => {
+                        custom_bindings.insert((mode.clone(), sequence), functionality.clone());
+                    }
+                    Err(e) => warnings.push(format!("{}: {}", plugin_name, e)),
+                }
+            }
+        }
+        warnings.sort();
+        (custom_bindings, warnings)
+    }
+}
+
+/// Parse a key sequence written in the keymap file, refusing sequences with unknown keys.
+pub fn parse_binding(keys: &str) -> Result<Vec<KeyEvent>, String> {
+    let sequence = parse_keys(keys).map_err(|e| format!("{} in {}", e, keys))?;
+    match sequence.is_empty() {
+        true => Err("Empty key sequence".to_string()),
+        false => Ok(sequence),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::crossterm::event::{KeyCode, KeyModifiers};
+
+    use crate::{
+        action::{AppAction, ExplorerAction},
+        input_machine::{
+            InputMachine, KeyProcessingResult, input_machine_helpers::convert_str_to_events,
+        },
+        testing_utils::create_custom_testing_folder,
+    };
+
+    use super::*;
+
+    fn press_keys(
+        input_machine: &mut AppInputMachine<Action>,
+        mode: &Mode,
+        keys: &str,
+    ) -> KeyProcessingResult<Action> {
+        let mut current_sequence = Vec::new();
+        let mut result = KeyProcessingResult::Invalid;
+        for key in convert_str_to_events(keys) {
+            result = input_machine.process_keys(mode, &mut current_sequence, key);
+        }
+        result
+    }
+
+    #[test]
+    fn test_parse_binding() {
+        assert_eq!(
+            parse_binding("<C-a>b"),
+            Ok(vec![
+                KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL),
+                KeyEvent::new(KeyCode::Char('b'), KeyModifiers::NONE),
+            ])
+        );
+        assert!(parse_binding("<Nope>b").is_err());
+        assert!(parse_binding("").is_err());
+    }
+
+    #[test]
+    fn test_load_keymap() {
+        let test_dir = create_custom_testing_folder(vec![]).unwrap();
+        let keymap_path = test_dir.root_dir.path().join("keymap.json");
+        let missing = KeymapConfig::try_load_from_file(&keymap_path).unwrap();
+        assert_eq!(missing, KeymapConfig::default());
+
+        std::fs::write(
+            &keymap_path,
+            r#"{"bindings": {"normal": {"<C-p>": "JumpToStart", "dd": null}}}"#,
+        )
+        .unwrap();
+        let keymap = KeymapConfig::try_load_from_file(&keymap_path).unwrap();
+        let normal = keymap.bindings.get("normal").unwrap();
+        assert_eq!(normal.get("<C-p>"), Some(&Some("JumpToStart".to_string())));
+        assert_eq!(normal.get("dd"), Some(&None));
+
+        std::fs::write(&keymap_path, "not json").unwrap();
+        assert!(KeymapConfig::try_load_from_file(&keymap_path).is_err());
+    }
+
+    #[test]
+    fn test_apply_keymap() {
+        let mut input_machine = AppInputMachine::new();
+        let mut normal = ModeBindings::new();
+        normal.insert("<C-p>".to_string(), Some("JumpToStart".to_string()));
+        normal.insert("j".to_string(), Some("SelectUp".to_string()));
+        normal.insert("d".to_string(), None);
+        let mut visual = ModeBindings::new();
+        visual.insert(
+            "<space>gs".to_string(),
+            Some("ParseCommand !git status".to_string()),
+        );
+        let mut bindings = HashMap::new();
+        bindings.insert("normal".to_string(), normal);
+        bindings.insert("Visual".to_string(), visual);
+        let keymap = KeymapConfig {
+            bindings,
+            timeout: Some(300),
+            ..Default::default()
+        };
+
+        let errors = keymap.apply(&mut input_machine);
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(
+            input_machine.get_sequence_timeout(),
+            Some(Duration::from_millis(300))
+        );
+
+        assert_eq!(
+            press_keys(&mut input_machine, &Mode::Normal, "<C-p>"),
+            KeyProcessingResult::Complete(Action::ExplorerAct(ExplorerAction::JumpToStart))
+        );
+        assert_eq!(
+            press_keys(&mut input_machine, &Mode::Normal, "j"),
+            KeyProcessingResult::Complete(Action::ExplorerAct(ExplorerAction::SelectUp))
+        );
+        assert_eq!(
+            press_keys(&mut input_machine, &Mode::Normal, "dd"),
+            KeyProcessingResult::Invalid
+        );
+        assert_eq!(
+            press_keys(&mut input_machine, &Mode::Visual, " gs"),
+            KeyProcessingResult::Complete(Action::AppAct(AppAction::ParseCommand(
+                "!git status".to_string()
+            )))
+        );
+    }
+
+    #[test]
+    fn test_get_plugin_bindings() {
+        let mut functionality_map = HashMap::new();
+        functionality_map.insert(
+            "OpenSFS".to_string(),
+            Action::AppAct(AppAction::DisplayMessage("sfs".to_string())),
+        );
+        functionality_map.insert("NextResult".to_string(), Action::Noop);
+        let keymap: KeymapConfig = serde_json::from_str(
+            r#"{"plugins": {"Telescope": {
+                "normal": {"<space>sf": "OpenSFS", "<space>sg": "OpenGrep"},
+                "popup": {"<C-n>": "NextResult", "<Nope>": "NextResult"},
+                "insert": {"a": "OpenSFS"}
+            }}}"#,
+        )
+        .unwrap();
+
+        let (bindings, warnings) = keymap.get_plugin_bindings("Telescope", &functionality_map);
+        let mut expected = BindingsMap::new();
+        expected.insert(
+            (Mode::Normal, convert_str_to_events("<space>sf")),
+            "OpenSFS".to_string(),
+        );
+        expected.insert(
+            (Mode::PopUp, convert_str_to_events("<C-n>")),
+            "NextResult".to_string(),
+        );
+        assert_eq!(bindings, expected);
+        assert_eq!(warnings.len(), 3, "{:?}", warnings);
+        assert!(
+            warnings.contains(
+                &"Telescope: unknown functionality OpenGrep (available: NextResult, OpenSFS)"
+                    .to_string()
+            )
+        );
+
+        let (bindings, warnings) = keymap.get_plugin_bindings("Flash", &functionality_map);
+        assert!(bindings.is_empty());
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_apply_invalid_keymap() {
+        let mut input_machine = AppInputMachine::new();
+        let mut normal = ModeBindings::new();
+        normal.insert("<Nope>".to_string(), Some("JumpToStart".to_string()));
+        normal.insert("x".to_string(), Some("NotAnAction".to_string()));
+        normal.insert("zz".to_string(), None);
+        let mut bindings = HashMap::new();
+        bindings.insert("normal".to_string(), normal);
+        bindings.insert("insert".to_string(), ModeBindings::new());
+        bindings.insert("popup".to_string(), ModeBindings::new());
+        let keymap = KeymapConfig {
+            bindings,
+            ..Default::default()
+        };
+
+        let errors = keymap.apply(&mut input_machine);
+        assert_eq!(errors.len(), 5, "{:?}", errors);
+    }
+}
@@ -5,19 +5,28 @@
 pub mod action;
 pub mod app;
 pub mod app_input_machine;
+pub mod clipboard;
 pub mod command;
+pub mod command_line_history;
+pub mod completion;
 pub mod components;
 pub mod core_features;
+pub mod directory_watcher;
+pub mod ex_commands;
 pub mod explorer_helpers;
 pub mod function_helpers;
 pub mod git_helpers;
 pub mod history_stack;
 pub mod input_machine;
+pub mod key_notation;
+pub mod keymap_config;
 pub mod line_entry;
 pub mod logging;
+pub mod macro_registers;
 pub mod mode;
 pub mod plugin;
 pub mod query;
+pub mod search_pattern;
 pub mod testing_utils;
 pub mod themes;
 pub mod tools;
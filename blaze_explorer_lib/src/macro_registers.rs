@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::Path;
+
+use ratatui::crossterm::event::KeyEvent;
+use serde::{Deserialize, Serialize};
+
+/// Macro being recorded with `q{register}`
+#[derive(Debug, PartialEq, Clone)]
+struct MacroRecording {
+    register: char,
+    keys: Vec<KeyEvent>,
+    /// Index of the first key of the last key sequence, so that the sequence stopping the
+    /// recording can be dropped
+    sequence_start: usize,
+}
+
+/// Keystroke macros recorded with `q{register}` and replayed with `@{register}`. Registers are
+/// stored in `macros.json` in the data directory, so that they survive restarts.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
+#[serde(default)]
+pub struct MacroRegisters {
+    pub registers: HashMap<char, Vec<KeyEvent>>,
+    #[serde(skip)]
+    recording: Option<MacroRecording>,
+    #[serde(skip)]
+    last_replayed: Option<char>,
+}
+
+impl MacroRegisters {
+    /// Load the registers from the given path. A missing file results in empty registers.
+    pub fn try_load_from_file<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let file = File::open(path);
+        match file {
+            Ok(mut file) => {
+                let mut contents = String::new();
+                file.read_to_string(&mut contents)?;
+                let registers: MacroRegisters = serde_json::from_str(&contents)?;
+                Ok(registers)
+            }
+            Err(_) => Ok(MacroRegisters::default()),
+        }
+    }
+
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(path)?;
+        file.write_all(json.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn start_recording(&mut self, register: char) {
+        self.recording = Some(MacroRecording {
+            register,
+            keys: Vec::new(),
+            sequence_start: 0,
+        });
+    }
+
+    /// Register the macro is being recorded to, if any
+    pub fn get_recording_register(&self) -> Option<char> {
+        self.recording.as_ref().map(|recording| recording.register)
+    }
+
+    /// Record a key processed by the app. `starts_sequence` tells whether the key is the first
+    /// one of a key sequence.
+    pub fn record_key(&mut self, key: KeyEvent, starts_sequence: bool) {
+        if let Some(recording) = &mut self.recording {
+            if starts_sequence {
+                recording.sequence_start = recording.keys.len();
+            }
+            recording.keys.push(key);
+        }
+    }
+
+    /// Stop recording and store the macro, leaving out the key sequence which stopped the
+    /// recording. Returns the register the macro was stored in.
+    pub fn stop_recording(&mut self) -> Option<char> {
+        let mut recording = self.recording.take()?;
+        recording.keys.truncate(recording.sequence_start);
+        self.registers.insert(recording.register, recording.keys);
+        Some(recording.register)
+    }
+
+    /// Keys of the macro in the register, which becomes the one replayed by `@@`
+    pub fn replay(&mut self, register: char) -> Option<Vec<KeyEvent>> {
+        let keys = self.registers.get(&register)?.clone();
+        self.last_replayed = Some(register);
+        Some(keys)
+    }
+
+    /// Keys of the macro replayed last, i.e. `@@`
+    pub fn replay_last(&mut self) -> Option<Vec<KeyEvent>> {
+        self.replay(self.last_replayed?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::input_machine::input_machine_helpers::convert_str_to_events;
+    use crate::testing_utils::create_custom_testing_folder;
+
+    use super::*;
+
+    fn record_keys(registers: &mut MacroRegisters, keys: &str) {
+        for key in convert_str_to_events(keys) {
+            registers.record_key(key, true);
+        }
+    }
+
+    #[test]
+    fn test_record_macro() {
+        let mut registers = MacroRegisters::default();
+        // Nothing is recorded before starting
+        record_keys(&mut registers, "j");
+        registers.start_recording('a');
+        assert_eq!(registers.get_recording_register(), Some('a'));
+        record_keys(&mut registers, "jdd");
+        // The sequence stopping the recording is left out
+        registers.record_key(convert_str_to_events("q")[0], true);
+        assert_eq!(registers.stop_recording(), Some('a'));
+        assert_eq!(registers.get_recording_register(), None);
+        assert_eq!(
+            registers.registers.get(&'a'),
+            Some(&convert_str_to_events("jdd"))
+        );
+        assert_eq!(registers.stop_recording(), None);
+    }
+
+    #[test]
+    fn test_replay_macro() {
+        let mut registers = MacroRegisters::default();
+        registers.registers.insert('a', convert_str_to_events("jj"));
+        assert_eq!(registers.replay_last(), None);
+        assert_eq!(registers.replay('b'), None);
+        assert_eq!(registers.replay('a'), Some(convert_str_to_events("jj")));
+        assert_eq!(registers.replay_last(), Some(convert_str_to_events("jj")));
+    }
+
+    #[test]
+    fn test_save_load_macros() {
+        let test_dir = create_custom_testing_folder(vec![]).unwrap();
+        let macros_path = test_dir.root_dir.path().join("macros.json");
+        let missing = MacroRegisters::try_load_from_file(&macros_path).unwrap();
+        assert_eq!(missing, MacroRegisters::default());
+
+        let mut registers = MacroRegisters::default();
+        registers
+            .registers
+            .insert('a', convert_str_to_events("<C-w>v<space>ff"));
+        registers.save_to_file(&macros_path).unwrap();
+        let loaded = MacroRegisters::try_load_from_file(&macros_path).unwrap();
+        assert_eq!(loaded.registers, registers.registers);
+    }
+}
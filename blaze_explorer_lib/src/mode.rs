@@ -1,9 +1,10 @@
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Mode {
     Normal,
     Search,
+    Filter,
     Command,
     PopUp,
     Visual,
@@ -14,9 +15,26 @@ impl Display for Mode {
         match self {
             Mode::Normal => write!(f, "Normal"),
             Mode::Search => write!(f, "Search"),
+            Mode::Filter => write!(f, "Filter"),
             Mode::Command => write!(f, "Command"),
             Mode::PopUp => write!(f, "PopUp"),
             Mode::Visual => write!(f, "Visual"),
         }
     }
 }
+
+impl FromStr for Mode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "normal" => Ok(Mode::Normal),
+            "search" => Ok(Mode::Search),
+            "filter" => Ok(Mode::Filter),
+            "command" => Ok(Mode::Command),
+            "popup" => Ok(Mode::PopUp),
+            "visual" => Ok(Mode::Visual),
+            other => Err(format!("Unknown mode: {}", other)),
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::ffi::CStr;
+
+/// Version of the plugin interface. It needs to be bumped whenever the [crate::plugin::Plugin]
+/// trait or the types passed between the app and the plugins change.
+pub const PLUGIN_API_VERSION: u32 = 1;
+
+/// Name of the symbol returning the API version the plugin was built for
+pub const API_VERSION_SYMBOL: &[u8] = b"blaze_plugin_api_version";
+/// Name of the symbol returning the fingerprint of the build the plugin was compiled with
+pub const BUILD_FINGERPRINT_SYMBOL: &[u8] = b"blaze_plugin_build_fingerprint";
+
+/// Version of the library and of the compiler used to build it. Plugins exchange Rust types with
+/// the app, so both need to match for the plugin to be loaded safely.
+pub const BUILD_FINGERPRINT: &CStr = match CStr::from_bytes_with_nul(
+    concat!(
+        "blaze_explorer_lib ",
+        env!("CARGO_PKG_VERSION"),
+        ", ",
+        env!("BLAZE_RUSTC_VERSION"),
+        "\0"
+    )
+    .as_bytes(),
+) {
+    Ok(fingerprint) => fingerprint,
+    Err(_) => panic!("Build fingerprint must not contain nul bytes"),
+};
+
+pub fn build_fingerprint() -> &'static str {
+    BUILD_FINGERPRINT.to_str().unwrap_or_default()
+}
+
+/// Check whether a plugin reporting the given API version and build fingerprint can be loaded.
+pub fn check_compatibility(api_version: u32, fingerprint: &str) -> Result<(), String> {
+    if api_version == PLUGIN_API_VERSION && fingerprint == build_fingerprint() {
+        return Ok(());
+    }
+    Err(format!(
+        "incompatible build - expected API version {} ({}), found API version {} ({}). Rebuild the plugin against the same blaze_explorer_lib and rustc",
+        PLUGIN_API_VERSION,
+        build_fingerprint(),
+        api_version,
+        fingerprint
+    ))
+}
+
+/// Export the symbols the app checks before loading the plugin. Needs to be called once in the
+/// plugin's library, next to the `get_plugin` function.
+#[macro_export]
+macro_rules! export_plugin_abi {
+    () => {
+        #[unsafe(no_mangle)]
+        pub extern "C" fn blaze_plugin_api_version() -> u32 {
+            $crate::plugin::plugin_abi::PLUGIN_API_VERSION
+        }
+
+        #[unsafe(no_mangle)]
+        pub extern "C" fn blaze_plugin_build_fingerprint() -> *const ::std::ffi::c_char {
+            $crate::plugin::plugin_abi::BUILD_FINGERPRINT.as_ptr()
+        }
+    };
+}
+pub use export_plugin_abi;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod exported {
+        crate::export_plugin_abi!();
+    }
+
+    #[test]
+    fn test_build_fingerprint() {
+        assert!(
+            build_fingerprint()
+                .starts_with(concat!("blaze_explorer_lib ", env!("CARGO_PKG_VERSION")))
+        );
+        assert!(build_fingerprint().contains("rustc"));
+    }
+
+    #[test]
+    fn test_check_compatibility() {
+        assert!(check_compatibility(PLUGIN_API_VERSION, build_fingerprint()).is_ok());
+        let error = check_compatibility(
+            PLUGIN_API_VERSION + 1,
+            "blaze_explorer_lib 0.1.0, rustc 1.0.0",
+        )
+        .unwrap_err();
+        assert!(error.contains(&format!("expected API version {}", PLUGIN_API_VERSION)));
+        assert!(error.contains(build_fingerprint()));
+        assert!(error.contains("rustc 1.0.0"));
+        assert!(check_compatibility(PLUGIN_API_VERSION, "blaze_explorer_lib 0.1.0").is_err());
+    }
+
+    #[test]
+    fn test_export_plugin_abi() {
+        assert_eq!(exported::blaze_plugin_api_version(), PLUGIN_API_VERSION);
+        let fingerprint = unsafe { CStr::from_ptr(exported::blaze_plugin_build_fingerprint()) };
+        assert!(
+            check_compatibility(
+                exported::blaze_plugin_api_version(),
+                &fingerprint.to_string_lossy()
+            )
+            .is_ok()
+        );
+    }
+}
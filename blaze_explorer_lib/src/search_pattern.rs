@@ -0,0 +1,315 @@
+// Patterns typed in search and filter mode. A search query is a literal, a regex or a glob
+// depending on the `search` option, unless it starts with `re:` or `glob:`, while a filter query
+// is matched as a substring or fuzzily depending on the `filter` option. With `smartcase` on, both
+// ignore case unless the query contains an uppercase character.
+use std::ops::Range;
+
+use regex::Regex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchKind {
+    #[default]
+    Literal,
+    Regex,
+    Glob,
+}
+
+impl SearchKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "literal" => Some(SearchKind::Literal),
+            "regex" => Some(SearchKind::Regex),
+            "glob" => Some(SearchKind::Glob),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SearchKind::Literal => "literal",
+            SearchKind::Regex => "regex",
+            SearchKind::Glob => "glob",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FilterKind {
+    #[default]
+    Substring,
+    /// The characters of the query appear in order, not necessarily next to each other
+    Fuzzy,
+}
+
+impl FilterKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "substring" => Some(FilterKind::Substring),
+            "fuzzy" => Some(FilterKind::Fuzzy),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FilterKind::Substring => "substring",
+            FilterKind::Fuzzy => "fuzzy",
+        }
+    }
+}
+
+/// Options of search and filter mode, set with `:set search=...`, `:set filter=...` and
+/// `:set smartcase=...`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchSettings {
+    pub kind: SearchKind,
+    pub filter_kind: FilterKind,
+    pub smart_case: bool,
+}
+
+impl Default for SearchSettings {
+    fn default() -> Self {
+        Self {
+            kind: SearchKind::Literal,
+            filter_kind: FilterKind::Substring,
+            smart_case: true,
+        }
+    }
+}
+
+/// Whether a query matches regardless of case
+fn ignores_case(query: &str, settings: &SearchSettings) -> bool {
+    settings.smart_case && !query.chars().any(char::is_uppercase)
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchPattern {
+    query: String,
+    kind: SearchKind,
+    /// None if the query is not a valid regex, in which case nothing matches
+    regex: Option<Regex>,
+}
+
+impl PartialEq for SearchPattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.query == other.query
+            && self.kind == other.kind
+            && self.regex.as_ref().map(Regex::as_str) == other.regex.as_ref().map(Regex::as_str)
+    }
+}
+
+impl SearchPattern {
+    pub fn new(query: &str, settings: &SearchSettings) -> Self {
+        let (kind, pattern) = if let Some(pattern) = query.strip_prefix("re:") {
+            (SearchKind::Regex, pattern)
+        } else if let Some(pattern) = query.strip_prefix("glob:") {
+            (SearchKind::Glob, pattern)
+        } else {
+            (settings.kind, query)
+        };
+        let expression = match kind {
+            SearchKind::Literal => regex::escape(pattern),
+            SearchKind::Regex => pattern.to_string(),
+            SearchKind::Glob => glob_to_regex(pattern),
+        };
+        let expression = match ignores_case(pattern, settings) {
+            true => format!("(?i){}", expression),
+            false => expression,
+        };
+        Self {
+            query: query.to_string(),
+            kind,
+            regex: Regex::new(&expression).ok(),
+        }
+    }
+
+    /// Query as typed, including its prefix
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn kind(&self) -> SearchKind {
+        self.kind
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.regex.is_some()
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.regex
+            .as_ref()
+            .is_some_and(|regex| regex.is_match(text))
+    }
+
+    /// Byte ranges of the non-empty matches in the text
+    pub fn find_spans(&self, text: &str) -> Vec<Range<usize>> {
+        match &self.regex {
+            Some(regex) => regex
+                .find_iter(text)
+                .map(|found| found.range())
+                .filter(|range| !range.is_empty())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Query narrowing down the rows of a table
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterPattern {
+    query: String,
+    kind: FilterKind,
+    ignore_case: bool,
+}
+
+impl FilterPattern {
+    pub fn new(query: &str, settings: &SearchSettings) -> Self {
+        Self {
+            query: query.to_string(),
+            kind: settings.filter_kind,
+            ignore_case: ignores_case(query, settings),
+        }
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        let (query, text) = match self.ignore_case {
+            true => (self.query.to_lowercase(), text.to_lowercase()),
+            false => (self.query.clone(), text.to_string()),
+        };
+        match self.kind {
+            FilterKind::Substring => text.contains(&query),
+            FilterKind::Fuzzy => {
+                let mut text_chars = text.chars();
+                query
+                    .chars()
+                    .all(|query_ch| text_chars.any(|text_ch| text_ch == query_ch))
+            }
+        }
+    }
+}
+
+/// Translate a glob matching the whole filename into a regex. `*` matches any sequence, `?` any
+/// character and `[...]` (or `[!...]`) a set of characters.
+fn glob_to_regex(glob: &str) -> String {
+    let mut expression = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '*' => expression.push_str(".*"),
+            '?' => expression.push('.'),
+            '[' => {
+                let mut class = String::new();
+                let mut closed = false;
+                if chars.next_if_eq(&'!').is_some() {
+                    class.push('^');
+                }
+                for class_ch in chars.by_ref() {
+                    if class_ch == ']' {
+                        closed = true;
+                        break;
+                    }
+                    if class_ch == '\\' || class_ch == '[' {
+                        class.push('\\');
+                    }
+                    class.push(class_ch);
+                }
+                match closed {
+                    true => expression.push_str(&format!("[{}]", class)),
+                    // An unclosed bracket is taken literally
+                    false => expression.push_str(&regex::escape(&format!("[{}", class))),
+                }
+            }
+            _ => expression.push_str(&regex::escape(&ch.to_string())),
+        }
+    }
+    expression.push('$');
+    expression
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_smart_case() {
+        let settings = SearchSettings::default();
+        let lowercase = SearchPattern::new("read", &settings);
+        assert!(lowercase.is_match("README.md"));
+        assert!(lowercase.is_match("readme.md"));
+        let uppercase = SearchPattern::new("Read", &settings);
+        assert!(!uppercase.is_match("README.md"));
+        assert!(uppercase.is_match("Readme.md"));
+
+        let case_sensitive = SearchSettings {
+            smart_case: false,
+            ..settings
+        };
+        assert!(!SearchPattern::new("read", &case_sensitive).is_match("README.md"));
+    }
+
+    #[test]
+    fn test_search_kinds() {
+        let settings = SearchSettings::default();
+        let literal = SearchPattern::new("a.c", &settings);
+        assert_eq!(literal.kind(), SearchKind::Literal);
+        assert!(literal.is_match("a.csv"));
+        assert!(!literal.is_match("abc"));
+
+        let regex = SearchPattern::new("re:^\\d+_", &settings);
+        assert_eq!(regex.kind(), SearchKind::Regex);
+        assert!(regex.is_match("01_intro.md"));
+        assert!(!regex.is_match("intro_01.md"));
+
+        let glob = SearchPattern::new("glob:*.t?ml", &settings);
+        assert_eq!(glob.kind(), SearchKind::Glob);
+        assert!(glob.is_match("Cargo.toml"));
+        assert!(!glob.is_match("Cargo.toml.bak"));
+        assert!(SearchPattern::new("glob:[!a-c]*", &settings).is_match("docs"));
+        assert!(!SearchPattern::new("glob:[!a-c]*", &settings).is_match("build"));
+
+        let default_regex = SearchSettings {
+            kind: SearchKind::Regex,
+            ..settings
+        };
+        assert!(SearchPattern::new("a.c", &default_regex).is_match("abc"));
+
+        let invalid = SearchPattern::new("re:(", &settings);
+        assert!(!invalid.is_valid());
+        assert!(!invalid.is_match("("));
+    }
+
+    #[test]
+    fn test_filter_pattern() {
+        let settings = SearchSettings::default();
+        let substring = FilterPattern::new("car", &settings);
+        assert!(substring.is_match("Cargo.toml"));
+        assert!(!substring.is_match("crate.rs"));
+        assert!(!FilterPattern::new("Car", &settings).is_match("cargo.lock"));
+
+        let fuzzy = SearchSettings {
+            filter_kind: FilterKind::Fuzzy,
+            ..settings
+        };
+        let pattern = FilterPattern::new("ctml", &fuzzy);
+        assert!(pattern.is_match("Cargo.toml"));
+        assert!(!pattern.is_match("Cargo.lock"));
+        assert!(FilterPattern::new("", &fuzzy).is_match("anything"));
+    }
+
+    #[test]
+    fn test_find_spans() {
+        let settings = SearchSettings::default();
+        let pattern = SearchPattern::new("re:o+", &settings);
+        assert_eq!(pattern.find_spans("foo bo"), vec![1..3, 5..6]);
+        assert!(
+            SearchPattern::new("", &settings)
+                .find_spans("foo")
+                .is_empty()
+        );
+    }
+}